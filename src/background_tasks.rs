@@ -0,0 +1,217 @@
+//! The tasks that run alongside the commands: delivering reminders, sending digests and catch-up summaries,
+//! applying the retention policy and serving health checks and metrics.
+//!
+//! [`init`] opens the database and returns the [`Data`] for the framework along with the [`BackgroundTasks`],
+//! which are started from the framework's setup once the bot is connected to Discord.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+    sync::Arc,
+    time::Duration,
+};
+
+use poise::serenity_prelude as serenity;
+use tokio::{
+    sync::{mpsc, Mutex},
+    task::JoinHandle,
+};
+use tokio_rusqlite::Connection;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    catch_up,
+    config::Config,
+    database::{
+        create_all_tables_if_nonexistent, message_encryption::attach_message_keyring,
+        reminder::get_all_reminders,
+    },
+    digest::{self, PaginatedDigests},
+    metrics::Metrics,
+    models::reminder::PersistedReminder,
+    retention, scheduler, shutdown, Data, Error,
+};
+
+/// Everything [`init`] prepared for the tasks, until they're started
+pub struct BackgroundTasks {
+    db_connection: Arc<Mutex<Connection>>,
+    config: Config,
+    metrics: Metrics,
+    paginated_digests: PaginatedDigests,
+    held_catch_up_summaries: catch_up::HeldCatchUpSummaries,
+    reminders: BinaryHeap<Reverse<PersistedReminder>>,
+    missed_reminders_by_user: BTreeMap<u64, Vec<PersistedReminder>>,
+    rx: mpsc::Receiver<PersistedReminder>,
+    reschedule_tx: mpsc::Sender<PersistedReminder>,
+    cancel_user_reminders_rx: mpsc::Receiver<u64>,
+    shutdown: CancellationToken,
+}
+
+/// The started [`BackgroundTasks`], to be shut down once the client has stopped
+pub struct RunningTasks {
+    db_connection: Arc<Mutex<Connection>>,
+    scheduler: JoinHandle<Result<(), Error>>,
+    /// Everything else, which stops on its own once `shutdown` is cancelled
+    tasks: Vec<JoinHandle<()>>,
+    shutdown: CancellationToken,
+    shutdown_timeout: Duration,
+}
+
+/// Opens the database (with the configured message keyring attached), brings its tables up to date
+/// and loads the reminders, setting aside the ones that were missed while the bot was offline.
+pub async fn init(config: Config) -> Result<(Data, BackgroundTasks), Error> {
+    let db_connection = Arc::new(Mutex::new(Connection::open(&config.database.path).await?));
+    if let Some(keyring) = config.load_message_keyring()? {
+        tracing::info!(
+            key_id = keyring.current_key_id(),
+            "Encrypting stored messages"
+        );
+        attach_message_keyring(&db_connection, keyring).await?;
+    }
+    create_all_tables_if_nonexistent(&db_connection).await?;
+
+    let (missed_reminders_by_user, reminders) = catch_up::partition_missed_reminders(
+        get_all_reminders(&db_connection).await?,
+        &chrono::Utc::now(),
+        &catch_up::CatchUpOptions::from(&config.delivery),
+    );
+
+    let (tx, rx) = mpsc::channel(config.delivery.queue_capacity);
+    let (cancel_user_reminders_tx, cancel_user_reminders_rx) =
+        mpsc::channel(config.delivery.queue_capacity);
+    let metrics = Metrics::new();
+    let paginated_digests = PaginatedDigests::default();
+    let held_catch_up_summaries = catch_up::HeldCatchUpSummaries::default();
+
+    let data = Data::new(
+        db_connection.clone(),
+        tx.clone(),
+        cancel_user_reminders_tx,
+        config.clone(),
+        metrics.clone(),
+        paginated_digests.clone(),
+        held_catch_up_summaries.clone(),
+    );
+    let background_tasks = BackgroundTasks {
+        db_connection,
+        config,
+        metrics,
+        paginated_digests,
+        held_catch_up_summaries,
+        reminders: BinaryHeap::from(reminders.into_iter().map(Reverse).collect::<Vec<_>>()),
+        missed_reminders_by_user,
+        rx,
+        reschedule_tx: tx,
+        cancel_user_reminders_rx,
+        shutdown: CancellationToken::new(),
+    };
+
+    Ok((data, background_tasks))
+}
+
+impl BackgroundTasks {
+    /// Cancelling it stops the tasks, e.g. from [`shutdown::shut_down_on_signal`]
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Takes a whole [`serenity::Context`] because digests listen for presses on their page buttons
+    pub fn start(
+        self,
+        ctx: &serenity::Context,
+        shard_manager: Arc<serenity::ShardManager>,
+    ) -> RunningTasks {
+        let mut tasks = Vec::new();
+
+        let digests = digest::send_digests(
+            ctx.clone(),
+            self.db_connection.clone(),
+            self.paginated_digests,
+            self.shutdown.clone(),
+        );
+        tasks.push(tokio::spawn(async move {
+            if let Err(err) = digests.await {
+                tracing::error!(error = %err, "Stopped sending digests");
+            }
+        }));
+
+        tasks.push(tokio::spawn(catch_up::send_catch_up_summaries(
+            ctx.http.clone(),
+            self.db_connection.clone(),
+            self.missed_reminders_by_user,
+            self.held_catch_up_summaries,
+            self.shutdown.clone(),
+        )));
+
+        tasks.push(tokio::spawn(retention::run_retention_maintenance(
+            self.db_connection.clone(),
+            retention::RetentionPolicy::from(&self.config.retention),
+            self.shutdown.clone(),
+        )));
+
+        #[cfg(feature = "metrics")]
+        if let Some(address) = self.config.metrics.address {
+            let health_checks = crate::metrics::server::HealthChecks {
+                shard_manager,
+                db_connection: self.db_connection.clone(),
+                scheduler_tx: self.reschedule_tx.clone(),
+                metrics: self.metrics.clone(),
+            };
+            let server =
+                crate::metrics::server::serve(address, health_checks, self.shutdown.clone());
+            tasks.push(tokio::spawn(async move {
+                if let Err(err) = server.await {
+                    tracing::error!(error = %err, "Stopped serving health checks and metrics");
+                }
+            }));
+        }
+        #[cfg(not(feature = "metrics"))]
+        {
+            let _ = shard_manager;
+            if self.config.metrics.address.is_some() {
+                tracing::warn!(
+                    "`metrics.address` is set, but the bot was built without the `metrics` feature"
+                );
+            }
+        }
+
+        let delivery_workers = scheduler::DeliveryWorkers::spawn(
+            self.config.delivery.worker_count,
+            ctx.http.clone(),
+            self.db_connection.clone(),
+            self.reschedule_tx,
+            self.metrics,
+            self.shutdown.clone(),
+        );
+        let scheduler = tokio::spawn(scheduler::send_reminders(
+            self.reminders,
+            self.rx,
+            self.cancel_user_reminders_rx,
+            delivery_workers,
+            self.config.logging.include_message_content,
+            self.shutdown.clone(),
+        ));
+
+        RunningTasks {
+            db_connection: self.db_connection,
+            scheduler,
+            tasks,
+            shutdown: self.shutdown,
+            shutdown_timeout: Duration::from_secs(self.config.shutdown.timeout_seconds),
+        }
+    }
+}
+
+impl RunningTasks {
+    /// See [`shutdown::finish_shutdown`]
+    pub async fn shut_down(self) {
+        shutdown::finish_shutdown(
+            self.shutdown,
+            self.scheduler,
+            self.tasks,
+            &self.db_connection,
+            self.shutdown_timeout,
+        )
+        .await;
+    }
+}
@@ -0,0 +1,331 @@
+//! Finding one of a user's bookmarks from a few typed characters, for commands that act on a single bookmark
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use poise::serenity_prelude as serenity;
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+use crate::{
+    database::bookmark::get_bookmarks_for_user, models::bookmark::PersistedBookmarkedMessage,
+};
+
+/// Discord rejects autocomplete responses with more than 25 choices
+pub const MAX_BOOKMARK_SEARCH_RESULTS: usize = 25;
+
+/// How long [`BookmarkSearchCache`] keeps a user's bookmarks. Every keystroke asks for new choices,
+/// so this only has to outlast typing one search.
+const BOOKMARK_SEARCH_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Bookmarks are loaded this many at a time, so users with lots of them don't hold up the database
+const BOOKMARK_SEARCH_PAGE_SIZE: u64 = 500;
+
+/// Each user's bookmarks as of their last search, so typing a search doesn't load (and decrypt)
+/// all of them again for every keystroke. Bookmarks made in the meantime only show up once it expires.
+#[derive(Clone, Default)]
+pub struct BookmarkSearchCache(Arc<std::sync::Mutex<HashMap<u64, CachedBookmarks>>>);
+
+struct CachedBookmarks {
+    loaded_at: Instant,
+    bookmarks: Arc<Vec<PersistedBookmarkedMessage>>,
+}
+
+impl BookmarkSearchCache {
+    /// All of the user's bookmarks, newest first, loading them if they aren't cached or have expired
+    pub async fn get_bookmarks(
+        &self,
+        db_connection: &Mutex<Connection>,
+        user_id: u64,
+    ) -> Result<Arc<Vec<PersistedBookmarkedMessage>>, tokio_rusqlite::Error> {
+        if let Some(cached) = self.lock().get(&user_id) {
+            if cached.loaded_at.elapsed() < BOOKMARK_SEARCH_CACHE_TTL {
+                return Ok(cached.bookmarks.clone());
+            }
+        }
+
+        let mut bookmarks = Vec::new();
+        loop {
+            let page = get_bookmarks_for_user(
+                db_connection,
+                user_id,
+                BOOKMARK_SEARCH_PAGE_SIZE,
+                bookmarks.len() as u64,
+                false,
+            )
+            .await?;
+            let is_last_page = (page.len() as u64) < BOOKMARK_SEARCH_PAGE_SIZE;
+            bookmarks.extend(page);
+            if is_last_page {
+                break;
+            }
+        }
+        let bookmarks = Arc::new(bookmarks);
+
+        let mut cache = self.lock();
+        // Dropping the expired entries here keeps the cache from holding on to every user who ever searched
+        cache.retain(|_, cached| cached.loaded_at.elapsed() < BOOKMARK_SEARCH_CACHE_TTL);
+        cache.insert(
+            user_id,
+            CachedBookmarks {
+                loaded_at: Instant::now(),
+                bookmarks: bookmarks.clone(),
+            },
+        );
+        Ok(bookmarks)
+    }
+
+    /// Called when the user's bookmarks are deleted, so they stop showing up as choices
+    pub fn invalidate(&self, user_id: u64) {
+        self.lock().remove(&user_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, CachedBookmarks>> {
+        self.0
+            .lock()
+            .expect("Bookmark search cache lock shouldn't be poisoned")
+    }
+}
+
+/// Matches are ranked in tiers, so that e.g. any match at the start of a word beats any match in the middle of one
+const PREFIX_MATCH_SCORE: u32 = 4000;
+const WORD_START_MATCH_SCORE: u32 = 3000;
+const SUBSTRING_MATCH_SCORE: u32 = 2000;
+const SCATTERED_MATCH_SCORE: u32 = 1000;
+/// The most a match's position (or spread, for scattered matches) can lower its score, keeping it within its tier
+const MAX_MATCH_PENALTY: usize = 999;
+
+/// Ranks `bookmarks` by how well `query` matches their content, author or channel name, best first, keeping at most
+/// [`MAX_BOOKMARK_SEARCH_RESULTS`]. Equally good matches keep their order in `bookmarks`. An empty query matches everything.
+pub fn search_bookmarks<'a>(
+    bookmarks: &'a [PersistedBookmarkedMessage],
+    query: &str,
+    get_channel_name: impl Fn(&serenity::Message) -> Option<String>,
+) -> Vec<&'a PersistedBookmarkedMessage> {
+    let query = query.trim().to_lowercase();
+
+    let mut matches = bookmarks
+        .iter()
+        .filter_map(|bookmark| {
+            let message = bookmark.message();
+            let channel_name = get_channel_name(message);
+            [
+                Some(message.content.as_str()),
+                Some(message.author.name.as_str()),
+                message.author.global_name.as_deref(),
+                channel_name.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .filter_map(|text| get_match_score(&query, text))
+            .max()
+            .map(|score| (score, bookmark))
+        })
+        .collect::<Vec<_>>();
+    // Stable, so ties stay in their original order
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    matches
+        .into_iter()
+        .take(MAX_BOOKMARK_SEARCH_RESULTS)
+        .map(|(_, bookmark)| bookmark)
+        .collect()
+}
+
+/// Within a tier, earlier (and for scattered matches, less spread out) matches score higher.
+/// `query` must already be lowercase. Returns [`None`] if `text` doesn't contain all of the query's characters in order.
+fn get_match_score(query: &str, text: &str) -> Option<u32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text = text.to_lowercase();
+
+    if let Some(position) = text.find(query) {
+        let is_word_start = text[..position]
+            .chars()
+            .next_back()
+            .is_none_or(|previous| !previous.is_alphanumeric());
+        let tier_score = match (position, is_word_start) {
+            (0, _) => PREFIX_MATCH_SCORE,
+            (_, true) => WORD_START_MATCH_SCORE,
+            (_, false) => SUBSTRING_MATCH_SCORE,
+        };
+        return Some(tier_score - get_penalty(text[..position].chars().count()));
+    }
+
+    // Every character of the query appears in order, e.g. "mtg nts" in "meeting notes"
+    let text_chars = text.chars().collect::<Vec<_>>();
+    let mut next_index = 0;
+    let mut first_index = None;
+    for query_char in query.chars() {
+        let index = next_index
+            + text_chars[next_index..]
+                .iter()
+                .position(|c| *c == query_char)?;
+        first_index.get_or_insert(index);
+        next_index = index + 1;
+    }
+    let first_index = first_index?;
+    let spread = next_index - first_index - query.chars().count();
+    Some(SCATTERED_MATCH_SCORE - get_penalty(first_index + spread))
+}
+
+fn get_penalty(amount: usize) -> u32 {
+    // Can't truncate, since it's at most MAX_MATCH_PENALTY
+    amount.min(MAX_MATCH_PENALTY) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::bookmark::BookmarkedMessage,
+        test_fixtures::{get_test_bookmark, get_test_db_connection},
+    };
+
+    fn get_bookmark(
+        pk: i64,
+        content: &str,
+        author: &str,
+        channel_id: u64,
+    ) -> PersistedBookmarkedMessage {
+        let mut message = serenity::Message::default();
+        message.content = content.to_string();
+        message.author.name = author.to_string();
+        message.channel_id = channel_id.into();
+        get_test_bookmark(pk, message)
+    }
+
+    fn get_channel_name(message: &serenity::Message) -> Option<String> {
+        match message.channel_id.get() {
+            1 => Some("general".to_string()),
+            2 => Some("release-notes".to_string()),
+            _ => None,
+        }
+    }
+
+    fn search(bookmarks: &[PersistedBookmarkedMessage], query: &str) -> Vec<i64> {
+        search_bookmarks(bookmarks, query, get_channel_name)
+            .into_iter()
+            .map(PersistedBookmarkedMessage::pk)
+            .collect()
+    }
+
+    #[test]
+    fn test_get_match_score_tiers() {
+        let prefix = get_match_score("meet", "Meeting notes").unwrap();
+        let word_start = get_match_score("notes", "Meeting notes").unwrap();
+        let substring = get_match_score("ting", "Meeting notes").unwrap();
+        let scattered = get_match_score("mtg", "Meeting notes").unwrap();
+
+        assert!(prefix > word_start);
+        assert!(word_start > substring);
+        assert!(substring > scattered);
+        assert_eq!(get_match_score("xyz", "Meeting notes"), None);
+        assert_eq!(get_match_score("setonm", "Meeting notes"), None);
+    }
+
+    #[test]
+    fn test_get_match_score_prefers_earlier_and_tighter_matches() {
+        assert!(
+            get_match_score("notes", "notes for later").unwrap()
+                > get_match_score("notes", "some notes").unwrap()
+        );
+        assert!(
+            get_match_score("some notes", "a some notes").unwrap()
+                > get_match_score("notes", "a long list of notes").unwrap()
+        );
+        assert!(
+            get_match_score("mn", "my notes").unwrap()
+                > get_match_score("mn", "my very long notes").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_search_bookmarks_ranks_best_matches_first() {
+        let bookmarks = vec![
+            get_bookmark(1, "Has a deploy checklist", "alice", 3),
+            get_bookmark(2, "Deploy steps", "bob", 3),
+            get_bookmark(3, "Nothing relevant", "carol", 3),
+            get_bookmark(4, "redeployed yesterday", "dave", 3),
+            get_bookmark(5, "Draft plenary agenda", "erin", 3),
+        ];
+
+        assert_eq!(search(&bookmarks, "deploy"), vec![2, 1, 4]);
+        assert_eq!(search(&bookmarks, "  DEPLOY "), vec![2, 1, 4]);
+        assert_eq!(search(&bookmarks, "dply"), vec![2, 4, 1, 5]);
+    }
+
+    #[test]
+    fn test_search_bookmarks_matches_authors_and_channel_names() {
+        let bookmarks = vec![
+            get_bookmark(1, "Lunch?", "alice", 1),
+            get_bookmark(2, "v1.2 is out", "bob", 2),
+            get_bookmark(3, "Something", "notes_bot", 3),
+        ];
+
+        assert_eq!(search(&bookmarks, "alice"), vec![1]);
+        assert_eq!(search(&bookmarks, "release"), vec![2]);
+        assert_eq!(search(&bookmarks, "notes"), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_search_bookmarks_keeps_order_of_ties() {
+        let bookmarks = vec![
+            get_bookmark(3, "todo: newest", "alice", 1),
+            get_bookmark(2, "todo: older", "alice", 1),
+            get_bookmark(1, "todo: oldest", "alice", 1),
+        ];
+
+        assert_eq!(search(&bookmarks, "todo"), vec![3, 2, 1]);
+        assert_eq!(search(&bookmarks, ""), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_search_bookmarks_caps_results() {
+        let bookmarks = (0..40)
+            .map(|pk| get_bookmark(pk, "Same content", "alice", 1))
+            .collect::<Vec<_>>();
+
+        let results = search(&bookmarks, "content");
+
+        assert_eq!(results.len(), MAX_BOOKMARK_SEARCH_RESULTS);
+        assert_eq!(results, (0..25).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_bookmark_search_cache_loads_every_bookmark_once() {
+        let db_connection = get_test_db_connection().await;
+        let insert_bookmark = |message_id: u64| {
+            let mut message = serenity::Message::default();
+            message.id = message_id.into();
+            crate::database::bookmark::insert_bookmark(
+                &db_connection,
+                BookmarkedMessage::new(
+                    uuid::Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                    1,
+                    message,
+                ),
+            )
+        };
+        for message_id in 1..=BOOKMARK_SEARCH_PAGE_SIZE + 1 {
+            insert_bookmark(message_id).await.unwrap();
+        }
+        let cache = BookmarkSearchCache::default();
+
+        let bookmarks = cache.get_bookmarks(&db_connection, 1).await.unwrap();
+        assert_eq!(bookmarks.len() as u64, BOOKMARK_SEARCH_PAGE_SIZE + 1);
+
+        insert_bookmark(BOOKMARK_SEARCH_PAGE_SIZE + 2)
+            .await
+            .unwrap();
+        let cached_bookmarks = cache.get_bookmarks(&db_connection, 1).await.unwrap();
+        assert!(Arc::ptr_eq(&bookmarks, &cached_bookmarks));
+
+        cache.invalidate(1);
+        let bookmarks = cache.get_bookmarks(&db_connection, 1).await.unwrap();
+        assert_eq!(bookmarks.len() as u64, BOOKMARK_SEARCH_PAGE_SIZE + 2);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use poise::serenity_prelude as serenity;
+use serde::Deserialize;
+use strum_macros::{Display, EnumString};
+use tokio::{sync::Mutex, task::JoinSet};
+use tokio_rusqlite::Connection;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    components::{
+        interaction_custom_id::DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+        reminder::catch_up_message::get_catch_up_message, DELETE_MESSAGE_EMOJI,
+    },
+    config::DeliveryConfig,
+    database::{
+        reminder::{delete_reminder_by_id, get_reminder_by_id},
+        user_settings::get_user_settings,
+    },
+    models::reminder::PersistedReminder,
+    Error,
+};
+
+/// What to do with reminders that came due while the bot was offline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CatchUpBehavior {
+    /// Send each user one message listing all of their missed reminders
+    Summary,
+    /// Send each missed reminder on its own, as if it had just come due
+    Individual,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CatchUpOptions {
+    behavior: CatchUpBehavior,
+    /// Reminders overdue by less than this are delivered normally
+    threshold: chrono::Duration,
+}
+
+impl CatchUpOptions {
+    pub fn new(behavior: CatchUpBehavior, threshold: chrono::Duration) -> Self {
+        Self {
+            behavior,
+            threshold,
+        }
+    }
+}
+
+impl From<&DeliveryConfig> for CatchUpOptions {
+    fn from(config: &DeliveryConfig) -> Self {
+        Self::new(
+            config.catch_up_behavior,
+            chrono::Duration::seconds(config.catch_up_threshold_seconds),
+        )
+    }
+}
+
+/// Splits reminders loaded at start-up into the ones that were missed while the bot was offline (grouped by user)
+/// and the ones that should be handed to the scheduler as usual.
+pub fn partition_missed_reminders(
+    reminders: Vec<PersistedReminder>,
+    now: &chrono::DateTime<chrono::Utc>,
+    options: &CatchUpOptions,
+) -> (
+    BTreeMap<u64, Vec<PersistedReminder>>,
+    Vec<PersistedReminder>,
+) {
+    if options.behavior == CatchUpBehavior::Individual {
+        return (BTreeMap::new(), reminders);
+    }
+
+    let missed_before = *now - options.threshold;
+    let mut missed_reminders_by_user = BTreeMap::<u64, Vec<PersistedReminder>>::new();
+    let mut other_reminders = Vec::new();
+
+    for reminder in reminders {
+        // Bookmark reviews are delivered late rather than summarized, since their buttons schedule the next review
+        if *reminder.remind_at() < missed_before && reminder.review_bookmark_id().is_none() {
+            missed_reminders_by_user
+                .entry(reminder.user_id())
+                .or_default()
+                .push(reminder);
+        } else {
+            other_reminders.push(reminder);
+        }
+    }
+
+    missed_reminders_by_user
+        .values_mut()
+        .for_each(|reminders| reminders.sort());
+
+    (missed_reminders_by_user, other_reminders)
+}
+
+/// The catch-up summaries being held for quiet hours, so they can be dropped when their user asks to be forgotten
+#[derive(Clone, Default)]
+pub struct HeldCatchUpSummaries(Arc<std::sync::Mutex<BTreeMap<u64, CancellationToken>>>);
+
+impl HeldCatchUpSummaries {
+    /// Drops the user's summary if it's still being held, e.g. after `/forget_me`
+    pub fn cancel(&self, user_id: u64) {
+        if let Some(cancel) = self.lock().remove(&user_id) {
+            cancel.cancel();
+        }
+    }
+
+    /// Returns the token that [`Self::cancel`] cancels, which is also cancelled along with `shutdown`
+    fn hold(&self, user_id: u64, shutdown: &CancellationToken) -> CancellationToken {
+        let cancel = shutdown.child_token();
+        self.lock().insert(user_id, cancel.clone());
+        cancel
+    }
+
+    fn release(&self, user_id: u64) {
+        self.lock().remove(&user_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, CancellationToken>> {
+        self.0
+            .lock()
+            .expect("Held catch-up summary lock shouldn't be poisoned")
+    }
+}
+
+/// Sends each user a single message summarizing their missed reminders, respecting their digest and quiet hours settings.
+/// `held_summaries` should be the same as the [`crate::Data`]'s.
+///
+/// Returns once every summary is sent. Summaries still held for quiet hours when `shutdown` is cancelled are dropped,
+/// leaving their reminders in the database to be caught up on at the next start-up.
+pub async fn send_catch_up_summaries(
+    http: Arc<serenity::Http>,
+    db_connection: Arc<Mutex<Connection>>,
+    missed_reminders_by_user: BTreeMap<u64, Vec<PersistedReminder>>,
+    held_summaries: HeldCatchUpSummaries,
+    shutdown: CancellationToken,
+) {
+    let mut summaries = JoinSet::new();
+    for (user_id, reminders) in missed_reminders_by_user {
+        let http = http.clone();
+        let db_connection = db_connection.clone();
+        let held_summaries = held_summaries.clone();
+        let shutdown = shutdown.clone();
+        summaries.spawn(async move {
+            let result = send_catch_up_summary(
+                &http,
+                &db_connection,
+                user_id,
+                reminders,
+                &held_summaries,
+                &shutdown,
+            )
+            .await;
+            if let Err(err) = result {
+                tracing::error!(user_id, error = %err, "Failed to send catch-up summary");
+            }
+        });
+    }
+
+    while let Some(result) = summaries.join_next().await {
+        if let Err(err) = result {
+            tracing::error!(error = %err, "Catch-up summary panicked");
+        }
+    }
+}
+
+async fn send_catch_up_summary(
+    http: &serenity::Http,
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+    reminders: Vec<PersistedReminder>,
+    held_summaries: &HeldCatchUpSummaries,
+    shutdown: &CancellationToken,
+) -> Result<(), Error> {
+    let has_urgent_reminder = reminders.iter().any(PersistedReminder::is_urgent);
+
+    if !has_urgent_reminder {
+        let settings = get_user_settings(db_connection, user_id).await?;
+
+        if settings
+            .as_ref()
+            .is_some_and(|settings| settings.digest_time().is_some())
+        {
+            tracing::info!(
+                user_id,
+                missed_reminder_count = reminders.len(),
+                "Leaving missed reminders for the user's next digest"
+            );
+            return Ok(());
+        }
+
+        let end_of_quiet_hours = settings
+            .and_then(|settings| settings.get_end_of_quiet_hours_containing(&chrono::Utc::now()));
+        if let Some(end_of_quiet_hours) = end_of_quiet_hours {
+            tracing::info!(
+                user_id,
+                %end_of_quiet_hours,
+                "Holding catch-up summary until the end of quiet hours"
+            );
+            let sleep_time = end_of_quiet_hours
+                .signed_duration_since(chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default();
+            let cancel = held_summaries.hold(user_id, shutdown);
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    tracing::info!(user_id, "Dropped held catch-up summary");
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(sleep_time) => {}
+            }
+            held_summaries.release(user_id);
+        }
+    }
+
+    // Re-read the reminders in case any were deleted while waiting for quiet hours to end
+    let mut current_reminders = Vec::with_capacity(reminders.len());
+    for reminder in reminders {
+        if let Some(reminder) = get_reminder_by_id(db_connection, reminder.pk()).await? {
+            current_reminders.push(reminder);
+        }
+    }
+
+    if current_reminders.is_empty() {
+        return Ok(());
+    }
+
+    serenity::UserId::new(user_id)
+        .create_dm_channel(http)
+        .await?
+        .send_message(
+            http,
+            get_catch_up_message(
+                &current_reminders,
+                DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+                DELETE_MESSAGE_EMOJI,
+            ),
+        )
+        .await?;
+
+    for reminder in current_reminders {
+        delete_reminder_by_id(db_connection, reminder.pk()).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::reminder::Reminder;
+
+    fn reminder(
+        pk: i64,
+        user_id: u64,
+        remind_at: chrono::DateTime<chrono::Utc>,
+    ) -> PersistedReminder {
+        PersistedReminder::from_reminder(
+            Reminder::new(user_id, serenity::Message::default(), remind_at),
+            pk,
+        )
+    }
+
+    #[test]
+    fn test_partition_missed_reminders_into_summaries() {
+        let now = chrono::Utc::now();
+        let options = CatchUpOptions::new(CatchUpBehavior::Summary, chrono::Duration::minutes(5));
+
+        let reminders = vec![
+            reminder(1, 1, now - chrono::Duration::hours(2)),
+            reminder(2, 1, now - chrono::Duration::hours(3)),
+            reminder(3, 2, now - chrono::Duration::hours(1)),
+            // Only slightly overdue, so it's delivered normally
+            reminder(4, 1, now - chrono::Duration::minutes(1)),
+            reminder(5, 2, now + chrono::Duration::hours(1)),
+            PersistedReminder::from_reminder(
+                Reminder::new_bookmark_review(
+                    1,
+                    serenity::Message::default(),
+                    now - chrono::Duration::hours(2),
+                    uuid::Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                ),
+                6,
+            ),
+        ];
+
+        let (missed_reminders_by_user, other_reminders) =
+            partition_missed_reminders(reminders, &now, &options);
+
+        let missed_pks = missed_reminders_by_user
+            .iter()
+            .map(|(user_id, reminders)| {
+                (
+                    *user_id,
+                    reminders
+                        .iter()
+                        .map(PersistedReminder::pk)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        // Each user's reminders are ordered by when they were due
+        assert_eq!(missed_pks, vec![(1, vec![2, 1]), (2, vec![3])]);
+
+        let other_pks = other_reminders
+            .iter()
+            .map(PersistedReminder::pk)
+            .collect::<Vec<_>>();
+        assert_eq!(other_pks, vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn test_partition_missed_reminders_individually() {
+        let now = chrono::Utc::now();
+        let options =
+            CatchUpOptions::new(CatchUpBehavior::Individual, chrono::Duration::minutes(5));
+
+        let reminders = vec![
+            reminder(1, 1, now - chrono::Duration::hours(2)),
+            reminder(2, 2, now + chrono::Duration::hours(1)),
+        ];
+
+        let (missed_reminders_by_user, other_reminders) =
+            partition_missed_reminders(reminders, &now, &options);
+
+        assert!(missed_reminders_by_user.is_empty());
+        assert_eq!(other_reminders.len(), 2);
+    }
+
+    #[test]
+    fn test_catch_up_behavior_parse() {
+        assert_eq!(
+            "summary".parse::<CatchUpBehavior>().unwrap(),
+            CatchUpBehavior::Summary
+        );
+        assert_eq!(
+            "individual".parse::<CatchUpBehavior>().unwrap(),
+            CatchUpBehavior::Individual
+        );
+        assert!("everything_at_once".parse::<CatchUpBehavior>().is_err());
+    }
+
+    #[test]
+    fn test_held_catch_up_summaries_are_cancelled_per_user_and_on_shutdown() {
+        let held_summaries = HeldCatchUpSummaries::default();
+        let shutdown = CancellationToken::new();
+        let first = held_summaries.hold(1, &shutdown);
+        let second = held_summaries.hold(2, &shutdown);
+
+        held_summaries.cancel(1);
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        shutdown.cancel();
+        assert!(second.is_cancelled());
+    }
+}
@@ -0,0 +1,324 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use poise::serenity_prelude::{self as serenity, CreateCommand};
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+use discord_attendant::{
+    command_registration::register_commands as register_commands_with_discord,
+    config::Config,
+    database::{
+        backup_database, create_all_tables_if_nonexistent,
+        message_encryption::reencrypt_all_messages, reminder::get_all_reminders,
+        user_data::get_user_data_archive, vacuum_database,
+    },
+    models::reminder::PersistedReminder,
+    Error,
+};
+
+/// A Discord bot for bookmarking messages and being reminded of them.
+/// Every subcommand reads the same configuration as the bot itself (see the README).
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Runs the bot when not given
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Subcommand)]
+pub enum CliCommand {
+    /// Run the bot
+    #[default]
+    Run,
+    /// Register the bot's slash commands with Discord, only sending the ones that changed.
+    /// Servers only keep their own copy of the commands while the global ones are out of date.
+    RegisterCommands {
+        /// Register the commands globally, then remove the configured test servers' copies of them.
+        /// Discord limits how many commands can be created a day.
+        #[arg(long, conflicts_with = "guild_ids")]
+        global: bool,
+        /// Register the commands in this server instead of the configured test servers. May be repeated.
+        #[arg(long = "guild", value_name = "ID", value_parser = clap::value_parser!(u64).range(1..))]
+        guild_ids: Vec<u64>,
+    },
+    /// Create any tables and columns missing from the database, then exit
+    Migrate,
+    /// Print everything stored about a user as JSON, in the same format as `/my_data`
+    Export {
+        #[arg(long = "user", value_name = "ID")]
+        user_id: u64,
+    },
+    /// Copy the database to a new file. Safe to run while the bot is running.
+    Backup { path: PathBuf },
+    /// Shrink the database file by reclaiming the space left behind by deleted rows
+    Vacuum,
+    /// List the pending reminders that are due soon, including overdue ones. Message contents aren't shown.
+    ListDue {
+        /// How far ahead to look
+        #[arg(long, value_name = "HOURS", default_value_t = 24)]
+        within_hours: u32,
+    },
+    /// Check the configuration (including the Discord token) and exit
+    CheckConfig,
+    /// Re-encrypt every stored message under the current encryption key, e.g. after rotating keys
+    ReencryptMessages,
+}
+
+/// Registers `commands` globally and/or in `guild_ids` (the configured test servers unless given any),
+/// as described by [`register_commands_with_discord`]. Only commands that changed are sent to Discord. Doesn't need the bot to be connected to the gateway,
+/// so this is safe to run alongside a running bot.
+pub async fn register_commands(
+    config: &Config,
+    commands: Vec<CreateCommand>,
+    global: bool,
+    guild_ids: Vec<u64>,
+) -> Result<(), Error> {
+    let guild_ids = if !guild_ids.is_empty() {
+        guild_ids
+    } else {
+        config.commands.test_guild_ids.clone()
+    };
+    if !global && guild_ids.is_empty() {
+        return Err("No servers to register commands in: pass `--global` or `--guild <ID>`, or configure `commands.test_guild_ids`".into());
+    }
+
+    let http = serenity::Http::new(config.require_discord_token()?);
+    let application_id = http.get_current_application_info().await?.id;
+    http.set_application_id(application_id);
+
+    register_commands_with_discord(
+        &http,
+        &commands,
+        global,
+        &guild_ids
+            .into_iter()
+            .map(serenity::GuildId::new)
+            .collect::<Vec<_>>(),
+    )
+    .await
+}
+
+impl CliCommand {
+    /// Whether the command only reads the database, which it's then opened for so it's never modified by accident
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, Self::Export { .. } | Self::ListDue { .. })
+    }
+}
+
+/// Runs the subcommands that only need the database
+pub async fn run_database_command(
+    command: CliCommand,
+    db_connection: &Mutex<Connection>,
+) -> Result<(), Error> {
+    match command {
+        CliCommand::Migrate => {
+            create_all_tables_if_nonexistent(db_connection).await?;
+            println!("The database is up to date");
+        }
+        CliCommand::Export { user_id } => {
+            let archive = get_user_data_archive(db_connection, user_id, chrono::Utc::now())
+                .await
+                .map_err(get_missing_schema_error)?;
+            println!("{}", serde_json::to_string_pretty(&archive)?);
+        }
+        CliCommand::Backup { path } => {
+            backup_database(db_connection, path.clone()).await?;
+            println!("Backed up the database to {}", path.display());
+        }
+        CliCommand::Vacuum => {
+            vacuum_database(db_connection).await?;
+            println!("Vacuumed the database");
+        }
+        CliCommand::ListDue { within_hours } => {
+            let now = chrono::Utc::now();
+            let due_reminders = get_reminders_due_within(
+                get_all_reminders(db_connection)
+                    .await
+                    .map_err(get_missing_schema_error)?,
+                &now,
+                chrono::Duration::hours(within_hours.into()),
+            );
+            if due_reminders.is_empty() {
+                println!("No reminders are due within {} hours", within_hours);
+            }
+            for reminder in &due_reminders {
+                println!("{}", format_due_reminder(reminder, &now));
+            }
+        }
+        CliCommand::ReencryptMessages => {
+            create_all_tables_if_nonexistent(db_connection).await?;
+            let updated_row_count = reencrypt_all_messages(db_connection).await?;
+            println!("Re-encrypted {} stored messages.", updated_row_count);
+        }
+        CliCommand::Run | CliCommand::RegisterCommands { .. } | CliCommand::CheckConfig => {
+            return Err(format!("`{:?}` doesn't only use the database", command).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// The read-only commands can't create the tables they need, so a database without them
+/// gets an error saying what to do about it rather than just SQLite's
+fn get_missing_schema_error(err: tokio_rusqlite::Error) -> Error {
+    match &err {
+        tokio_rusqlite::Error::Rusqlite(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message.starts_with("no such table") || message.starts_with("no such column") =>
+        {
+            format!(
+                "The database is missing part of its schema ({}), run `migrate` first",
+                message
+            )
+            .into()
+        }
+        _ => err.into(),
+    }
+}
+
+/// Sorted by when they're due, soonest (or most overdue) first
+fn get_reminders_due_within(
+    reminders: Vec<PersistedReminder>,
+    now: &chrono::DateTime<chrono::Utc>,
+    within: chrono::Duration,
+) -> Vec<PersistedReminder> {
+    let mut due_reminders = reminders
+        .into_iter()
+        .filter(|reminder| *reminder.remind_at() <= *now + within)
+        .collect::<Vec<_>>();
+    due_reminders.sort_by_key(|reminder| *reminder.remind_at());
+
+    due_reminders
+}
+
+/// Leaves out the message's content, since this is meant for operators rather than the reminder's owner
+fn format_due_reminder(
+    reminder: &PersistedReminder,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> String {
+    let mut line = format!(
+        "#{} for user {} at {}",
+        reminder.pk(),
+        reminder.user_id(),
+        reminder.remind_at().to_rfc3339()
+    );
+    if reminder.remind_at() < now {
+        line.push_str(" (overdue)");
+    }
+    if reminder.is_urgent() {
+        line.push_str(" [urgent]");
+    }
+    line.push_str(&format!(": {}", reminder.message().link()));
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use clap::CommandFactory;
+    use poise::serenity_prelude::Message;
+
+    use super::*;
+    use discord_attendant::models::reminder::Reminder;
+
+    fn get_reminder(pk: i64, remind_at: chrono::DateTime<chrono::Utc>) -> PersistedReminder {
+        let mut message = Message::default();
+        message.content = "secret".to_string();
+        PersistedReminder::from_reminder(Reminder::new(123456789, message, remind_at), pk)
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_cli() {
+        assert_eq!(Cli::parse_from(["bot"]).command, None);
+        assert_eq!(
+            Cli::parse_from(["bot", "register-commands", "--guild", "1", "--guild", "2"]).command,
+            Some(CliCommand::RegisterCommands {
+                global: false,
+                guild_ids: vec![1, 2],
+            })
+        );
+        assert_eq!(
+            Cli::parse_from(["bot", "export", "--user", "123456789"]).command,
+            Some(CliCommand::Export { user_id: 123456789 })
+        );
+        assert_eq!(
+            Cli::parse_from(["bot", "list-due"]).command,
+            Some(CliCommand::ListDue { within_hours: 24 })
+        );
+        assert_eq!(
+            Cli::parse_from(["bot", "backup", "./backup.db"]).command,
+            Some(CliCommand::Backup {
+                path: PathBuf::from("./backup.db")
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_cli() {
+        assert!(
+            Cli::try_parse_from(["bot", "register-commands", "--global", "--guild", "1"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["bot", "register-commands", "--guild", "0"]).is_err());
+        assert!(Cli::try_parse_from(["bot", "export"]).is_err());
+    }
+
+    #[test]
+    fn test_get_reminders_due_within() {
+        let now = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let reminders = vec![
+            get_reminder(1, now + chrono::Duration::hours(2)),
+            get_reminder(2, now + chrono::Duration::hours(48)),
+            get_reminder(3, now - chrono::Duration::hours(1)),
+        ];
+
+        let due_reminders = get_reminders_due_within(reminders, &now, chrono::Duration::hours(24));
+
+        assert_eq!(
+            due_reminders
+                .iter()
+                .map(|reminder| reminder.pk())
+                .collect::<Vec<_>>(),
+            vec![3, 1]
+        );
+    }
+
+    #[test]
+    fn test_format_due_reminder_leaves_out_message_content() {
+        let now = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let reminder = get_reminder(7, now - chrono::Duration::minutes(5));
+
+        let line = format_due_reminder(&reminder, &now);
+
+        assert_eq!(
+            line,
+            format!(
+                "#7 for user 123456789 at 2024-01-01T11:55:00+00:00 (overdue): {}",
+                reminder.message().link()
+            )
+        );
+        assert!(!line.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_commands_report_a_missing_schema() {
+        let db_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+
+        for command in [
+            CliCommand::Export { user_id: 1 },
+            CliCommand::ListDue { within_hours: 24 },
+        ] {
+            assert!(command.is_read_only());
+            let err = run_database_command(command, &db_connection)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("run `migrate` first"));
+        }
+    }
+}
@@ -0,0 +1,548 @@
+use std::fmt::Display;
+
+use poise::serenity_prelude::{self as serenity, CommandId, CreateCommand, GuildId, Http};
+use serde_json::{Map, Value};
+
+use crate::Error;
+
+/// Fields Discord fills in itself, which a [`CreateCommand`] never sets
+const SERVER_ASSIGNED_FIELDS: [&str; 6] = [
+    "id",
+    "application_id",
+    "guild_id",
+    "version",
+    "name_localized",
+    "description_localized",
+];
+/// Fields Discord gives a default value when a [`CreateCommand`] leaves them out. The ones with a fixed default
+/// (see [`get_field_default`]) are compared against it, the others only when the wanted command sets them.
+const DEFAULTED_FIELDS: [&str; 5] = [
+    "type",
+    "contexts",
+    "integration_types",
+    "dm_permission",
+    "handler",
+];
+/// Fields Discord ignores on commands registered in a server
+const GLOBAL_ONLY_FIELDS: [&str; 3] = ["contexts", "integration_types", "dm_permission"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    Global,
+    Guild(GuildId),
+}
+
+impl Display for CommandScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Global => write!(f, "Global commands"),
+            Self::Guild(guild_id) => write!(f, "Commands in server {}", guild_id),
+        }
+    }
+}
+
+/// What has to be done to one command for the registered commands to match the wanted ones
+#[derive(Debug, Clone)]
+pub enum CommandChange {
+    Create {
+        name: String,
+        command: CreateCommand,
+    },
+    Update {
+        id: CommandId,
+        name: String,
+        command: CreateCommand,
+        changed_fields: Vec<String>,
+    },
+    Delete {
+        id: CommandId,
+        name: String,
+    },
+}
+
+impl Display for CommandChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Create { name, .. } => write!(f, "+ /{}", name),
+            Self::Update {
+                name,
+                changed_fields,
+                ..
+            } => write!(f, "~ /{} ({})", name, changed_fields.join(", ")),
+            Self::Delete { name, .. } => write!(f, "- /{}", name),
+        }
+    }
+}
+
+/// Registers `commands` globally when `register_globally` is set, then brings each of `guild_ids` up to date.
+///
+/// Commands registered both globally and in a server show up twice there, so servers only get their own copy
+/// of the commands while the global ones are out of date (which is what makes changes show up immediately
+/// during development). Once the global commands are current, the servers' copies are deleted.
+pub async fn register_commands(
+    http: &Http,
+    commands: &[CreateCommand],
+    register_globally: bool,
+    guild_ids: &[GuildId],
+) -> Result<(), Error> {
+    if !register_globally && guild_ids.is_empty() {
+        return Ok(());
+    }
+
+    let are_global_commands_current = if register_globally {
+        sync_commands(http, CommandScope::Global, commands).await?;
+        true
+    } else {
+        get_command_changes(
+            &http.get_global_commands_with_localizations().await?,
+            commands,
+            CommandScope::Global,
+        )?
+        .is_empty()
+    };
+
+    let guild_commands = if are_global_commands_current {
+        &[]
+    } else {
+        commands
+    };
+    for guild_id in guild_ids {
+        sync_commands(http, CommandScope::Guild(*guild_id), guild_commands).await?;
+    }
+
+    Ok(())
+}
+
+/// Creates, updates and deletes commands in `scope` until they match `commands`, leaving unchanged commands alone
+pub async fn sync_commands(
+    http: &Http,
+    scope: CommandScope,
+    commands: &[CreateCommand],
+) -> Result<Vec<CommandChange>, Error> {
+    let registered_commands = match scope {
+        CommandScope::Global => http.get_global_commands_with_localizations().await?,
+        CommandScope::Guild(guild_id) => {
+            http.get_guild_commands_with_localizations(guild_id).await?
+        }
+    };
+    let changes = get_command_changes(&registered_commands, commands, scope)?;
+
+    for change in &changes {
+        match (scope, change) {
+            (CommandScope::Global, CommandChange::Create { command, .. }) => {
+                http.create_global_command(command).await?;
+            }
+            (CommandScope::Global, CommandChange::Update { id, command, .. }) => {
+                http.edit_global_command(*id, command).await?;
+            }
+            (CommandScope::Global, CommandChange::Delete { id, .. }) => {
+                http.delete_global_command(*id).await?;
+            }
+            (CommandScope::Guild(guild_id), CommandChange::Create { command, .. }) => {
+                http.create_guild_command(guild_id, command).await?;
+            }
+            (CommandScope::Guild(guild_id), CommandChange::Update { id, command, .. }) => {
+                http.edit_guild_command(guild_id, *id, command).await?;
+            }
+            (CommandScope::Guild(guild_id), CommandChange::Delete { id, .. }) => {
+                http.delete_guild_command(guild_id, *id).await?;
+            }
+        }
+    }
+
+    tracing::info!("{}", get_command_changes_summary(scope, &changes));
+
+    Ok(changes)
+}
+
+fn get_command_changes_summary(scope: CommandScope, changes: &[CommandChange]) -> String {
+    if changes.is_empty() {
+        return format!("{}: already up to date", scope);
+    }
+
+    changes
+        .iter()
+        .fold(format!("{}:", scope), |summary, change| {
+            format!("{}\n  {}", summary, change)
+        })
+}
+
+/// Compares commands by their JSON representation, since that's the only thing a [`CreateCommand`] exposes.
+/// Commands are matched up by their type and name, so renaming a command deletes the old one and creates a new one.
+pub fn get_command_changes(
+    registered_commands: &[serenity::Command],
+    wanted_commands: &[CreateCommand],
+    scope: CommandScope,
+) -> Result<Vec<CommandChange>, serde_json::Error> {
+    let mut registered_commands = registered_commands
+        .iter()
+        .map(|command| {
+            Ok((
+                command.id,
+                normalize_command_json(serde_json::to_value(command)?),
+            ))
+        })
+        .collect::<Result<Vec<_>, serde_json::Error>>()?;
+
+    let mut changes = Vec::new();
+    for command in wanted_commands {
+        let wanted_command = normalize_command_json(serde_json::to_value(command)?);
+        let name = get_command_name(&wanted_command);
+
+        match registered_commands
+            .iter()
+            .position(|(_, registered_command)| {
+                get_command_key(registered_command) == get_command_key(&wanted_command)
+            }) {
+            Some(index) => {
+                let (id, registered_command) = registered_commands.remove(index);
+                let changed_fields =
+                    get_changed_fields(&registered_command, &wanted_command, scope);
+                if !changed_fields.is_empty() {
+                    changes.push(CommandChange::Update {
+                        id,
+                        name,
+                        command: command.clone(),
+                        changed_fields,
+                    });
+                }
+            }
+            None => changes.push(CommandChange::Create {
+                name,
+                command: command.clone(),
+            }),
+        }
+    }
+
+    changes.extend(
+        registered_commands
+            .into_iter()
+            .map(|(id, registered_command)| CommandChange::Delete {
+                id,
+                name: get_command_name(&registered_command),
+            }),
+    );
+
+    Ok(changes)
+}
+
+fn get_command_name(command: &Value) -> String {
+    command["name"].as_str().unwrap_or_default().to_string()
+}
+
+/// Commands without a type are chat input commands, whose type is 1
+fn get_command_key(command: &Value) -> (u64, &str) {
+    (
+        command["type"].as_u64().unwrap_or(1),
+        command["name"].as_str().unwrap_or_default(),
+    )
+}
+
+fn get_changed_fields(
+    registered_command: &Value,
+    wanted_command: &Value,
+    scope: CommandScope,
+) -> Vec<String> {
+    let empty_map = Map::new();
+    let registered_command = registered_command.as_object().unwrap_or(&empty_map);
+    let wanted_command = wanted_command.as_object().unwrap_or(&empty_map);
+
+    let mut changed_fields = registered_command
+        .keys()
+        .chain(wanted_command.keys())
+        .filter(|field| !SERVER_ASSIGNED_FIELDS.contains(&field.as_str()))
+        .filter(|field| {
+            !DEFAULTED_FIELDS.contains(&field.as_str())
+                || wanted_command.contains_key(*field)
+                || get_field_default(field).is_some()
+        })
+        .filter(|field| {
+            scope == CommandScope::Global || !GLOBAL_ONLY_FIELDS.contains(&field.as_str())
+        })
+        .filter(|field| {
+            get_field_value(registered_command, field) != get_field_value(wanted_command, field)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    changed_fields.sort();
+    changed_fields.dedup();
+
+    changed_fields
+}
+
+fn get_field_value(command: &Map<String, Value>, field: &str) -> Option<Value> {
+    command
+        .get(field)
+        .cloned()
+        .or_else(|| get_field_default(field))
+}
+
+/// The value Discord gives the [`DEFAULTED_FIELDS`] that always default to the same thing
+fn get_field_default(field: &str) -> Option<Value> {
+    match field {
+        "type" => Some(Value::from(1)),
+        "dm_permission" => Some(Value::Bool(true)),
+        _ => None,
+    }
+}
+
+/// Discord leaves out fields that have their default value, while a [`CreateCommand`] writes some of them out
+/// (and vice versa), so this drops every null, `false`, empty array and empty object from objects.
+/// `false` is kept for the [`DEFAULTED_FIELDS`], since they don't all default to it.
+/// Whole numbers are also written the same way regardless of whether they were given as floats.
+fn normalize_command_json(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, normalize_command_json(value)))
+                .filter(|(key, value)| {
+                    !is_default_json_value(value)
+                        || (*value == Value::Bool(false)
+                            && DEFAULTED_FIELDS.contains(&key.as_str()))
+                })
+                .collect(),
+        ),
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(normalize_command_json).collect())
+        }
+        Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() && float.fract() == 0.0 => Value::from(float as i64),
+            _ => Value::Number(number),
+        },
+        value => value,
+    }
+}
+
+fn is_default_json_value(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::Array(values) => values.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{CommandOptionType, CreateCommandOption, InteractionContext};
+    use serde_json::json;
+
+    use super::*;
+
+    fn get_registered_command(
+        id: u64,
+        name: &str,
+        description: &str,
+        options: Value,
+    ) -> serenity::Command {
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "application_id": "1",
+            "version": "1",
+            "type": 1,
+            "name": name,
+            "name_localizations": null,
+            "description": description,
+            "description_localizations": null,
+            "options": options,
+            "default_member_permissions": null,
+            "nsfw": false,
+            "integration_types": [0],
+            "contexts": [0, 1, 2],
+        }))
+        .unwrap()
+    }
+
+    fn get_bookmarks_command(description: &str) -> CreateCommand {
+        CreateCommand::new("get_bookmarks")
+            .description(description)
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "offset",
+                    "Offset to start fetching bookmarks from",
+                )
+                .min_number_value(0.0),
+            )
+    }
+
+    fn get_registered_bookmarks_command(id: u64, description: &str) -> serenity::Command {
+        get_registered_command(
+            id,
+            "get_bookmarks",
+            description,
+            json!([{
+                "type": 4,
+                "name": "offset",
+                "description": "Offset to start fetching bookmarks from",
+                "min_value": 0,
+            }]),
+        )
+    }
+
+    #[test]
+    fn test_unchanged_commands_have_no_changes() {
+        let changes = get_command_changes(
+            &[
+                get_registered_bookmarks_command(10, "Get your bookmarks"),
+                get_registered_command(11, "help", "Show help", json!([])),
+            ],
+            &[
+                get_bookmarks_command("Get your bookmarks"),
+                CreateCommand::new("help").description("Show help"),
+            ],
+            CommandScope::Global,
+        )
+        .unwrap();
+
+        assert!(changes.is_empty(), "{:?}", changes);
+    }
+
+    #[test]
+    fn test_get_command_changes() {
+        let changes = get_command_changes(
+            &[
+                get_registered_bookmarks_command(10, "Get your bookmarks"),
+                get_registered_command(11, "old_command", "Not used anymore", json!([])),
+            ],
+            &[
+                get_bookmarks_command("Get a subset of your bookmarks"),
+                CreateCommand::new("help").description("Show help"),
+            ],
+            CommandScope::Global,
+        )
+        .unwrap();
+
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "~ /get_bookmarks (description)",
+                "+ /help",
+                "- /old_command"
+            ]
+        );
+        assert!(matches!(
+            changes[0],
+            CommandChange::Update { id, .. } if id == CommandId::new(10)
+        ));
+        assert!(matches!(
+            changes[2],
+            CommandChange::Delete { id, .. } if id == CommandId::new(11)
+        ));
+    }
+
+    #[test]
+    fn test_contexts_are_only_compared_globally_and_when_set() {
+        let registered_commands = [get_registered_command(11, "help", "Show help", json!([]))];
+        let wanted_commands = [CreateCommand::new("help")
+            .description("Show help")
+            .add_context(InteractionContext::BotDm)];
+
+        let global_changes =
+            get_command_changes(&registered_commands, &wanted_commands, CommandScope::Global)
+                .unwrap();
+        assert_eq!(
+            global_changes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["~ /help (contexts)"]
+        );
+
+        let guild_changes = get_command_changes(
+            &registered_commands,
+            &wanted_commands,
+            CommandScope::Guild(GuildId::new(1)),
+        )
+        .unwrap();
+        assert!(guild_changes.is_empty(), "{:?}", guild_changes);
+    }
+
+    #[test]
+    fn test_dm_permission_is_compared_against_its_default() {
+        let mut allowed_in_dms = get_registered_command(11, "help", "Show help", json!([]));
+        allowed_in_dms.dm_permission = Some(true);
+        let mut not_allowed_in_dms = allowed_in_dms.clone();
+        not_allowed_in_dms.dm_permission = Some(false);
+        let help_command = CreateCommand::new("help").description("Show help");
+
+        for (registered_command, wanted_command, expected_changes) in [
+            (
+                &allowed_in_dms,
+                help_command.clone().dm_permission(false),
+                vec!["~ /help (dm_permission)"],
+            ),
+            (
+                &not_allowed_in_dms,
+                help_command.clone(),
+                vec!["~ /help (dm_permission)"],
+            ),
+            (
+                &not_allowed_in_dms,
+                help_command.clone().dm_permission(false),
+                vec![],
+            ),
+            (&allowed_in_dms, help_command.clone(), vec![]),
+        ] {
+            let changes = get_command_changes(
+                std::slice::from_ref(registered_command),
+                &[wanted_command],
+                CommandScope::Global,
+            )
+            .unwrap();
+            assert_eq!(
+                changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                expected_changes
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_command_changes_summary() {
+        assert_eq!(
+            get_command_changes_summary(CommandScope::Global, &[]),
+            "Global commands: already up to date"
+        );
+        assert_eq!(
+            get_command_changes_summary(
+                CommandScope::Guild(GuildId::new(123)),
+                &[
+                    CommandChange::Delete {
+                        id: CommandId::new(1),
+                        name: "help".to_string(),
+                    },
+                    CommandChange::Delete {
+                        id: CommandId::new(2),
+                        name: "settings".to_string(),
+                    },
+                ]
+            ),
+            "Commands in server 123:\n  - /help\n  - /settings"
+        );
+    }
+
+    #[test]
+    fn test_normalize_command_json() {
+        assert_eq!(
+            normalize_command_json(json!({
+                "name": "test",
+                "required": false,
+                "dm_permission": false,
+                "autocomplete": true,
+                "choices": [],
+                "name_localizations": {},
+                "min_value": 1.0,
+                "max_value": 2.5,
+                "options": [{ "name": "option", "channel_types": null }],
+            })),
+            json!({
+                "name": "test",
+                "dm_permission": false,
+                "autocomplete": true,
+                "min_value": 1,
+                "max_value": 2.5,
+                "options": [{ "name": "option" }],
+            })
+        );
+    }
+}
@@ -1,26 +1,60 @@
 use crate::{
     components::{
         bookmark::{
+            bookmark_autocomplete_choice::get_bookmark_autocomplete_choice,
             bookmark_created_reply::{
                 get_bookmark_already_exists_reply, get_bookmark_created_reply,
                 get_failed_to_create_bookmark_reply,
             },
+            bookmark_deleted_reply::get_bookmark_deleted_reply,
             bookmark_message::get_bookmark_message,
             get_bookmark_reply::create_get_bookmarks_reply,
+            next_bookmark_reply::{get_next_bookmark_reply, get_no_unread_bookmarks_reply},
             no_bookmarks_found_reply::get_no_bookmarks_found_reply,
         },
+        interaction_custom_id::InteractionCustomId,
         reminder::{
+            countdown_created_reply::{
+                get_countdown_alerts_in_past_reply, get_countdown_created_reply,
+            },
             get_reminders_reply::create_get_reminders_reply,
+            invalid_countdown_reply::get_invalid_countdown_reply,
             no_reminders_found_reply::get_no_reminders_found_reply,
             reminder_created_reply::get_reminder_created_reply,
         },
+        user_data::forget_me_reply::{
+            get_forget_me_cancelled_reply, get_forget_me_completed_reply,
+            get_forget_me_confirmation_reply, get_forget_me_timed_out_reply,
+        },
+        user_data::my_data_message::{get_my_data_message, get_my_data_sent_reply},
+        user_settings::{
+            guild_settings_reply::get_guild_settings_reply,
+            invalid_setting_reply::get_invalid_setting_reply,
+            user_settings_reply::get_user_settings_reply,
+        },
         DELETE_MESSAGE_EMOJI,
     },
     database::{
-        bookmark::InsertBookmarkError,
-        reminder::{get_reminders_for_user, insert_reminder},
+        bookmark::{
+            count_bookmarks_for_user, delete_bookmarks_by_pk, get_bookmark_by_id,
+            get_bookmarks_for_user, get_oldest_unread_bookmark_for_user, InsertBookmarkError,
+        },
+        guild_settings::{get_guild_settings, upsert_guild_settings},
+        reminder::{get_reminders_due_before_for_user, get_reminders_for_user, insert_reminder},
+        user_data::{delete_all_user_data, get_user_data_archive},
+        user_settings::{get_user_settings, upsert_user_settings},
+    },
+    models::{
+        bookmark::PersistedBookmarkedMessage,
+        countdown::{
+            parse_deadline, parse_offset, parse_offsets, Countdown, MAX_COUNTDOWN_OFFSETS,
+        },
+        guild_settings::GuildSettings,
+        message_link::parse_message_link,
+        reaction_shortcut::{InvalidReactionShortcutEmojisError, ReactionShortcutEmojis},
+        reminder::Reminder,
+        user_settings::{QuietHours, RetentionOverride, UserSettings, TIME_OF_DAY_FORMAT},
     },
-    models::reminder::Reminder,
 };
 
 use poise::{
@@ -28,7 +62,16 @@ use poise::{
     CreateReply,
 };
 
-use crate::{Context, Error};
+use crate::{
+    bookmark_search::search_bookmarks,
+    error::UserError,
+    linked_message::fetch_linked_message,
+    privacy::{is_privacy_mode_enabled, with_live_bookmark_message},
+    spaced_repetition::{
+        start_reviews_for_existing_bookmarks, start_reviews_if_enabled, stop_reviews,
+    },
+    ApplicationContext, AttendantState, Context, Error,
+};
 
 /// A slightly modified version of [`poise::builtins::autocomplete_command`] that trims the input string
 /// to enable something kinda like a fuzzy search - I wanted this because I found myself inputting
@@ -47,6 +90,52 @@ pub async fn autocomplete_command<'a, U, E>(
         .map(|cmd| cmd.name.to_string())
 }
 
+/// Like [`autocomplete_command`], trims the input string before fuzzy matching it against all of the caller's bookmarks
+/// (see [`search_bookmarks`] and [`BookmarkSearchCache`]). Each choice's value is a bookmark ID, so commands using this
+/// take it as a string.
+///
+/// [`BookmarkSearchCache`]: crate::bookmark_search::BookmarkSearchCache
+pub async fn autocomplete_bookmark<'a, U: AttendantState>(
+    ctx: Context<'a, U>,
+    partial: &'a str,
+) -> Vec<serenity::AutocompleteChoice> {
+    let data = ctx.data().attendant();
+    let bookmarks = match data
+        .bookmark_search_cache
+        .get_bookmarks(&data.db_connection, ctx.author().id.get())
+        .await
+    {
+        Ok(bookmarks) => bookmarks,
+        Err(err) => {
+            tracing::error!(error = %err, "Failed to get bookmarks to autocomplete");
+            return Vec::new();
+        }
+    };
+
+    // Only cached channels are used, since Discord doesn't wait long for autocomplete responses
+    let get_channel_name = |message: &serenity::Message| {
+        let guild = ctx.cache().guild(message.guild_id?)?;
+        guild
+            .channels
+            .get(&message.channel_id)
+            .map(|channel| channel.name.clone())
+            .or_else(|| {
+                guild
+                    .threads
+                    .iter()
+                    .find(|thread| thread.id == message.channel_id)
+                    .map(|thread| thread.name.clone())
+            })
+    };
+    search_bookmarks(&bookmarks, partial.trim(), get_channel_name)
+        .into_iter()
+        .map(|bookmark| {
+            let channel_name = get_channel_name(bookmark.message());
+            get_bookmark_autocomplete_choice(bookmark, channel_name.as_deref())
+        })
+        .collect()
+}
+
 /// Show this help menu
 #[poise::command(prefix_command, track_edits, slash_command)]
 pub async fn help(
@@ -70,17 +159,18 @@ pub async fn help(
 
 /// Get a subset of your pending reminders
 #[poise::command(slash_command)]
-pub async fn get_reminders(
-    ctx: Context<'_>,
-    #[description = "Maximum quantity of reminders to fetch. Defaults to 20."]
+pub async fn get_reminders<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "Maximum quantity of reminders to fetch. Defaults to 20 unless configured otherwise."]
     #[min = 1]
     #[max = 100]
     maximum_quantity: Option<u64>,
 ) -> Result<(), Error> {
-    let quantity_to_retrieve = maximum_quantity.unwrap_or(20);
+    let quantity_to_retrieve =
+        maximum_quantity.unwrap_or(ctx.data().attendant().config.lists.default_reminder_count);
 
     let reminders = get_reminders_for_user(
-        &ctx.data().db_connection,
+        &ctx.data().attendant().db_connection,
         ctx.author().id.get(),
         quantity_to_retrieve,
     )
@@ -96,50 +186,719 @@ pub async fn get_reminders(
     Ok(())
 }
 
-pub async fn add_reminder(ctx: &Context<'_>, reminder: Reminder) -> Result<(), Error> {
-    let reminder = insert_reminder(&ctx.data().db_connection, reminder).await?;
+/// Whether privacy mode (the user's, or that of the guild the message is from) means only the message's IDs may be stored
+async fn should_omit_message_content<U: AttendantState>(
+    ctx: &Context<'_, U>,
+    message: &serenity::Message,
+) -> Result<bool, Error> {
+    Ok(is_privacy_mode_enabled(
+        &ctx.data().attendant().db_connection,
+        ctx.author().id.get(),
+        ctx.guild_id().or(message.guild_id),
+    )
+    .await?)
+}
+
+/// Returns the primary key of the persisted reminder
+pub async fn add_reminder<U: AttendantState>(
+    ctx: &Context<'_, U>,
+    reminder: Reminder,
+) -> Result<i64, Error> {
+    let reminder = insert_reminder(&ctx.data().attendant().db_connection, reminder).await?;
+    tracing::Span::current().record("reminder_pk", reminder.pk());
+    let reminder_pk = reminder.pk();
 
-    ctx.data().tx.send(reminder).await?;
+    ctx.data().attendant().tx.send(reminder).await?;
 
-    Ok(())
+    Ok(reminder_pk)
 }
 
 #[poise::command(context_menu_command = "Remind me in 10 seconds")]
-pub async fn remind_me_in_10_seconds(
-    ctx: Context<'_>,
+pub async fn remind_me_in_10_seconds<U: AttendantState>(
+    ctx: Context<'_, U>,
     message: serenity::Message,
 ) -> Result<(), Error> {
     let remind_at = chrono::Utc::now() + chrono::Duration::seconds(10);
 
-    let reminder = Reminder::new(ctx.author().id.get(), message, remind_at);
+    remind_about_message(&ctx, message, remind_at).await
+}
+
+/// Get reminded of a message by its link, for when the context menu isn't available
+#[poise::command(slash_command, rename = "remind")]
+pub async fn remind_by_link<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "A link to the message, from \"Copy Message Link\""] link: String,
+    #[description = "How long from now, e.g. 10m, 1h30m or 2d"] when: String,
+) -> Result<(), Error> {
+    let wait_duration = parse_offset(when.trim())
+        .filter(|duration| *duration > chrono::Duration::zero())
+        .ok_or_else(|| UserError::InvalidDuration(when.clone()))?;
+    let remind_at = chrono::Utc::now()
+        .checked_add_signed(wait_duration)
+        .ok_or(UserError::InvalidDuration(when))?;
+
+    ctx.defer_ephemeral().await?;
+    let message = fetch_message_by_link(&ctx, &link).await?;
+
+    remind_about_message(&ctx, message, remind_at).await
+}
+
+async fn fetch_message_by_link<U: AttendantState>(
+    ctx: &Context<'_, U>,
+    link: &str,
+) -> Result<serenity::Message, Error> {
+    let message_link =
+        parse_message_link(link).ok_or_else(|| UserError::InvalidMessageLink(link.to_string()))?;
+    fetch_linked_message(ctx.serenity_context(), ctx.author().id, &message_link).await
+}
+
+async fn remind_about_message<U: AttendantState>(
+    ctx: &Context<'_, U>,
+    message: serenity::Message,
+    remind_at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), Error> {
+    let omit_message_content = should_omit_message_content(ctx, &message).await?;
+    let mut reminder = Reminder::new(ctx.author().id.get(), message, remind_at);
+    if omit_message_content {
+        reminder = reminder.without_message_content();
+    }
+
+    let reminder_pk = add_reminder(ctx, reminder).await?;
+
+    ctx.send(get_reminder_created_reply(&remind_at, reminder_pk))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, poise::Modal)]
+#[name = "Countdown to deadline"]
+struct CountdownModal {
+    #[name = "Deadline (YYYY-MM-DD HH:MM in your timezone)"]
+    #[placeholder = "2025-01-31 17:00, or a Discord timestamp like <t:1738342800:R>"]
+    deadline: String,
+    #[name = "Alert me this long before"]
+    #[placeholder = "1d, 1h, 10m"]
+    offsets: String,
+}
+
+/// Get several alerts counting down to a deadline
+#[poise::command(context_menu_command = "Countdown to deadline")]
+pub async fn countdown<U: AttendantState>(
+    app_ctx: ApplicationContext<'_, U>,
+    message: serenity::Message,
+) -> Result<(), Error> {
+    let Some(modal) = poise::execute_modal(app_ctx, None::<CountdownModal>, None).await? else {
+        return Ok(());
+    };
+    let ctx = Context::Application(app_ctx);
+
+    let settings = get_user_settings_or_default(&ctx).await?;
+    let deadline = match parse_deadline(&modal.deadline, settings.timezone()) {
+        Ok(deadline) => deadline,
+        Err(_) => {
+            ctx.send(get_invalid_countdown_reply(
+                &modal.deadline,
+                "a date and time like `2025-01-31 17:00` or a Discord timestamp",
+            ))
+            .await?;
+            return Ok(());
+        }
+    };
+    let offsets = match parse_offsets(&modal.offsets) {
+        Ok(offsets) => offsets,
+        Err(_) => {
+            ctx.send(get_invalid_countdown_reply(
+                &modal.offsets,
+                &format!("up to {} offsets like `1d, 1h, 10m`", MAX_COUNTDOWN_OFFSETS),
+            ))
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let countdown = Countdown::new(
+        uuid::Uuid::new_v7(uuid::Timestamp::now(
+            ctx.data().attendant().uuid_context.as_ref(),
+        )),
+        deadline,
+        offsets,
+    );
+    let omit_message_content = should_omit_message_content(&ctx, &message).await?;
+    let alerts = countdown
+        .get_alerts(ctx.author().id.get(), &message, &chrono::Utc::now())
+        .into_iter()
+        .map(|alert| {
+            if omit_message_content {
+                alert.without_message_content()
+            } else {
+                alert
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if alerts.is_empty() {
+        ctx.send(get_countdown_alerts_in_past_reply(&deadline))
+            .await?;
+        return Ok(());
+    }
+
+    let alert_times = alerts
+        .iter()
+        .map(|alert| *alert.remind_at())
+        .collect::<Vec<_>>();
+    for alert in alerts {
+        add_reminder(&ctx, alert).await?;
+    }
+
+    ctx.send(get_countdown_created_reply(
+        countdown.countdown_id(),
+        countdown.deadline(),
+        &alert_times,
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[expect(clippy::unused_async)]
+async fn autocomplete_timezone<'a, U: AttendantState>(
+    _ctx: Context<'_, U>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.trim().to_lowercase();
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|timezone| timezone.name())
+        .filter(move |name| name.to_lowercase().contains(&partial))
+        // Discord rejects autocomplete responses with more than 25 choices
+        .take(25)
+        .map(str::to_string)
+}
+
+async fn get_user_settings_or_default<U: AttendantState>(
+    ctx: &Context<'_, U>,
+) -> Result<UserSettings, Error> {
+    let user_id = ctx.author().id.get();
+    Ok(
+        get_user_settings(&ctx.data().attendant().db_connection, user_id)
+            .await?
+            .unwrap_or_else(|| UserSettings::new(user_id)),
+    )
+}
+
+/// View or change your settings
+#[poise::command(
+    slash_command,
+    subcommands(
+        "show_settings",
+        "set_timezone",
+        "set_quiet_hours",
+        "clear_quiet_hours",
+        "set_digest",
+        "clear_digest",
+        "set_privacy_mode",
+        "set_retention",
+        "clear_retention",
+        "set_spaced_repetition"
+    ),
+    subcommand_required
+)]
+pub async fn settings<U: AttendantState>(_: Context<'_, U>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show your current settings
+#[poise::command(slash_command, rename = "show")]
+pub async fn show_settings<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let settings = get_user_settings_or_default(&ctx).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Set the timezone used to interpret your quiet hours
+#[poise::command(slash_command, rename = "timezone")]
+pub async fn set_timezone<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "An IANA timezone name, e.g. Europe/Berlin"]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: String,
+) -> Result<(), Error> {
+    let Ok(parsed_timezone) = timezone.trim().parse::<chrono_tz::Tz>() else {
+        ctx.send(get_invalid_setting_reply("timezone", &timezone))
+            .await?;
+        return Ok(());
+    };
+
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_timezone(parsed_timezone);
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Hold back non-urgent reminders during these hours of your day
+#[poise::command(slash_command, rename = "quiet_hours")]
+pub async fn set_quiet_hours<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "When quiet hours start, as HH:MM in your timezone (e.g. 22:00)"] start: String,
+    #[description = "When quiet hours end, as HH:MM in your timezone (e.g. 07:00)"] end: String,
+) -> Result<(), Error> {
+    let parse_time =
+        |time: &str| chrono::NaiveTime::parse_from_str(time.trim(), TIME_OF_DAY_FORMAT);
+
+    let (start, end) = match (parse_time(&start), parse_time(&end)) {
+        (Ok(start), Ok(end)) => (start, end),
+        (Err(_), _) => {
+            ctx.send(get_invalid_setting_reply("time (expected HH:MM)", &start))
+                .await?;
+            return Ok(());
+        }
+        (_, Err(_)) => {
+            ctx.send(get_invalid_setting_reply("time (expected HH:MM)", &end))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_quiet_hours(Some(QuietHours::new(start, end)));
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Stop holding back reminders during quiet hours
+#[poise::command(slash_command)]
+pub async fn clear_quiet_hours<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_quiet_hours(None);
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Get one message a day with your reminders and a few bookmarks instead of a ping per reminder
+#[poise::command(slash_command, rename = "digest")]
+pub async fn set_digest<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "When to send your digest, as HH:MM in your timezone (e.g. 08:00)"]
+    time: String,
+) -> Result<(), Error> {
+    let Ok(digest_time) = chrono::NaiveTime::parse_from_str(time.trim(), TIME_OF_DAY_FORMAT) else {
+        ctx.send(get_invalid_setting_reply("time (expected HH:MM)", &time))
+            .await?;
+        return Ok(());
+    };
+
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_digest_time(Some(digest_time));
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Go back to being pinged for each reminder when it's due
+#[poise::command(slash_command)]
+pub async fn clear_digest<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_digest_time(None);
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    // Reminders that came due while waiting for a digest were dropped by the scheduler,
+    // so hand them back to it now that they won't be delivered in a digest.
+    let overdue_reminders = get_reminders_due_before_for_user(
+        &ctx.data().attendant().db_connection,
+        ctx.author().id.get(),
+        chrono::Utc::now(),
+    )
+    .await?;
+    for reminder in overdue_reminders {
+        ctx.data().attendant().tx.send(reminder).await?;
+    }
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Only store links to the messages you bookmark or set reminders on, not their content
+#[poise::command(slash_command, rename = "privacy_mode")]
+pub async fn set_privacy_mode<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "Whether to stop storing the content of messages you bookmark or set reminders on"]
+    enabled: bool,
+) -> Result<(), Error> {
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_privacy_mode(enabled);
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Have your bookmarks come back to you after 1, 3, 7, 21 days and so on, so you don't forget them
+#[poise::command(slash_command, rename = "spaced_repetition")]
+pub async fn set_spaced_repetition<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "Whether to resurface the messages you've bookmarked at growing intervals"]
+    enabled: bool,
+) -> Result<(), Error> {
+    // Scheduling reviews for lots of existing bookmarks can take longer than Discord waits for a reply
+    ctx.defer_ephemeral().await?;
+    let data = ctx.data().attendant();
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_spaced_repetition(enabled);
+    let settings = upsert_user_settings(&data.db_connection, settings).await?;
+
+    if enabled {
+        let scheduled_count = start_reviews_for_existing_bookmarks(
+            &data.db_connection,
+            &data.tx,
+            ctx.author().id.get(),
+        )
+        .await?;
+        tracing::info!(scheduled_count, "Started reviews for existing bookmarks");
+    } else {
+        let dropped_count = stop_reviews(&data.db_connection, ctx.author().id.get()).await?;
+        tracing::info!(dropped_count, "Dropped pending bookmark reviews");
+    }
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Choose how long your bookmarks and their messages' content are kept, instead of the server's default
+#[poise::command(slash_command, rename = "retention")]
+pub async fn set_retention<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "Days to keep unstarred bookmarks for (0 keeps them forever)"]
+    #[min = 0]
+    bookmark_days: Option<u32>,
+    #[description = "Days to keep bookmarked messages' content for before keeping only a link (0 is forever)"]
+    #[min = 0]
+    message_content_days: Option<u32>,
+) -> Result<(), Error> {
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    if let Some(bookmark_days) = bookmark_days {
+        settings.set_bookmark_retention(Some(RetentionOverride::from_days(bookmark_days)));
+    }
+    if let Some(message_content_days) = message_content_days {
+        settings.set_message_content_retention(Some(RetentionOverride::from_days(
+            message_content_days,
+        )));
+    }
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Go back to the server's default retention policy
+#[poise::command(slash_command)]
+pub async fn clear_retention<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let mut settings = get_user_settings_or_default(&ctx).await?;
+    settings.set_bookmark_retention(None);
+    settings.set_message_content_retention(None);
+    let settings = upsert_user_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_user_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// View or change settings for this server
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands(
+        "show_server_settings",
+        "set_server_privacy_mode",
+        "set_server_reaction_shortcuts"
+    ),
+    subcommand_required,
+    default_member_permissions = "MANAGE_GUILD",
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn server_settings<U: AttendantState>(_: Context<'_, U>) -> Result<(), Error> {
+    Ok(())
+}
 
-    add_reminder(&ctx, reminder).await?;
+async fn get_guild_settings_or_default<U: AttendantState>(
+    ctx: &Context<'_, U>,
+) -> Result<Option<GuildSettings>, Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(None);
+    };
+    Ok(Some(
+        get_guild_settings(&ctx.data().attendant().db_connection, guild_id.get())
+            .await?
+            .unwrap_or_else(|| GuildSettings::new(guild_id.get())),
+    ))
+}
 
-    ctx.send(get_reminder_created_reply(&remind_at)).await?;
+/// Show this server's settings
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "show",
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn show_server_settings<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let Some(settings) = get_guild_settings_or_default(&ctx).await? else {
+        return Ok(());
+    };
+
+    ctx.send(get_guild_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Never store the content of this server's messages when members bookmark or set reminders on them
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "privacy_mode",
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn set_server_privacy_mode<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "Whether to stop storing the content of this server's messages"] enabled: bool,
+) -> Result<(), Error> {
+    let Some(mut settings) = get_guild_settings_or_default(&ctx).await? else {
+        return Ok(());
+    };
+    settings.set_privacy_mode(enabled);
+    let settings = upsert_guild_settings(&ctx.data().attendant().db_connection, settings).await?;
+
+    ctx.send(get_guild_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// Let members bookmark messages and set reminders on them by reacting with these emojis
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "reaction_shortcuts",
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn set_server_reaction_shortcuts<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "Whether reacting to messages with the shortcut emojis does anything"]
+    enabled: Option<bool>,
+    #[description = "The emoji that bookmarks a message (defaults to 🔖)"] bookmark: Option<String>,
+    #[description = "The emoji that sends you a reminder time picker for a message (defaults to ⏰)"]
+    remind: Option<String>,
+    #[description = "The emoji that reminds you of a message this time tomorrow (defaults to 📌)"]
+    remind_tomorrow: Option<String>,
+) -> Result<(), Error> {
+    let Some(mut settings) = get_guild_settings_or_default(&ctx).await? else {
+        return Ok(());
+    };
+
+    let current_emojis = settings.reaction_shortcut_emojis();
+    let emojis = match ReactionShortcutEmojis::new(
+        bookmark.as_deref().unwrap_or(current_emojis.bookmark()),
+        remind.as_deref().unwrap_or(current_emojis.remind()),
+        remind_tomorrow
+            .as_deref()
+            .unwrap_or(current_emojis.remind_tomorrow()),
+    ) {
+        Ok(emojis) => emojis,
+        Err(InvalidReactionShortcutEmojisError::NotAnEmoji(emoji)) => {
+            ctx.send(get_invalid_setting_reply("emoji", &emoji)).await?;
+            return Ok(());
+        }
+        Err(InvalidReactionShortcutEmojisError::Duplicate(emoji)) => {
+            ctx.send(get_invalid_setting_reply(
+                "emoji (each shortcut needs its own)",
+                &emoji,
+            ))
+            .await?;
+            return Ok(());
+        }
+    };
+
+    settings.set_reaction_shortcut_emojis(emojis);
+    if let Some(enabled) = enabled {
+        settings.set_reaction_shortcuts_enabled(enabled);
+    }
+    let settings = upsert_guild_settings(&ctx.data().attendant().db_connection, settings).await?;
+    ctx.data()
+        .attendant()
+        .reaction_shortcut_emojis
+        .update(&settings);
+
+    ctx.send(get_guild_settings_reply(&settings)).await?;
+
+    Ok(())
+}
+
+/// How long the `/forget_me` confirmation buttons keep working
+const FORGET_ME_CONFIRMATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Delete all of your bookmarks, reminders and settings
+#[poise::command(slash_command)]
+pub async fn forget_me<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let confirmation_id = uuid::Uuid::new_v7(uuid::Timestamp::now(
+        ctx.data().attendant().uuid_context.as_ref(),
+    ));
+    let reply_handle = ctx
+        .send(get_forget_me_confirmation_reply(confirmation_id))
+        .await?;
+
+    let confirm_button_id = String::from(InteractionCustomId::ConfirmForgetMe(confirmation_id));
+    let cancel_button_id = String::from(InteractionCustomId::CancelForgetMe(confirmation_id));
+    let press = {
+        let (confirm_button_id, cancel_button_id) =
+            (confirm_button_id.clone(), cancel_button_id.clone());
+        serenity::ComponentInteractionCollector::new(ctx.serenity_context())
+            .author_id(ctx.author().id)
+            .filter(move |press| {
+                press.data.custom_id == confirm_button_id
+                    || press.data.custom_id == cancel_button_id
+            })
+            .timeout(FORGET_ME_CONFIRMATION_TIMEOUT)
+            .await
+    };
+
+    let Some(press) = press else {
+        reply_handle
+            .edit(ctx, get_forget_me_timed_out_reply())
+            .await?;
+        return Ok(());
+    };
+
+    let reply = if press.data.custom_id == confirm_button_id {
+        let user_id = ctx.author().id.get();
+        let deleted_user_data =
+            delete_all_user_data(&ctx.data().attendant().db_connection, user_id).await?;
+        // The rows are already gone, so the scheduler would skip these reminders anyway,
+        // but there's no reason to keep the messages they hold in memory until then
+        ctx.data()
+            .attendant()
+            .cancel_user_reminders_tx
+            .send(user_id)
+            .await?;
+        ctx.data()
+            .attendant()
+            .held_catch_up_summaries
+            .cancel(user_id);
+        ctx.data()
+            .attendant()
+            .bookmark_search_cache
+            .invalidate(user_id);
+        get_forget_me_completed_reply(&deleted_user_data)
+    } else {
+        get_forget_me_cancelled_reply()
+    };
+
+    press
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                reply.to_slash_initial_response(serenity::CreateInteractionResponseMessage::new()),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Get a copy of everything stored about you
+#[poise::command(slash_command)]
+pub async fn my_data<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let archive = get_user_data_archive(
+        &ctx.data().attendant().db_connection,
+        ctx.author().id.get(),
+        chrono::Utc::now(),
+    )
+    .await?;
+
+    ctx.author().dm(ctx, get_my_data_message(&archive)?).await?;
+
+    ctx.send(get_my_data_sent_reply()).await?;
+
+    Ok(())
+}
+
+/// Gets the caller's bookmark chosen with [`autocomplete_bookmark`]
+async fn get_own_bookmark<U: AttendantState>(
+    ctx: &Context<'_, U>,
+    bookmark_id: &str,
+) -> Result<PersistedBookmarkedMessage, Error> {
+    // Anything that isn't an ID was typed without picking one of the choices
+    let bookmark_id =
+        uuid::Uuid::parse_str(bookmark_id.trim()).map_err(|_| UserError::BookmarkNotFound)?;
+    let bookmark = get_bookmark_by_id(&ctx.data().attendant().db_connection, bookmark_id)
+        .await?
+        .ok_or(UserError::BookmarkNotFound)?;
+    if bookmark.user_id() != ctx.author().id.get() {
+        return Err(UserError::NotYourBookmark.into());
+    }
+    tracing::Span::current().record("bookmark_id", bookmark.bookmark_id().to_string());
+    Ok(bookmark)
+}
+
+/// Delete one of your bookmarks
+#[poise::command(slash_command)]
+pub async fn delete_bookmark<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "Start typing to search your bookmarks"]
+    #[autocomplete = "autocomplete_bookmark"]
+    bookmark: String,
+) -> Result<(), Error> {
+    let bookmark = get_own_bookmark(&ctx, &bookmark).await?;
+
+    delete_bookmarks_by_pk(&ctx.data().attendant().db_connection, vec![bookmark.pk()]).await?;
+    ctx.data()
+        .attendant()
+        .bookmark_search_cache
+        .invalidate(ctx.author().id.get());
+
+    ctx.send(get_bookmark_deleted_reply()).await?;
 
     Ok(())
 }
 
 /// Get a subset of your bookmarks
 #[poise::command(slash_command)]
-pub async fn get_bookmarks(
-    ctx: Context<'_>,
+pub async fn get_bookmarks<U: AttendantState>(
+    ctx: Context<'_, U>,
     #[description = "Offset to start fetching bookmarks from. Defaults to 0."]
     #[min = 0]
     offset: Option<u64>,
-    #[description = "Maximum quantity of bookmarks to fetch. Defaults to 20."]
+    #[description = "Maximum quantity of bookmarks to fetch. Defaults to 20 unless configured otherwise."]
     #[min = 1]
     #[max = 100]
     maximum_quantity: Option<u64>,
+    #[description = "Only show bookmarks you haven't marked as read. Defaults to false."]
+    unread_only: Option<bool>,
 ) -> Result<(), Error> {
     let offset = offset.unwrap_or(0);
-    let quantity_to_retrieve = maximum_quantity.unwrap_or(20);
+    let quantity_to_retrieve =
+        maximum_quantity.unwrap_or(ctx.data().attendant().config.lists.default_bookmark_count);
 
-    let bookmarks = crate::database::bookmark::get_bookmarks_for_user(
-        &ctx.data().db_connection,
+    let bookmarks = get_bookmarks_for_user(
+        &ctx.data().attendant().db_connection,
         ctx.author().id.get(),
         quantity_to_retrieve,
         offset,
+        unread_only.unwrap_or(false),
     )
     .await?;
 
@@ -148,25 +907,114 @@ pub async fn get_bookmarks(
         return Ok(());
     }
 
-    ctx.send(create_get_bookmarks_reply(&bookmarks, ctx.cache()))
-        .await?;
+    // Fetching messages whose content wasn't stored can take longer than Discord waits for a response
+    if bookmarks
+        .iter()
+        .any(|bookmark| bookmark.is_content_omitted())
+    {
+        ctx.defer_ephemeral().await?;
+    }
+    let mut displayable_bookmarks = Vec::with_capacity(bookmarks.len());
+    for bookmark in bookmarks {
+        displayable_bookmarks.push(with_live_bookmark_message(ctx, bookmark).await);
+    }
+
+    let counts =
+        count_bookmarks_for_user(&ctx.data().attendant().db_connection, ctx.author().id.get())
+            .await?;
+
+    ctx.send(create_get_bookmarks_reply(
+        &displayable_bookmarks,
+        counts,
+        ctx.cache(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Show your oldest unread bookmark, to work through your bookmarks like a reading list
+#[poise::command(slash_command)]
+pub async fn next_bookmark<U: AttendantState>(ctx: Context<'_, U>) -> Result<(), Error> {
+    let db_connection = &ctx.data().attendant().db_connection;
+    let Some(bookmark) =
+        get_oldest_unread_bookmark_for_user(db_connection, ctx.author().id.get()).await?
+    else {
+        ctx.send(get_no_unread_bookmarks_reply()).await?;
+        return Ok(());
+    };
+    tracing::Span::current().record("bookmark_id", bookmark.bookmark_id().to_string());
+
+    // Fetching a message whose content wasn't stored can take longer than Discord waits for a response
+    if bookmark.is_content_omitted() {
+        ctx.defer_ephemeral().await?;
+    }
+    let counts = count_bookmarks_for_user(db_connection, ctx.author().id.get()).await?;
+    let bookmark = with_live_bookmark_message(ctx, bookmark).await;
+    let channel_name = bookmark.message().channel_id.name(ctx.http()).await?;
+
+    ctx.send(get_next_bookmark_reply(
+        &bookmark,
+        &channel_name,
+        counts.unread(),
+    ))
+    .await?;
 
     Ok(())
 }
 
-#[poise::command(context_menu_command = "Bookmark")]
-pub async fn bookmark(ctx: Context<'_>, message: serenity::Message) -> Result<(), Error> {
-    let bookmark = crate::models::bookmark::BookmarkedMessage::new(
-        uuid::Uuid::new_v7(uuid::Timestamp::now(ctx.data().uuid_context.as_ref())),
+#[poise::command(context_menu_command = "Bookmark", rename = "bookmark_message")]
+pub async fn bookmark<U: AttendantState>(
+    ctx: Context<'_, U>,
+    message: serenity::Message,
+) -> Result<(), Error> {
+    bookmark_message(ctx, message).await
+}
+
+/// Bookmark a message by its link, for when the context menu isn't available
+#[poise::command(slash_command, rename = "bookmark")]
+pub async fn bookmark_by_link<U: AttendantState>(
+    ctx: Context<'_, U>,
+    #[description = "A link to the message, from \"Copy Message Link\""] link: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let message = fetch_message_by_link(&ctx, &link).await?;
+
+    bookmark_message(ctx, message).await
+}
+
+async fn bookmark_message<U: AttendantState>(
+    ctx: Context<'_, U>,
+    message: serenity::Message,
+) -> Result<(), Error> {
+    let omit_message_content = should_omit_message_content(&ctx, &message).await?;
+    let mut bookmark = crate::models::bookmark::BookmarkedMessage::new(
+        uuid::Uuid::new_v7(uuid::Timestamp::now(
+            ctx.data().attendant().uuid_context.as_ref(),
+        )),
         ctx.author().id.get(),
-        message,
+        message.clone(),
     );
+    if omit_message_content {
+        bookmark = bookmark.without_message_content();
+    }
 
     let inserted_bookmark =
-        crate::database::bookmark::insert_bookmark(&ctx.data().db_connection, bookmark).await;
+        crate::database::bookmark::insert_bookmark(&ctx.data().attendant().db_connection, bookmark)
+            .await;
 
     let (dm_message, message_reply) = match inserted_bookmark {
         Ok(bookmark) => {
+            tracing::Span::current().record("bookmark_id", bookmark.bookmark_id().to_string());
+            ctx.data().attendant().metrics.record_bookmark_created();
+            start_reviews_if_enabled(
+                &ctx.data().attendant().db_connection,
+                &ctx.data().attendant().tx,
+                &bookmark,
+            )
+            .await?;
+            // The message was just received, so there's no need to fetch it again even if its content wasn't stored
+            let bookmark = bookmark.with_message(message);
             let channel_name = bookmark.message().channel_id.name(ctx.http()).await?;
 
             (
@@ -179,6 +1027,7 @@ pub async fn bookmark(ctx: Context<'_>, message: serenity::Message) -> Result<()
             )
         }
         Err(InsertBookmarkError::BookmarkAlreadyExists(bookmark)) => {
+            let bookmark = with_live_bookmark_message(ctx, bookmark).await;
             let channel_name = bookmark.message().channel_id.name(ctx.http()).await?;
 
             (
@@ -190,15 +1039,15 @@ pub async fn bookmark(ctx: Context<'_>, message: serenity::Message) -> Result<()
                 get_bookmark_already_exists_reply(),
             )
         }
-        Err(other) => {
-            eprintln!("Failed to insert bookmark: {:?}", other);
+        Err(InsertBookmarkError::TokioRusqliteError(err)) => {
+            tracing::error!(error = %err, "Failed to insert bookmark");
 
             (None, get_failed_to_create_bookmark_reply())
         }
     };
 
-    async fn send_message(
-        ctx: &Context<'_>,
+    async fn send_message<U: AttendantState>(
+        ctx: &Context<'_, U>,
         dm_message: Option<CreateMessage>,
     ) -> Result<(), Error> {
         if let Some(dm_message) = dm_message {
@@ -211,7 +1060,10 @@ pub async fn bookmark(ctx: Context<'_>, message: serenity::Message) -> Result<()
         Ok(())
     }
 
-    async fn send_reply(ctx: &Context<'_>, message_reply: CreateReply) -> Result<(), Error> {
+    async fn send_reply<U: AttendantState>(
+        ctx: &Context<'_, U>,
+        message_reply: CreateReply,
+    ) -> Result<(), Error> {
         ctx.send(message_reply).await?;
         Ok(())
     }
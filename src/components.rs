@@ -1,8 +1,12 @@
 pub mod bookmark;
 pub mod delete_message_button;
+pub mod digest;
+pub mod error_reply;
 pub mod interaction_custom_id;
 pub mod relative_timestamp_string;
 pub mod reminder;
+pub mod user_data;
+pub mod user_settings;
 
 pub const DELETE_MESSAGE_EMOJI: &str = "🗑️";
 
@@ -21,6 +25,11 @@ fn trim_embed_field_name(field_name: &str) -> &str {
     &field_name[..field_name.len().min(MAX_EMBED_FIELD_NAME_LENGTH)]
 }
 
+const MAX_EMBED_FIELD_VALUE_LENGTH: usize = 1024;
+fn trim_embed_field_value(field_value: &str) -> &str {
+    &field_value[..field_value.len().min(MAX_EMBED_FIELD_VALUE_LENGTH)]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,4 +52,10 @@ mod tests {
         let trimmed_field_name = trim_embed_field_name(&field_name);
         trimmed_field_name.len() <= MAX_EMBED_FIELD_NAME_LENGTH
     }
+
+    #[quickcheck]
+    fn test_trim_embed_field_value(field_value: String) -> bool {
+        let trimmed_field_value = trim_embed_field_value(&field_value);
+        trimmed_field_value.len() <= MAX_EMBED_FIELD_VALUE_LENGTH
+    }
 }
@@ -1,4 +1,14 @@
+pub mod bookmark_autocomplete_choice;
 pub mod bookmark_created_reply;
+pub mod bookmark_deleted_reply;
 pub mod bookmark_message;
+pub mod bookmark_read_toggled_reply;
+pub mod bookmark_reminder_select_menu;
+pub mod bookmark_review_answered_reply;
+pub mod bookmark_review_message;
+pub mod bookmark_star_toggled_reply;
 pub mod get_bookmark_reply;
+pub mod next_bookmark_reply;
 pub mod no_bookmarks_found_reply;
+pub mod read_bookmark_button;
+pub mod star_bookmark_button;
@@ -0,0 +1,111 @@
+use poise::serenity_prelude as serenity;
+
+use crate::models::bookmark::PersistedBookmarkedMessage;
+
+/// Discord rejects autocomplete choices with longer names
+const MAX_CHOICE_NAME_LENGTH: usize = 100;
+
+/// Its value is the bookmark's ID, which is what commands taking a bookmark expect
+pub fn get_bookmark_autocomplete_choice(
+    bookmark: &PersistedBookmarkedMessage,
+    channel_name: Option<&str>,
+) -> serenity::AutocompleteChoice {
+    serenity::AutocompleteChoice::new(
+        get_bookmark_autocomplete_choice_name(bookmark, channel_name),
+        bookmark.bookmark_id().to_string(),
+    )
+}
+
+/// Like `alice: Deploy steps for… in #general`, leaving out whatever wasn't stored (e.g. because of privacy mode)
+pub fn get_bookmark_autocomplete_choice_name(
+    bookmark: &PersistedBookmarkedMessage,
+    channel_name: Option<&str>,
+) -> String {
+    let message = bookmark.message();
+    let author = message
+        .author
+        .global_name
+        .as_deref()
+        .unwrap_or(&message.author.name);
+    let preview = message
+        .content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let location = channel_name.map_or_else(
+        || format!("message {}", message.id),
+        |channel_name| format!("#{}", channel_name),
+    );
+
+    let name = match (author.is_empty(), preview.is_empty()) {
+        (false, false) => format!("{}: {}", author, preview),
+        (false, true) => format!("{} in {}", author, location),
+        (true, false) => preview,
+        (true, true) => format!("Bookmarked {}", location),
+    };
+    trim_choice_name(&name)
+}
+
+fn trim_choice_name(name: &str) -> String {
+    if name.chars().count() <= MAX_CHOICE_NAME_LENGTH {
+        return name.to_string();
+    }
+    let mut trimmed_name = name
+        .chars()
+        .take(MAX_CHOICE_NAME_LENGTH - 1)
+        .collect::<String>();
+    trimmed_name.push('…');
+    trimmed_name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::get_test_bookmark;
+
+    #[test]
+    fn test_get_bookmark_autocomplete_choice_name() {
+        let mut message = serenity::Message::default();
+        message.author.name = "alice".to_string();
+        message.content = "Deploy\n  steps".to_string();
+        let bookmark = get_test_bookmark(1, message);
+
+        assert_eq!(
+            get_bookmark_autocomplete_choice_name(&bookmark, Some("general")),
+            "alice: Deploy steps"
+        );
+    }
+
+    #[test]
+    fn test_get_bookmark_autocomplete_choice_name_without_content() {
+        let mut message = serenity::Message::default();
+        message.id = 3.into();
+        message.author.name = "alice".to_string();
+        assert_eq!(
+            get_bookmark_autocomplete_choice_name(
+                &get_test_bookmark(1, message.clone()),
+                Some("general")
+            ),
+            "alice in #general"
+        );
+
+        message.author.name = String::new();
+        assert_eq!(
+            get_bookmark_autocomplete_choice_name(&get_test_bookmark(1, message), None),
+            "Bookmarked message 3"
+        );
+    }
+
+    #[test]
+    fn test_get_bookmark_autocomplete_choice_name_is_trimmed() {
+        let mut message = serenity::Message::default();
+        message.author.name = "alice".to_string();
+        message.content = "ü".repeat(200);
+
+        let name = get_bookmark_autocomplete_choice_name(&get_test_bookmark(1, message), None);
+
+        assert_eq!(name.chars().count(), MAX_CHOICE_NAME_LENGTH);
+        assert!(name.starts_with("alice: üü"));
+        assert!(name.ends_with('…'));
+    }
+}
@@ -22,6 +22,7 @@ pub fn get_failed_to_create_bookmark_reply() -> CreateReply {
 }
 
 #[cfg(test)]
+#[expect(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
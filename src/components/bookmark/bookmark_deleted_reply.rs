@@ -0,0 +1,19 @@
+use poise::CreateReply;
+
+pub fn get_bookmark_deleted_reply() -> CreateReply {
+    CreateReply::default()
+        .content("Bookmark deleted.")
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_bookmark_deleted_reply() {
+        let reply = get_bookmark_deleted_reply();
+        assert_eq!(reply.content, Some("Bookmark deleted.".to_string()));
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
@@ -2,6 +2,10 @@ use poise::serenity_prelude::{Colour, CreateEmbed, CreateMessage};
 
 use crate::{
     components::{
+        bookmark::{
+            read_bookmark_button::get_read_bookmark_button,
+            star_bookmark_button::get_star_bookmark_button,
+        },
         delete_message_button::get_delete_button,
         interaction_custom_id::DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
         reminder::reminder_time_select_menu::select_menu::get_reminder_select_menu,
@@ -22,6 +26,8 @@ pub fn get_bookmark_message(
                 bookmark.bookmark_id(),
             ),
         ))
+        .button(get_star_bookmark_button(bookmark.bookmark_id()))
+        .button(get_read_bookmark_button(bookmark.bookmark_id()))
         .button(get_delete_button(
             DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
             delete_interaction_emoji,
@@ -0,0 +1,33 @@
+use poise::CreateReply;
+
+pub fn get_bookmark_read_toggled_reply(read: bool) -> CreateReply {
+    let content = if read {
+        "Marked as read. Use `/next_bookmark` for your next unread bookmark."
+    } else {
+        "Marked as unread. It'll come up again in `/next_bookmark`."
+    };
+
+    CreateReply::default().content(content).ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_bookmark_read_toggled_reply() {
+        let reply = get_bookmark_read_toggled_reply(true);
+        assert_eq!(
+            reply.content,
+            Some("Marked as read. Use `/next_bookmark` for your next unread bookmark.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+
+        let reply = get_bookmark_read_toggled_reply(false);
+        assert_eq!(
+            reply.content,
+            Some("Marked as unread. It'll come up again in `/next_bookmark`.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
@@ -0,0 +1,103 @@
+use poise::serenity_prelude as serenity;
+
+use crate::{
+    components::{
+        bookmark::bookmark_autocomplete_choice::get_bookmark_autocomplete_choice_name,
+        interaction_custom_id::InteractionCustomId,
+    },
+    models::bookmark::PersistedBookmarkedMessage,
+};
+
+/// Discord rejects select menus with more options
+const MAX_SELECT_MENU_OPTIONS: usize = 25;
+
+/// Lets the user pick one of the listed bookmarks to set a reminder on, after which they're asked when
+/// to be reminded. Only the first [`MAX_SELECT_MENU_OPTIONS`] bookmarks can be picked.
+pub fn get_bookmark_reminder_select_menu(
+    bookmarks: &[PersistedBookmarkedMessage],
+) -> serenity::CreateSelectMenu {
+    let options = bookmarks
+        .iter()
+        .take(MAX_SELECT_MENU_OPTIONS)
+        .map(|bookmark| {
+            serenity::CreateSelectMenuOption::new(
+                get_bookmark_autocomplete_choice_name(bookmark, None),
+                bookmark.bookmark_id().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    serenity::CreateSelectMenu::new(
+        InteractionCustomId::ChooseBookmarkToRemind,
+        serenity::CreateSelectMenuKind::String { options },
+    )
+    .min_values(1)
+    .max_values(1)
+    .placeholder("Set a reminder on one of these bookmarks")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::get_test_bookmark;
+
+    fn get_bookmark(pk: i64) -> PersistedBookmarkedMessage {
+        let mut message = serenity::Message::default();
+        message.author.name = "alice".to_string();
+        message.content = format!("Message {}", pk);
+        get_test_bookmark(pk, message)
+    }
+
+    #[test]
+    fn test_get_bookmark_reminder_select_menu() {
+        let bookmarks = vec![get_bookmark(1), get_bookmark(2)];
+
+        let select_menu = get_bookmark_reminder_select_menu(&bookmarks);
+
+        let expected_select_menu = serenity::CreateSelectMenu::new(
+            InteractionCustomId::ChooseBookmarkToRemind,
+            serenity::CreateSelectMenuKind::String {
+                options: vec![
+                    serenity::CreateSelectMenuOption::new(
+                        "alice: Message 1",
+                        bookmarks[0].bookmark_id().to_string(),
+                    ),
+                    serenity::CreateSelectMenuOption::new(
+                        "alice: Message 2",
+                        bookmarks[1].bookmark_id().to_string(),
+                    ),
+                ],
+            },
+        )
+        .min_values(1)
+        .max_values(1)
+        .placeholder("Set a reminder on one of these bookmarks");
+        assert_eq!(select_menu, expected_select_menu);
+    }
+
+    #[test]
+    fn test_get_bookmark_reminder_select_menu_caps_options() {
+        let bookmarks = (0..30).map(get_bookmark).collect::<Vec<_>>();
+
+        let select_menu = get_bookmark_reminder_select_menu(&bookmarks);
+
+        let expected_select_menu = serenity::CreateSelectMenu::new(
+            InteractionCustomId::ChooseBookmarkToRemind,
+            serenity::CreateSelectMenuKind::String {
+                options: bookmarks[..MAX_SELECT_MENU_OPTIONS]
+                    .iter()
+                    .map(|bookmark| {
+                        serenity::CreateSelectMenuOption::new(
+                            get_bookmark_autocomplete_choice_name(bookmark, None),
+                            bookmark.bookmark_id().to_string(),
+                        )
+                    })
+                    .collect(),
+            },
+        )
+        .min_values(1)
+        .max_values(1)
+        .placeholder("Set a reminder on one of these bookmarks");
+        assert_eq!(select_menu, expected_select_menu);
+    }
+}
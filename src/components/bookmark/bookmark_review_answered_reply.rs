@@ -0,0 +1,35 @@
+use poise::CreateReply;
+
+use crate::components::relative_timestamp_string::get_discord_relative_timestamp_string;
+
+pub fn get_bookmark_review_answered_reply(
+    next_review_at: &chrono::DateTime<chrono::Utc>,
+) -> CreateReply {
+    CreateReply::default()
+        .content(format!(
+            "Got it, I'll show you this bookmark again {}.",
+            get_discord_relative_timestamp_string(next_review_at)
+        ))
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_bookmark_review_answered_reply() {
+        let next_review_at = chrono::Utc::now();
+
+        let reply = get_bookmark_review_answered_reply(&next_review_at);
+
+        assert_eq!(
+            reply.content,
+            Some(format!(
+                "Got it, I'll show you this bookmark again <t:{}:R>.",
+                next_review_at.timestamp()
+            ))
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
@@ -0,0 +1,126 @@
+use poise::serenity_prelude::{
+    ButtonStyle, Colour, CreateButton, CreateEmbed, CreateEmbedFooter, CreateMessage,
+};
+
+use crate::{
+    components::{
+        delete_message_button::get_delete_button,
+        interaction_custom_id::{InteractionCustomId, DELETE_MESSAGE_INTERACTION_CUSTOM_ID},
+        trim_embed_description, trim_embed_title,
+    },
+    models::{
+        bookmark::PersistedBookmarkedMessage,
+        bookmark_review::{ReviewAnswer, ReviewStep},
+    },
+};
+
+/// Resurfaces a bookmark for spaced repetition, with buttons for how soon to see it again
+pub fn get_bookmark_review_message(
+    bookmark: &PersistedBookmarkedMessage,
+    bookmark_channel_name: &str,
+    review_step: ReviewStep,
+    delete_interaction_emoji: impl Into<String>,
+) -> CreateMessage {
+    CreateMessage::default()
+        .embed(get_bookmark_review_embed(
+            bookmark,
+            bookmark_channel_name,
+            review_step,
+        ))
+        .button(
+            CreateButton::new(InteractionCustomId::BookmarkReviewGotIt(
+                bookmark.bookmark_id(),
+            ))
+            .label("Got it")
+            .style(ButtonStyle::Success),
+        )
+        .button(
+            CreateButton::new(InteractionCustomId::BookmarkReviewShowSooner(
+                bookmark.bookmark_id(),
+            ))
+            .label("Show again sooner")
+            .style(ButtonStyle::Secondary),
+        )
+        .button(get_delete_button(
+            DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+            delete_interaction_emoji,
+        ))
+}
+
+pub fn get_bookmark_review_embed(
+    bookmark: &PersistedBookmarkedMessage,
+    bookmark_channel_name: &str,
+    review_step: ReviewStep,
+) -> CreateEmbed {
+    let title = format!("Remember this bookmark from {}?", bookmark_channel_name);
+    let trimmed_title = trim_embed_title(&title);
+
+    let description = format!(
+        "# {} \n # {}",
+        bookmark.message().content,
+        bookmark.message().link()
+    );
+    let trimmed_description = trim_embed_description(&description);
+
+    let footer = format!(
+        "Got it: see it again in {}. Show again sooner: in {}. Otherwise: in {}.",
+        get_days_string(review_step.after(ReviewAnswer::GotIt).interval()),
+        get_days_string(review_step.after(ReviewAnswer::ShowSooner).interval()),
+        get_days_string(review_step.interval())
+    );
+
+    CreateEmbed::default()
+        .title(trimmed_title)
+        .description(trimmed_description)
+        .footer(CreateEmbedFooter::new(footer))
+        .timestamp(bookmark.message().timestamp)
+        .colour(Colour::TEAL)
+}
+
+fn get_days_string(interval: chrono::Duration) -> String {
+    match interval.num_days() {
+        1 => "1 day".to_string(),
+        days => format!("{} days", days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::Message;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::bookmark::BookmarkedMessage;
+
+    /// [`CreateMessage`] doesn't impl [`PartialEq`] nor does it
+    /// expose any of it's fields, so I can't actually test any values of type
+    /// [`CreateMessage`] (e.g. the return value of [`get_bookmark_review_message`]).
+    /// The best I can do is test the [`CreateEmbed`] returned by [`get_bookmark_review_embed`].
+    #[test]
+    fn test_get_bookmark_review_embed() {
+        let bookmark = PersistedBookmarkedMessage::from_bookmarked_message(
+            BookmarkedMessage::new(
+                Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                123456789,
+                Message::default(),
+            ),
+            1,
+        );
+
+        let embed = get_bookmark_review_embed(&bookmark, "test_channel", ReviewStep::FIRST);
+
+        let expected_embed = CreateEmbed::default()
+            .title("Remember this bookmark from test_channel?")
+            .description(format!(
+                "# {} \n # {}",
+                bookmark.message().content,
+                bookmark.message().link()
+            ))
+            .footer(CreateEmbedFooter::new(
+                "Got it: see it again in 3 days. Show again sooner: in 1 day. Otherwise: in 1 day.",
+            ))
+            .timestamp(bookmark.message().timestamp)
+            .colour(Colour::TEAL);
+        assert_eq!(embed, expected_embed);
+    }
+}
@@ -0,0 +1,36 @@
+use poise::CreateReply;
+
+pub fn get_bookmark_star_toggled_reply(starred: bool) -> CreateReply {
+    let content = if starred {
+        "Bookmark starred! It will be kept until you delete it."
+    } else {
+        "Bookmark unstarred. It may now expire according to the retention policy."
+    };
+
+    CreateReply::default().content(content).ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_bookmark_star_toggled_reply() {
+        let reply = get_bookmark_star_toggled_reply(true);
+        assert_eq!(
+            reply.content,
+            Some("Bookmark starred! It will be kept until you delete it.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+
+        let reply = get_bookmark_star_toggled_reply(false);
+        assert_eq!(
+            reply.content,
+            Some(
+                "Bookmark unstarred. It may now expire according to the retention policy."
+                    .to_string()
+            )
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
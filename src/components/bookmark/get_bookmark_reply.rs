@@ -1,15 +1,19 @@
 use poise::{
-    serenity_prelude::{self as serenity, CreateEmbed},
+    serenity_prelude::{self as serenity, CreateActionRow, CreateEmbed},
     CreateReply,
 };
 
 use crate::{
-    components::{trim_embed_description, trim_embed_field_name, trim_embed_title},
-    models::bookmark::PersistedBookmarkedMessage,
+    components::{
+        bookmark::bookmark_reminder_select_menu::get_bookmark_reminder_select_menu,
+        trim_embed_description, trim_embed_field_name, trim_embed_title,
+    },
+    models::bookmark::{BookmarkCounts, PersistedBookmarkedMessage},
 };
 
 pub fn create_get_bookmarks_reply(
     bookmarks: &[PersistedBookmarkedMessage],
+    counts: BookmarkCounts,
     cache: &serenity::Cache,
 ) -> CreateReply {
     let title = format!(
@@ -19,7 +23,12 @@ pub fn create_get_bookmarks_reply(
     );
     let trimmed_title = trim_embed_title(&title);
 
-    let description = format!("## Retrieved Bookmarks: {}", bookmarks.len());
+    let description = format!(
+        "## Retrieved Bookmarks: {}\nUnread: {} of {}",
+        bookmarks.len(),
+        counts.unread(),
+        counts.total()
+    );
     let trimmed_description = trim_embed_description(&description);
 
     const MESSAGE_PREVIEW_LENGTH: usize = 33;
@@ -63,10 +72,14 @@ pub fn create_get_bookmarks_reply(
                 }))
                 .colour(serenity::Colour::TEAL),
         )
+        .components(vec![CreateActionRow::SelectMenu(
+            get_bookmark_reminder_select_menu(bookmarks),
+        )])
         .ephemeral(true)
 }
 
 #[cfg(test)]
+#[expect(clippy::get_first)]
 mod tests {
     use uuid::Uuid;
 
@@ -100,7 +113,11 @@ mod tests {
             1,
         )];
 
-        let reply = create_get_bookmarks_reply(&bookmarks, &serenity::Cache::new());
+        let reply = create_get_bookmarks_reply(
+            &bookmarks,
+            BookmarkCounts::new(5, 2),
+            &serenity::Cache::new(),
+        );
 
         assert_eq!(reply.ephemeral, Some(true));
 
@@ -109,7 +126,7 @@ mod tests {
 
         let expected_embed = CreateEmbed::default()
             .title("Retrieved up to 1 bookmark.\nThere may be more bookmarks not shown.")
-            .description("## Retrieved Bookmarks: 1")
+            .description("## Retrieved Bookmarks: 1\nUnread: 2 of 5")
             .field(
                 format!(
                     "https://discord.com/channels/{}/{}/{}",
@@ -121,6 +138,12 @@ mod tests {
             .colour(serenity::Colour::TEAL);
 
         assert_eq!(embed, expected_embed);
+        assert_eq!(
+            reply.components,
+            Some(vec![CreateActionRow::SelectMenu(
+                get_bookmark_reminder_select_menu(&bookmarks)
+            )])
+        );
     }
 
     #[test]
@@ -172,7 +195,11 @@ mod tests {
             ),
         ];
 
-        let reply = create_get_bookmarks_reply(&bookmarks, &serenity::Cache::new());
+        let reply = create_get_bookmarks_reply(
+            &bookmarks,
+            BookmarkCounts::new(5, 2),
+            &serenity::Cache::new(),
+        );
 
         assert_eq!(reply.ephemeral, Some(true));
 
@@ -181,7 +208,7 @@ mod tests {
 
         let expected_embed = CreateEmbed::default()
             .title("Retrieved up to 2 bookmarks.\nThere may be more bookmarks not shown.")
-            .description("## Retrieved Bookmarks: 2")
+            .description("## Retrieved Bookmarks: 2\nUnread: 2 of 5")
             .field(
                 format!(
                     "https://discord.com/channels/@me/{}/{}",
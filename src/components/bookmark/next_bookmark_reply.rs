@@ -0,0 +1,110 @@
+use poise::{
+    serenity_prelude::{CreateActionRow, CreateEmbed, CreateEmbedFooter},
+    CreateReply,
+};
+
+use crate::{
+    components::{
+        bookmark::{
+            bookmark_message::get_bookmark_embed, read_bookmark_button::get_read_bookmark_button,
+        },
+        interaction_custom_id::InteractionCustomId,
+        reminder::reminder_time_select_menu::select_menu::get_reminder_select_menu,
+    },
+    models::bookmark::PersistedBookmarkedMessage,
+};
+
+/// Shows the oldest unread bookmark like its DM, with the "Mark as read" button for moving on to the next one
+pub fn get_next_bookmark_reply(
+    bookmark: &PersistedBookmarkedMessage,
+    bookmark_channel_name: &str,
+    unread_count: u64,
+) -> CreateReply {
+    CreateReply::default()
+        .embed(get_next_bookmark_embed(
+            bookmark,
+            bookmark_channel_name,
+            unread_count,
+        ))
+        .components(vec![
+            CreateActionRow::SelectMenu(get_reminder_select_menu(
+                InteractionCustomId::SetReminder(bookmark.bookmark_id()),
+            )),
+            CreateActionRow::Buttons(vec![get_read_bookmark_button(bookmark.bookmark_id())]),
+        ])
+        .ephemeral(true)
+}
+
+fn get_next_bookmark_embed(
+    bookmark: &PersistedBookmarkedMessage,
+    bookmark_channel_name: &str,
+    unread_count: u64,
+) -> CreateEmbed {
+    get_bookmark_embed(bookmark, bookmark_channel_name).footer(CreateEmbedFooter::new(format!(
+        "{} unread bookmark{}",
+        unread_count,
+        if unread_count == 1 { "" } else { "s" }
+    )))
+}
+
+pub fn get_no_unread_bookmarks_reply() -> CreateReply {
+    CreateReply::default()
+        .content("You're all caught up: none of your bookmarks are unread.")
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::Message;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::bookmark::BookmarkedMessage;
+
+    #[test]
+    fn test_get_next_bookmark_reply() {
+        let bookmark = PersistedBookmarkedMessage::from_bookmarked_message(
+            BookmarkedMessage::new(
+                Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                123456789,
+                Message::default(),
+            ),
+            1,
+        );
+
+        let reply = get_next_bookmark_reply(&bookmark, "test_channel", 3);
+
+        assert_eq!(reply.ephemeral, Some(true));
+        assert_eq!(
+            reply.embeds,
+            vec![get_bookmark_embed(&bookmark, "test_channel")
+                .footer(CreateEmbedFooter::new("3 unread bookmarks"))]
+        );
+        assert_eq!(
+            reply.components,
+            Some(vec![
+                CreateActionRow::SelectMenu(get_reminder_select_menu(
+                    InteractionCustomId::SetReminder(bookmark.bookmark_id())
+                )),
+                CreateActionRow::Buttons(vec![get_read_bookmark_button(bookmark.bookmark_id())]),
+            ])
+        );
+
+        let reply = get_next_bookmark_reply(&bookmark, "test_channel", 1);
+        assert_eq!(
+            reply.embeds,
+            vec![get_bookmark_embed(&bookmark, "test_channel")
+                .footer(CreateEmbedFooter::new("1 unread bookmark"))]
+        );
+    }
+
+    #[test]
+    fn test_get_no_unread_bookmarks_reply() {
+        let reply = get_no_unread_bookmarks_reply();
+        assert_eq!(
+            reply.content,
+            Some("You're all caught up: none of your bookmarks are unread.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
@@ -0,0 +1,32 @@
+use poise::serenity_prelude::{ButtonStyle, CreateButton, ReactionType};
+use uuid::Uuid;
+
+use crate::components::interaction_custom_id::InteractionCustomId;
+
+pub const STAR_BOOKMARK_EMOJI: &str = "⭐";
+
+pub fn get_star_bookmark_button(bookmark_id: Uuid) -> CreateButton {
+    CreateButton::new(InteractionCustomId::ToggleBookmarkStar(bookmark_id))
+        .label("Star/unstar (starred bookmarks never expire)")
+        .emoji(ReactionType::Unicode(STAR_BOOKMARK_EMOJI.to_string()))
+        .style(ButtonStyle::Secondary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_star_bookmark_button() {
+        let bookmark_id = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+
+        let button = get_star_bookmark_button(bookmark_id);
+
+        let expected_button = CreateButton::new(format!("toggle_bookmark_star:{}", bookmark_id))
+            .label("Star/unstar (starred bookmarks never expire)")
+            .emoji(ReactionType::Unicode("⭐".to_string()))
+            .style(ButtonStyle::Secondary);
+
+        assert_eq!(button, expected_button);
+    }
+}
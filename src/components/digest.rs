@@ -0,0 +1,2 @@
+pub mod digest_expired_reply;
+pub mod digest_message;
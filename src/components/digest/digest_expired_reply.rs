@@ -0,0 +1,22 @@
+use poise::CreateReply;
+
+pub fn get_digest_expired_reply() -> CreateReply {
+    CreateReply::default()
+        .content("This digest has expired, so its pages can't be turned anymore.")
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_digest_expired_reply() {
+        let reply = get_digest_expired_reply();
+        assert_eq!(
+            reply.content,
+            Some("This digest has expired, so its pages can't be turned anymore.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
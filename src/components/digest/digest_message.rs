@@ -0,0 +1,245 @@
+use poise::serenity_prelude::{
+    ButtonStyle, Colour, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateMessage,
+};
+use uuid::Uuid;
+
+use crate::{
+    components::{
+        bookmark::bookmark_message::get_bookmark_embed,
+        delete_message_button::get_delete_button,
+        interaction_custom_id::{InteractionCustomId, DELETE_MESSAGE_INTERACTION_CUSTOM_ID},
+        relative_timestamp_string::get_discord_relative_timestamp_string,
+        reminder::reminder_message::get_reminder_embed,
+        trim_embed_description, trim_embed_field_name, trim_embed_title,
+    },
+    models::{bookmark::PersistedBookmarkedMessage, reminder::PersistedReminder},
+};
+
+/// Discord rejects embeds with more than 25 fields
+const MAX_OVERVIEW_FIELDS: usize = 25;
+
+/// The first page summarizes the digest, followed by one page per reminder and then one page per bookmark.
+/// Each reminder and bookmark is paired with the name of the channel its message was sent in.
+pub fn get_digest_pages(
+    local_date: chrono::NaiveDate,
+    reminders: &[(PersistedReminder, String)],
+    bookmarks: &[(PersistedBookmarkedMessage, String)],
+) -> Vec<CreateEmbed> {
+    let title = format!("Your digest for {}", local_date.format("%A, %B %-d"));
+    let trimmed_title = trim_embed_title(&title);
+
+    let description = format!(
+        "## Reminders: {}\n## Bookmarks to revisit: {}",
+        reminders.len(),
+        bookmarks.len()
+    );
+    let trimmed_description = trim_embed_description(&description);
+
+    let overview = CreateEmbed::default()
+        .title(trimmed_title)
+        .description(trimmed_description)
+        .fields(
+            reminders
+                .iter()
+                .take(MAX_OVERVIEW_FIELDS)
+                .map(|(reminder, _)| {
+                    let field_name = format!(
+                        "{} at: {}",
+                        reminder.message().link(),
+                        get_discord_relative_timestamp_string(reminder.remind_at())
+                    );
+                    let trimmed_field_name = trim_embed_field_name(&field_name);
+                    (trimmed_field_name.to_owned(), "", true)
+                }),
+        )
+        .colour(Colour::TEAL);
+
+    let pages = std::iter::once(overview)
+        .chain(
+            reminders
+                .iter()
+                .map(|(reminder, channel_name)| get_reminder_embed(reminder, channel_name)),
+        )
+        .chain(
+            bookmarks
+                .iter()
+                .map(|(bookmark, channel_name)| get_bookmark_embed(bookmark, channel_name)),
+        )
+        .collect::<Vec<_>>();
+
+    let page_count = pages.len();
+    pages
+        .into_iter()
+        .enumerate()
+        .map(|(page_index, page)| {
+            page.footer(CreateEmbedFooter::new(format!(
+                "Page {} of {}",
+                page_index + 1,
+                page_count
+            )))
+        })
+        .collect()
+}
+
+pub fn get_digest_message(
+    pages: &[CreateEmbed],
+    digest_id: Uuid,
+    delete_interaction_emoji: impl Into<String>,
+) -> CreateMessage {
+    let first_page = pages
+        .first()
+        .cloned()
+        .expect("A digest always has an overview page");
+
+    CreateMessage::default()
+        .embed(first_page)
+        .components(get_digest_page_components(
+            digest_id,
+            0,
+            pages.len(),
+            delete_interaction_emoji,
+        ))
+}
+
+pub fn get_digest_page_components(
+    digest_id: Uuid,
+    page_index: usize,
+    page_count: usize,
+    delete_interaction_emoji: impl Into<String>,
+) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(InteractionCustomId::PreviousDigestPage(digest_id))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page_index == 0),
+        CreateButton::new(InteractionCustomId::NextDigestPage(digest_id))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page_index + 1 >= page_count),
+        get_delete_button(
+            DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+            delete_interaction_emoji,
+        ),
+    ])]
+}
+
+/// Once a digest can no longer be paginated, only its delete button is left
+pub fn get_expired_digest_components(
+    delete_interaction_emoji: impl Into<String>,
+) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![get_delete_button(
+        DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+        delete_interaction_emoji,
+    )])]
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::Message;
+
+    use super::*;
+    use crate::models::{bookmark::BookmarkedMessage, reminder::Reminder};
+
+    #[test]
+    fn test_get_digest_pages() {
+        let timestamp = chrono::Utc::now();
+        let reminders = vec![(
+            PersistedReminder::from_reminder(
+                Reminder::new(123456789, Message::default(), timestamp),
+                1,
+            ),
+            "reminder_channel".to_string(),
+        )];
+        let bookmarks = vec![(
+            PersistedBookmarkedMessage::from_bookmarked_message(
+                BookmarkedMessage::new(
+                    Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                    123456789,
+                    Message::default(),
+                ),
+                1,
+            ),
+            "bookmark_channel".to_string(),
+        )];
+        let local_date = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+        let pages = get_digest_pages(local_date, &reminders, &bookmarks);
+
+        assert_eq!(pages.len(), 3);
+
+        let expected_overview = CreateEmbed::default()
+            .title("Your digest for Wednesday, January 1")
+            .description("## Reminders: 1\n## Bookmarks to revisit: 1")
+            .field(
+                format!(
+                    "{} at: <t:{}:R>",
+                    Message::default().link(),
+                    timestamp.timestamp()
+                ),
+                "",
+                true,
+            )
+            .colour(Colour::TEAL)
+            .footer(CreateEmbedFooter::new("Page 1 of 3"));
+        assert_eq!(pages[0], expected_overview);
+
+        assert_eq!(
+            pages[1],
+            get_reminder_embed(&reminders[0].0, "reminder_channel")
+                .footer(CreateEmbedFooter::new("Page 2 of 3"))
+        );
+        assert_eq!(
+            pages[2],
+            get_bookmark_embed(&bookmarks[0].0, "bookmark_channel")
+                .footer(CreateEmbedFooter::new("Page 3 of 3"))
+        );
+    }
+
+    #[test]
+    fn test_get_digest_page_components() {
+        let digest_id = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+
+        let expected_components = |previous_disabled: bool, next_disabled: bool| {
+            vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(InteractionCustomId::PreviousDigestPage(digest_id))
+                    .label("Previous")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(previous_disabled),
+                CreateButton::new(InteractionCustomId::NextDigestPage(digest_id))
+                    .label("Next")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(next_disabled),
+                get_delete_button(DELETE_MESSAGE_INTERACTION_CUSTOM_ID, "🗑️"),
+            ])]
+        };
+
+        assert_eq!(
+            get_digest_page_components(digest_id, 0, 3, "🗑️"),
+            expected_components(true, false)
+        );
+        assert_eq!(
+            get_digest_page_components(digest_id, 1, 3, "🗑️"),
+            expected_components(false, false)
+        );
+        assert_eq!(
+            get_digest_page_components(digest_id, 2, 3, "🗑️"),
+            expected_components(false, true)
+        );
+        assert_eq!(
+            get_digest_page_components(digest_id, 0, 1, "🗑️"),
+            expected_components(true, true)
+        );
+    }
+
+    #[test]
+    fn test_get_expired_digest_components() {
+        assert_eq!(
+            get_expired_digest_components("🗑️"),
+            vec![CreateActionRow::Buttons(vec![get_delete_button(
+                DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+                "🗑️"
+            )])]
+        );
+    }
+}
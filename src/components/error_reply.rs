@@ -0,0 +1,68 @@
+use poise::CreateReply;
+
+use crate::error::Error;
+
+/// Only user errors are explained, since the details of anything else wouldn't help the user and are logged instead
+pub fn get_error_reply(error: &Error) -> CreateReply {
+    let content = match error {
+        Error::User(err) => err.to_string(),
+        Error::Discord(_) => "I couldn't reach Discord. Please try again in a moment.".to_string(),
+        Error::Storage(_) => {
+            "Something went wrong while accessing my storage. Please try again later.".to_string()
+        }
+        Error::Internal(_) => "Something went wrong. Please try again later.".to_string(),
+    };
+
+    CreateReply::default().content(content).ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude as serenity;
+
+    use super::*;
+    use crate::error::UserError;
+
+    #[test]
+    fn test_get_error_reply_for_user_error() {
+        let reply = get_error_reply(&Error::User(UserError::NotYourBookmark));
+        assert_eq!(
+            reply.content,
+            Some("That's someone else's bookmark.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+
+    #[test]
+    fn test_get_error_reply_for_discord_error() {
+        let reply = get_error_reply(&Error::from(serenity::Error::Other("test")));
+        assert_eq!(
+            reply.content,
+            Some("I couldn't reach Discord. Please try again in a moment.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+
+    #[test]
+    fn test_get_error_reply_for_storage_error() {
+        let reply = get_error_reply(&Error::Storage(tokio_rusqlite::Error::ConnectionClosed));
+        assert_eq!(
+            reply.content,
+            Some(
+                "Something went wrong while accessing my storage. Please try again later."
+                    .to_string()
+            )
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+
+    #[test]
+    fn test_get_error_reply_does_not_leak_internal_errors() {
+        let reply = get_error_reply(&Error::from("secret internal details"));
+        assert_eq!(
+            reply.content,
+            Some("Something went wrong. Please try again later.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
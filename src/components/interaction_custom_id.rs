@@ -2,11 +2,53 @@ use uuid::Uuid;
 
 pub const DELETE_MESSAGE_INTERACTION_CUSTOM_ID: &str = "delete_message";
 pub const SET_REMINDER_INTERACTION_CUSTOM_ID: &str = "set_reminder";
+pub const MARK_REMINDER_URGENT_INTERACTION_CUSTOM_ID: &str = "mark_reminder_urgent";
+pub const PREVIOUS_DIGEST_PAGE_INTERACTION_CUSTOM_ID: &str = "previous_digest_page";
+pub const NEXT_DIGEST_PAGE_INTERACTION_CUSTOM_ID: &str = "next_digest_page";
+pub const CANCEL_COUNTDOWN_INTERACTION_CUSTOM_ID: &str = "cancel_countdown";
+pub const CONFIRM_FORGET_ME_INTERACTION_CUSTOM_ID: &str = "confirm_forget_me";
+pub const CANCEL_FORGET_ME_INTERACTION_CUSTOM_ID: &str = "cancel_forget_me";
+pub const TOGGLE_BOOKMARK_STAR_INTERACTION_CUSTOM_ID: &str = "toggle_bookmark_star";
+pub const TOGGLE_BOOKMARK_READ_INTERACTION_CUSTOM_ID: &str = "toggle_bookmark_read";
+pub const BOOKMARK_REVIEW_GOT_IT_INTERACTION_CUSTOM_ID: &str = "bookmark_review_got_it";
+pub const BOOKMARK_REVIEW_SHOW_SOONER_INTERACTION_CUSTOM_ID: &str = "bookmark_review_show_sooner";
+pub const CHOOSE_BOOKMARK_TO_REMIND_INTERACTION_CUSTOM_ID: &str = "choose_bookmark_to_remind";
+pub const SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID: &str = "set_message_reminder";
+/// Stands in for the guild ID of messages outside of guilds, like in message links
+const DIRECT_MESSAGE_GUILD_ID: &str = "@me";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InteractionCustomId {
     DeleteMessage,
     SetReminder(Uuid),
+    /// Holds the reminder's primary key
+    MarkReminderUrgent(i64),
+    /// Holds an ID unique to the digest message being paginated
+    PreviousDigestPage(Uuid),
+    /// Holds an ID unique to the digest message being paginated
+    NextDigestPage(Uuid),
+    /// Holds the ID shared by all of the countdown's alerts
+    CancelCountdown(Uuid),
+    /// Holds an ID unique to the `/forget_me` invocation being confirmed
+    ConfirmForgetMe(Uuid),
+    /// Holds an ID unique to the `/forget_me` invocation being confirmed
+    CancelForgetMe(Uuid),
+    /// Holds the bookmark's ID
+    ToggleBookmarkStar(Uuid),
+    /// Holds the bookmark's ID
+    ToggleBookmarkRead(Uuid),
+    /// Holds the bookmark's ID
+    BookmarkReviewGotIt(Uuid),
+    /// Holds the bookmark's ID
+    BookmarkReviewShowSooner(Uuid),
+    /// The chosen bookmark's ID is the select menu's value, since the menu lists several
+    ChooseBookmarkToRemind,
+    /// Holds where the message is, for reminders on messages that weren't bookmarked (see [`crate::reaction_shortcuts`])
+    SetMessageReminder {
+        guild_id: Option<u64>,
+        channel_id: u64,
+        message_id: u64,
+    },
 }
 
 impl From<InteractionCustomId> for String {
@@ -16,6 +58,58 @@ impl From<InteractionCustomId> for String {
             InteractionCustomId::SetReminder(uuid) => {
                 format!("{}:{}", SET_REMINDER_INTERACTION_CUSTOM_ID, uuid)
             }
+            InteractionCustomId::MarkReminderUrgent(reminder_pk) => {
+                format!(
+                    "{}:{}",
+                    MARK_REMINDER_URGENT_INTERACTION_CUSTOM_ID, reminder_pk
+                )
+            }
+            InteractionCustomId::PreviousDigestPage(uuid) => {
+                format!("{}:{}", PREVIOUS_DIGEST_PAGE_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::NextDigestPage(uuid) => {
+                format!("{}:{}", NEXT_DIGEST_PAGE_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::CancelCountdown(uuid) => {
+                format!("{}:{}", CANCEL_COUNTDOWN_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::ConfirmForgetMe(uuid) => {
+                format!("{}:{}", CONFIRM_FORGET_ME_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::CancelForgetMe(uuid) => {
+                format!("{}:{}", CANCEL_FORGET_ME_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::ToggleBookmarkStar(uuid) => {
+                format!("{}:{}", TOGGLE_BOOKMARK_STAR_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::ToggleBookmarkRead(uuid) => {
+                format!("{}:{}", TOGGLE_BOOKMARK_READ_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::BookmarkReviewGotIt(uuid) => {
+                format!("{}:{}", BOOKMARK_REVIEW_GOT_IT_INTERACTION_CUSTOM_ID, uuid)
+            }
+            InteractionCustomId::BookmarkReviewShowSooner(uuid) => {
+                format!(
+                    "{}:{}",
+                    BOOKMARK_REVIEW_SHOW_SOONER_INTERACTION_CUSTOM_ID, uuid
+                )
+            }
+            InteractionCustomId::ChooseBookmarkToRemind => {
+                CHOOSE_BOOKMARK_TO_REMIND_INTERACTION_CUSTOM_ID.to_string()
+            }
+            InteractionCustomId::SetMessageReminder {
+                guild_id,
+                channel_id,
+                message_id,
+            } => {
+                format!(
+                    "{}:{}:{}:{}",
+                    SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID,
+                    guild_id.map_or(DIRECT_MESSAGE_GUILD_ID.to_string(), |id| id.to_string()),
+                    channel_id,
+                    message_id
+                )
+            }
         }
     }
 }
@@ -27,6 +121,7 @@ impl TryFrom<&str> for InteractionCustomId {
         let parts: Vec<&str> = value.split(':').collect();
         match parts.as_slice() {
             [DELETE_MESSAGE_INTERACTION_CUSTOM_ID] => Ok(Self::DeleteMessage),
+            [CHOOSE_BOOKMARK_TO_REMIND_INTERACTION_CUSTOM_ID] => Ok(Self::ChooseBookmarkToRemind),
             [SET_REMINDER_INTERACTION_CUSTOM_ID, maybe_uuid] => {
                 let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
                     format!(
@@ -36,6 +131,116 @@ impl TryFrom<&str> for InteractionCustomId {
                 })?;
                 Ok(Self::SetReminder(uuid))
             }
+            [MARK_REMINDER_URGENT_INTERACTION_CUSTOM_ID, maybe_pk] => {
+                let reminder_pk = maybe_pk.parse::<i64>().map_err(|_| {
+                    format!(
+                        "Received invalid reminder ID for {}: {}",
+                        MARK_REMINDER_URGENT_INTERACTION_CUSTOM_ID, maybe_pk
+                    )
+                })?;
+                Ok(Self::MarkReminderUrgent(reminder_pk))
+            }
+            [PREVIOUS_DIGEST_PAGE_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        PREVIOUS_DIGEST_PAGE_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::PreviousDigestPage(uuid))
+            }
+            [NEXT_DIGEST_PAGE_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        NEXT_DIGEST_PAGE_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::NextDigestPage(uuid))
+            }
+            [CANCEL_COUNTDOWN_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        CANCEL_COUNTDOWN_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::CancelCountdown(uuid))
+            }
+            [CONFIRM_FORGET_ME_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        CONFIRM_FORGET_ME_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::ConfirmForgetMe(uuid))
+            }
+            [CANCEL_FORGET_ME_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        CANCEL_FORGET_ME_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::CancelForgetMe(uuid))
+            }
+            [TOGGLE_BOOKMARK_STAR_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        TOGGLE_BOOKMARK_STAR_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::ToggleBookmarkStar(uuid))
+            }
+            [TOGGLE_BOOKMARK_READ_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        TOGGLE_BOOKMARK_READ_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::ToggleBookmarkRead(uuid))
+            }
+            [BOOKMARK_REVIEW_GOT_IT_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        BOOKMARK_REVIEW_GOT_IT_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::BookmarkReviewGotIt(uuid))
+            }
+            [BOOKMARK_REVIEW_SHOW_SOONER_INTERACTION_CUSTOM_ID, maybe_uuid] => {
+                let uuid = Uuid::parse_str(maybe_uuid).map_err(|_| {
+                    format!(
+                        "Received invalid UUID for {}: {}",
+                        BOOKMARK_REVIEW_SHOW_SOONER_INTERACTION_CUSTOM_ID, maybe_uuid
+                    )
+                })?;
+                Ok(Self::BookmarkReviewShowSooner(uuid))
+            }
+            [SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID, maybe_guild_id, maybe_channel_id, maybe_message_id] =>
+            {
+                let parse_id = |maybe_id: &str| {
+                    maybe_id.parse::<u64>().map_err(|_| {
+                        format!(
+                            "Received invalid ID for {}: {}",
+                            SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID, maybe_id
+                        )
+                    })
+                };
+                let guild_id = match *maybe_guild_id {
+                    DIRECT_MESSAGE_GUILD_ID => None,
+                    maybe_guild_id => Some(parse_id(maybe_guild_id)?),
+                };
+                Ok(Self::SetMessageReminder {
+                    guild_id,
+                    channel_id: parse_id(maybe_channel_id)?,
+                    message_id: parse_id(maybe_message_id)?,
+                })
+            }
             _ => Err(format!("Received invalid custom ID: {}", value)),
         }
     }
@@ -53,6 +258,18 @@ mod tests {
         match some_input {
             InteractionCustomId::DeleteMessage => (),
             InteractionCustomId::SetReminder(_) => (),
+            InteractionCustomId::MarkReminderUrgent(_) => (),
+            InteractionCustomId::PreviousDigestPage(_) => (),
+            InteractionCustomId::NextDigestPage(_) => (),
+            InteractionCustomId::CancelCountdown(_) => (),
+            InteractionCustomId::ConfirmForgetMe(_) => (),
+            InteractionCustomId::CancelForgetMe(_) => (),
+            InteractionCustomId::ToggleBookmarkStar(_) => (),
+            InteractionCustomId::ToggleBookmarkRead(_) => (),
+            InteractionCustomId::BookmarkReviewGotIt(_) => (),
+            InteractionCustomId::BookmarkReviewShowSooner(_) => (),
+            InteractionCustomId::ChooseBookmarkToRemind => (),
+            InteractionCustomId::SetMessageReminder { .. } => (),
         }
     }
 
@@ -61,6 +278,27 @@ mod tests {
         let delete_message_id = InteractionCustomId::DeleteMessage;
         let uuid = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
         let set_reminder_id = InteractionCustomId::SetReminder(uuid);
+        let mark_reminder_urgent_id = InteractionCustomId::MarkReminderUrgent(42);
+        let previous_digest_page_id = InteractionCustomId::PreviousDigestPage(uuid);
+        let next_digest_page_id = InteractionCustomId::NextDigestPage(uuid);
+        let cancel_countdown_id = InteractionCustomId::CancelCountdown(uuid);
+        let confirm_forget_me_id = InteractionCustomId::ConfirmForgetMe(uuid);
+        let cancel_forget_me_id = InteractionCustomId::CancelForgetMe(uuid);
+        let toggle_bookmark_star_id = InteractionCustomId::ToggleBookmarkStar(uuid);
+        let toggle_bookmark_read_id = InteractionCustomId::ToggleBookmarkRead(uuid);
+        let bookmark_review_got_it_id = InteractionCustomId::BookmarkReviewGotIt(uuid);
+        let bookmark_review_show_sooner_id = InteractionCustomId::BookmarkReviewShowSooner(uuid);
+        let choose_bookmark_to_remind_id = InteractionCustomId::ChooseBookmarkToRemind;
+        let set_message_reminder_id = InteractionCustomId::SetMessageReminder {
+            guild_id: Some(1),
+            channel_id: 2,
+            message_id: 3,
+        };
+        let set_direct_message_reminder_id = InteractionCustomId::SetMessageReminder {
+            guild_id: None,
+            channel_id: 2,
+            message_id: 3,
+        };
 
         assert_eq!(
             String::from(delete_message_id),
@@ -70,6 +308,61 @@ mod tests {
             String::from(set_reminder_id),
             format!("{}:{}", SET_REMINDER_INTERACTION_CUSTOM_ID, uuid)
         );
+        assert_eq!(
+            String::from(mark_reminder_urgent_id),
+            format!("{}:42", MARK_REMINDER_URGENT_INTERACTION_CUSTOM_ID)
+        );
+        assert_eq!(
+            String::from(previous_digest_page_id),
+            format!("{}:{}", PREVIOUS_DIGEST_PAGE_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(next_digest_page_id),
+            format!("{}:{}", NEXT_DIGEST_PAGE_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(cancel_countdown_id),
+            format!("{}:{}", CANCEL_COUNTDOWN_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(confirm_forget_me_id),
+            format!("{}:{}", CONFIRM_FORGET_ME_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(cancel_forget_me_id),
+            format!("{}:{}", CANCEL_FORGET_ME_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(toggle_bookmark_star_id),
+            format!("{}:{}", TOGGLE_BOOKMARK_STAR_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(toggle_bookmark_read_id),
+            format!("{}:{}", TOGGLE_BOOKMARK_READ_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(bookmark_review_got_it_id),
+            format!("{}:{}", BOOKMARK_REVIEW_GOT_IT_INTERACTION_CUSTOM_ID, uuid)
+        );
+        assert_eq!(
+            String::from(bookmark_review_show_sooner_id),
+            format!(
+                "{}:{}",
+                BOOKMARK_REVIEW_SHOW_SOONER_INTERACTION_CUSTOM_ID, uuid
+            )
+        );
+        assert_eq!(
+            String::from(choose_bookmark_to_remind_id),
+            CHOOSE_BOOKMARK_TO_REMIND_INTERACTION_CUSTOM_ID
+        );
+        assert_eq!(
+            String::from(set_message_reminder_id),
+            format!("{}:1:2:3", SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID)
+        );
+        assert_eq!(
+            String::from(set_direct_message_reminder_id),
+            format!("{}:@me:2:3", SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID)
+        );
     }
 
     #[test]
@@ -77,6 +370,23 @@ mod tests {
         let delete_message_id = DELETE_MESSAGE_INTERACTION_CUSTOM_ID;
         let uuid = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
         let set_reminder_id = format!("{}:{}", SET_REMINDER_INTERACTION_CUSTOM_ID, uuid);
+        let mark_reminder_urgent_id = format!("{}:42", MARK_REMINDER_URGENT_INTERACTION_CUSTOM_ID);
+        let previous_digest_page_id =
+            format!("{}:{}", PREVIOUS_DIGEST_PAGE_INTERACTION_CUSTOM_ID, uuid);
+        let next_digest_page_id = format!("{}:{}", NEXT_DIGEST_PAGE_INTERACTION_CUSTOM_ID, uuid);
+        let cancel_countdown_id = format!("{}:{}", CANCEL_COUNTDOWN_INTERACTION_CUSTOM_ID, uuid);
+        let confirm_forget_me_id = format!("{}:{}", CONFIRM_FORGET_ME_INTERACTION_CUSTOM_ID, uuid);
+        let cancel_forget_me_id = format!("{}:{}", CANCEL_FORGET_ME_INTERACTION_CUSTOM_ID, uuid);
+        let toggle_bookmark_star_id =
+            format!("{}:{}", TOGGLE_BOOKMARK_STAR_INTERACTION_CUSTOM_ID, uuid);
+        let toggle_bookmark_read_id =
+            format!("{}:{}", TOGGLE_BOOKMARK_READ_INTERACTION_CUSTOM_ID, uuid);
+        let bookmark_review_got_it_id =
+            format!("{}:{}", BOOKMARK_REVIEW_GOT_IT_INTERACTION_CUSTOM_ID, uuid);
+        let bookmark_review_show_sooner_id = format!(
+            "{}:{}",
+            BOOKMARK_REVIEW_SHOW_SOONER_INTERACTION_CUSTOM_ID, uuid
+        );
 
         assert_eq!(
             InteractionCustomId::try_from(delete_message_id).unwrap(),
@@ -86,6 +396,80 @@ mod tests {
             InteractionCustomId::try_from(set_reminder_id.as_str()).unwrap(),
             InteractionCustomId::SetReminder(uuid)
         );
+        assert_eq!(
+            InteractionCustomId::try_from(mark_reminder_urgent_id.as_str()).unwrap(),
+            InteractionCustomId::MarkReminderUrgent(42)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(previous_digest_page_id.as_str()).unwrap(),
+            InteractionCustomId::PreviousDigestPage(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(next_digest_page_id.as_str()).unwrap(),
+            InteractionCustomId::NextDigestPage(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(cancel_countdown_id.as_str()).unwrap(),
+            InteractionCustomId::CancelCountdown(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(confirm_forget_me_id.as_str()).unwrap(),
+            InteractionCustomId::ConfirmForgetMe(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(cancel_forget_me_id.as_str()).unwrap(),
+            InteractionCustomId::CancelForgetMe(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(toggle_bookmark_star_id.as_str()).unwrap(),
+            InteractionCustomId::ToggleBookmarkStar(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(toggle_bookmark_read_id.as_str()).unwrap(),
+            InteractionCustomId::ToggleBookmarkRead(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(bookmark_review_got_it_id.as_str()).unwrap(),
+            InteractionCustomId::BookmarkReviewGotIt(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(bookmark_review_show_sooner_id.as_str()).unwrap(),
+            InteractionCustomId::BookmarkReviewShowSooner(uuid)
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(CHOOSE_BOOKMARK_TO_REMIND_INTERACTION_CUSTOM_ID).unwrap(),
+            InteractionCustomId::ChooseBookmarkToRemind
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(
+                format!("{}:1:2:3", SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID).as_str()
+            )
+            .unwrap(),
+            InteractionCustomId::SetMessageReminder {
+                guild_id: Some(1),
+                channel_id: 2,
+                message_id: 3,
+            }
+        );
+        assert_eq!(
+            InteractionCustomId::try_from(
+                format!("{}:@me:2:3", SET_MESSAGE_REMINDER_INTERACTION_CUSTOM_ID).as_str()
+            )
+            .unwrap(),
+            InteractionCustomId::SetMessageReminder {
+                guild_id: None,
+                channel_id: 2,
+                message_id: 3,
+            }
+        );
         assert!(InteractionCustomId::try_from("invalid_id").is_err());
+        assert!(InteractionCustomId::try_from(
+            format!(
+                "{}:not_a_number",
+                MARK_REMINDER_URGENT_INTERACTION_CUSTOM_ID
+            )
+            .as_str()
+        )
+        .is_err());
     }
 }
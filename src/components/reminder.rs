@@ -1,5 +1,15 @@
+pub mod cancel_countdown_button;
+pub mod catch_up_message;
+pub mod countdown_cancelled_reply;
+pub mod countdown_created_reply;
 pub mod get_reminders_reply;
+pub mod invalid_countdown_reply;
+pub mod mark_urgent_button;
 pub mod no_reminders_found_reply;
+pub mod reminder_created_message;
 pub mod reminder_created_reply;
+pub mod reminder_marked_urgent_reply;
 pub mod reminder_message;
+pub mod reminder_picker_message;
+pub mod reminder_time_reply;
 pub mod reminder_time_select_menu;
@@ -0,0 +1,31 @@
+use poise::serenity_prelude::{ButtonStyle, CreateButton, ReactionType};
+use uuid::Uuid;
+
+use crate::components::interaction_custom_id::InteractionCustomId;
+
+pub const CANCEL_COUNTDOWN_EMOJI: &str = "🛑";
+
+pub fn get_cancel_countdown_button(countdown_id: Uuid) -> CreateButton {
+    CreateButton::new(InteractionCustomId::CancelCountdown(countdown_id))
+        .label("Cancel all alerts for this deadline")
+        .emoji(ReactionType::Unicode(CANCEL_COUNTDOWN_EMOJI.to_string()))
+        .style(ButtonStyle::Secondary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_cancel_countdown_button() {
+        let countdown_id = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+        let button = get_cancel_countdown_button(countdown_id);
+
+        let expected_button = CreateButton::new(format!("cancel_countdown:{}", countdown_id))
+            .label("Cancel all alerts for this deadline")
+            .emoji(ReactionType::Unicode("🛑".to_string()))
+            .style(ButtonStyle::Secondary);
+
+        assert_eq!(button, expected_button);
+    }
+}
@@ -0,0 +1,123 @@
+use poise::serenity_prelude::{Colour, CreateEmbed, CreateMessage};
+
+use crate::{
+    components::{
+        delete_message_button::get_delete_button,
+        relative_timestamp_string::get_discord_relative_timestamp_string, trim_embed_description,
+        trim_embed_field_name,
+    },
+    models::reminder::PersistedReminder,
+};
+
+/// Discord rejects embeds with more than 25 fields
+const MAX_CATCH_UP_FIELDS: usize = 25;
+
+pub fn get_catch_up_message(
+    reminders: &[PersistedReminder],
+    delete_interaction_custom_id: impl Into<String>,
+    delete_interaction_emoji: impl Into<String>,
+) -> CreateMessage {
+    CreateMessage::default()
+        .embed(get_catch_up_embed(reminders))
+        .button(get_delete_button(
+            delete_interaction_custom_id,
+            delete_interaction_emoji,
+        ))
+}
+
+pub fn get_catch_up_embed(reminders: &[PersistedReminder]) -> CreateEmbed {
+    let hidden_reminder_count = reminders.len().saturating_sub(MAX_CATCH_UP_FIELDS);
+    let description = if hidden_reminder_count > 0 {
+        format!(
+            "## Missed Reminders: {}\n{} more not shown.",
+            reminders.len(),
+            hidden_reminder_count
+        )
+    } else {
+        format!("## Missed Reminders: {}", reminders.len())
+    };
+    let trimmed_description = trim_embed_description(&description);
+
+    CreateEmbed::default()
+        .title("While I was offline, these were due")
+        .description(trimmed_description)
+        .fields(reminders.iter().take(MAX_CATCH_UP_FIELDS).map(|reminder| {
+            let field_name = format!(
+                "{} was due: {}",
+                reminder.message().link(),
+                get_discord_relative_timestamp_string(reminder.remind_at())
+            );
+            let trimmed_field_name = trim_embed_field_name(&field_name);
+            (trimmed_field_name.to_owned(), "", false)
+        }))
+        .colour(Colour::TEAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::Message;
+
+    use super::*;
+    use crate::models::reminder::Reminder;
+
+    /// [`CreateMessage`] doesn't impl [`PartialEq`] nor does it
+    /// expose any of it's fields, so I can't actually test any values of type
+    /// [`CreateMessage`] (e.g. the return value of [`get_catch_up_message`]).
+    /// The best I can do is test the [`CreateEmbed`] returned by [`get_catch_up_embed`].
+    #[test]
+    fn test_get_catch_up_embed() {
+        let timestamp = chrono::Utc::now() - chrono::Duration::hours(3);
+        let reminders = vec![
+            PersistedReminder::from_reminder(
+                Reminder::new(123456789, Message::default(), timestamp),
+                1,
+            ),
+            PersistedReminder::from_reminder(
+                Reminder::new(123456789, Message::default(), timestamp),
+                2,
+            ),
+        ];
+
+        let embed = get_catch_up_embed(&reminders);
+
+        let expected_field_name = format!(
+            "{} was due: <t:{}:R>",
+            Message::default().link(),
+            timestamp.timestamp()
+        );
+        let expected_embed = CreateEmbed::default()
+            .title("While I was offline, these were due")
+            .description("## Missed Reminders: 2")
+            .field(expected_field_name.clone(), "", false)
+            .field(expected_field_name, "", false)
+            .colour(Colour::TEAL);
+        assert_eq!(embed, expected_embed);
+    }
+
+    #[test]
+    fn test_get_catch_up_embed_with_too_many_reminders() {
+        let timestamp = chrono::Utc::now() - chrono::Duration::hours(3);
+        let reminders = (0..30)
+            .map(|pk| {
+                PersistedReminder::from_reminder(
+                    Reminder::new(123456789, Message::default(), timestamp),
+                    pk,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let embed = get_catch_up_embed(&reminders);
+
+        let expected_field_name = format!(
+            "{} was due: <t:{}:R>",
+            Message::default().link(),
+            timestamp.timestamp()
+        );
+        let expected_embed = CreateEmbed::default()
+            .title("While I was offline, these were due")
+            .description("## Missed Reminders: 30\n5 more not shown.")
+            .fields((0..25).map(|_| (expected_field_name.clone(), "", false)))
+            .colour(Colour::TEAL);
+        assert_eq!(embed, expected_embed);
+    }
+}
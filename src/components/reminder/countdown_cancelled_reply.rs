@@ -0,0 +1,38 @@
+use poise::CreateReply;
+
+pub fn get_countdown_cancelled_reply(cancelled_alert_count: usize) -> CreateReply {
+    let content = if cancelled_alert_count == 0 {
+        "That countdown has no alerts left to cancel.".to_string()
+    } else {
+        format!(
+            "Countdown cancelled. {} remaining alert{} won't be sent.",
+            cancelled_alert_count,
+            if cancelled_alert_count > 1 { "s" } else { "" }
+        )
+    };
+
+    CreateReply::default().content(content).ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_countdown_cancelled_reply() {
+        assert_eq!(
+            get_countdown_cancelled_reply(0).content,
+            Some("That countdown has no alerts left to cancel.".to_string())
+        );
+        assert_eq!(
+            get_countdown_cancelled_reply(1).content,
+            Some("Countdown cancelled. 1 remaining alert won't be sent.".to_string())
+        );
+        let reply = get_countdown_cancelled_reply(3);
+        assert_eq!(
+            reply.content,
+            Some("Countdown cancelled. 3 remaining alerts won't be sent.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
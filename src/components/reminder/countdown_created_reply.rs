@@ -0,0 +1,90 @@
+use poise::{serenity_prelude::CreateActionRow, CreateReply};
+use uuid::Uuid;
+
+use crate::components::{
+    relative_timestamp_string::get_discord_relative_timestamp_string,
+    reminder::cancel_countdown_button::get_cancel_countdown_button,
+};
+
+pub fn get_countdown_created_reply(
+    countdown_id: Uuid,
+    deadline: &chrono::DateTime<chrono::Utc>,
+    alert_times: &[chrono::DateTime<chrono::Utc>],
+) -> CreateReply {
+    let alert_list = alert_times
+        .iter()
+        .map(|alert_time| format!("- {}", get_discord_relative_timestamp_string(alert_time)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    CreateReply::default()
+        .content(format!(
+            "Countdown set for the deadline {}. You'll be alerted:\n{}",
+            get_discord_relative_timestamp_string(deadline),
+            alert_list
+        ))
+        .components(vec![CreateActionRow::Buttons(vec![
+            get_cancel_countdown_button(countdown_id),
+        ])])
+        .ephemeral(true)
+}
+
+/// Sent when every requested alert would already be in the past
+pub fn get_countdown_alerts_in_past_reply(deadline: &chrono::DateTime<chrono::Utc>) -> CreateReply {
+    CreateReply::default()
+        .content(format!(
+            "All of those alerts for the deadline {} would already be in the past, so no countdown was set.",
+            get_discord_relative_timestamp_string(deadline)
+        ))
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_countdown_created_reply() {
+        let countdown_id = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+        let deadline = chrono::Utc::now() + chrono::Duration::days(2);
+        let alert_times = vec![
+            deadline - chrono::Duration::days(1),
+            deadline - chrono::Duration::hours(1),
+        ];
+
+        let reply = get_countdown_created_reply(countdown_id, &deadline, &alert_times);
+
+        assert_eq!(reply.ephemeral, Some(true));
+        assert_eq!(
+            reply.content.unwrap(),
+            format!(
+                "Countdown set for the deadline <t:{}:R>. You'll be alerted:\n- <t:{}:R>\n- <t:{}:R>",
+                deadline.timestamp(),
+                alert_times[0].timestamp(),
+                alert_times[1].timestamp()
+            )
+        );
+        assert_eq!(
+            reply.components,
+            Some(vec![CreateActionRow::Buttons(vec![
+                get_cancel_countdown_button(countdown_id)
+            ])])
+        );
+    }
+
+    #[test]
+    fn test_get_countdown_alerts_in_past_reply() {
+        let deadline = chrono::Utc::now();
+
+        let reply = get_countdown_alerts_in_past_reply(&deadline);
+
+        assert_eq!(reply.ephemeral, Some(true));
+        assert_eq!(
+            reply.content.unwrap(),
+            format!(
+                "All of those alerts for the deadline <t:{}:R> would already be in the past, so no countdown was set.",
+                deadline.timestamp()
+            )
+        );
+    }
+}
@@ -6,11 +6,96 @@ use poise::{
 use crate::{
     components::{
         relative_timestamp_string::get_discord_relative_timestamp_string, trim_embed_description,
-        trim_embed_field_name, trim_embed_title,
+        trim_embed_field_name, trim_embed_field_value, trim_embed_title,
     },
-    models::reminder::PersistedReminder,
+    models::reminder::{CountdownLink, PersistedReminder},
 };
 
+/// A countdown's alerts are listed together under one field, so its entry collects all of their times
+enum ReminderListEntry<'a> {
+    Reminder(&'a PersistedReminder),
+    Countdown {
+        first_alert: &'a PersistedReminder,
+        countdown: &'a CountdownLink,
+        alert_times: Vec<&'a chrono::DateTime<chrono::Utc>>,
+    },
+}
+
+fn group_countdown_alerts(reminders: &[PersistedReminder]) -> Vec<ReminderListEntry<'_>> {
+    let mut entries = Vec::<ReminderListEntry>::new();
+
+    for reminder in reminders {
+        let Some(countdown) = reminder.countdown() else {
+            entries.push(ReminderListEntry::Reminder(reminder));
+            continue;
+        };
+
+        let existing_entry = entries.iter_mut().find_map(|entry| match entry {
+            ReminderListEntry::Countdown {
+                countdown: entry_countdown,
+                alert_times,
+                ..
+            } if entry_countdown.countdown_id() == countdown.countdown_id() => Some(alert_times),
+            _ => None,
+        });
+
+        match existing_entry {
+            Some(alert_times) => alert_times.push(reminder.remind_at()),
+            None => entries.push(ReminderListEntry::Countdown {
+                first_alert: reminder,
+                countdown,
+                alert_times: vec![reminder.remind_at()],
+            }),
+        }
+    }
+
+    entries
+}
+
+fn get_reminder_list_field(entry: &ReminderListEntry) -> (String, String, bool) {
+    match entry {
+        ReminderListEntry::Reminder(reminder) => {
+            let field_name = format!(
+                "{} at: {}",
+                reminder.message().link(),
+                get_discord_relative_timestamp_string(reminder.remind_at())
+            );
+            let trimmed_field_name = trim_embed_field_name(&field_name);
+            (trimmed_field_name.to_owned(), String::new(), true)
+        }
+        ReminderListEntry::Countdown {
+            first_alert,
+            countdown,
+            alert_times,
+        } => {
+            let field_name = format!(
+                "{} deadline: {}",
+                first_alert.message().link(),
+                get_discord_relative_timestamp_string(countdown.deadline())
+            );
+            let trimmed_field_name = trim_embed_field_name(&field_name);
+
+            let mut alert_times = alert_times.clone();
+            alert_times.sort();
+            let field_value = format!(
+                "Alerts: {}",
+                alert_times
+                    .into_iter()
+                    .map(get_discord_relative_timestamp_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let trimmed_field_value = trim_embed_field_value(&field_value);
+
+            (
+                trimmed_field_name.to_owned(),
+                trimmed_field_value.to_owned(),
+                false,
+            )
+        }
+    }
+}
+
 pub fn create_get_reminders_reply(reminders: &[PersistedReminder]) -> CreateReply {
     let title = format!(
         "Retrieved up to {} reminder{}.\nThere may be more reminders not shown.",
@@ -27,23 +112,20 @@ pub fn create_get_reminders_reply(reminders: &[PersistedReminder]) -> CreateRepl
             CreateEmbed::default()
                 .title(trimmed_title)
                 .description(trimmed_description)
-                .fields(reminders.iter().map(|reminder| {
-                    let field_name = format!(
-                        "{} at: {}",
-                        reminder.message().link(),
-                        get_discord_relative_timestamp_string(reminder.remind_at())
-                    );
-                    let trimmed_field_name = trim_embed_field_name(&field_name);
-                    (trimmed_field_name.to_owned(), "", true)
-                }))
+                .fields(
+                    group_countdown_alerts(reminders)
+                        .iter()
+                        .map(get_reminder_list_field),
+                )
                 .colour(serenity::Colour::TEAL),
         )
         .ephemeral(true)
 }
 
 #[cfg(test)]
+#[expect(clippy::get_first)]
 mod tests {
-    use crate::models::reminder::Reminder;
+    use crate::models::reminder::{CountdownLink, Reminder};
 
     use super::*;
 
@@ -157,4 +239,83 @@ mod tests {
 
         assert_eq!(embed, expected_embed);
     }
+
+    #[test]
+    fn test_create_get_reminders_reply_groups_countdown_alerts() {
+        let deadline = chrono::Utc::now() + chrono::Duration::days(2);
+        let standalone_time = chrono::Utc::now();
+
+        let channel_id = 1;
+        let message_id = 2;
+        let message = {
+            let mut message = serenity::Message::default();
+            message.channel_id = channel_id.into();
+            message.id = message_id.into();
+            message
+        };
+
+        let countdown = CountdownLink::new(
+            uuid::Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            deadline,
+        );
+        let alert = |pk: i64, offset: chrono::Duration| {
+            PersistedReminder::from_reminder(
+                Reminder::new_countdown_alert(
+                    123456789,
+                    message.clone(),
+                    deadline - offset,
+                    countdown,
+                ),
+                pk,
+            )
+        };
+
+        // Ordered latest first, as they are when fetched from the database
+        let reminders = vec![
+            alert(3, chrono::Duration::minutes(10)),
+            alert(2, chrono::Duration::hours(1)),
+            PersistedReminder::from_reminder(
+                Reminder::new(123456789, message.clone(), standalone_time),
+                4,
+            ),
+            alert(1, chrono::Duration::days(1)),
+        ];
+
+        let reply = create_get_reminders_reply(&reminders);
+
+        assert_eq!(reply.embeds.len(), 1);
+        let embed = reply.embeds.first().unwrap().to_owned();
+
+        let expected_embed = CreateEmbed::default()
+            .title("Retrieved up to 4 reminders.\nThere may be more reminders not shown.")
+            .description("## Queued Reminders: 4")
+            .field(
+                format!(
+                    "https://discord.com/channels/@me/{}/{} deadline: <t:{}:R>",
+                    channel_id,
+                    message_id,
+                    deadline.timestamp()
+                ),
+                format!(
+                    "Alerts: <t:{}:R>, <t:{}:R>, <t:{}:R>",
+                    (deadline - chrono::Duration::days(1)).timestamp(),
+                    (deadline - chrono::Duration::hours(1)).timestamp(),
+                    (deadline - chrono::Duration::minutes(10)).timestamp()
+                ),
+                false,
+            )
+            .field(
+                format!(
+                    "https://discord.com/channels/@me/{}/{} at: <t:{}:R>",
+                    channel_id,
+                    message_id,
+                    standalone_time.timestamp()
+                ),
+                "",
+                true,
+            )
+            .colour(serenity::Colour::TEAL);
+
+        assert_eq!(embed, expected_embed);
+    }
 }
@@ -0,0 +1,25 @@
+use poise::CreateReply;
+
+pub fn get_invalid_countdown_reply(received_value: &str, expected_format: &str) -> CreateReply {
+    CreateReply::default()
+        .content(format!(
+            "`{}` isn't valid here. Expected {}.",
+            received_value, expected_format
+        ))
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_invalid_countdown_reply() {
+        let reply = get_invalid_countdown_reply("1y", "offsets like `1d, 1h, 10m`");
+        assert_eq!(
+            reply.content,
+            Some("`1y` isn't valid here. Expected offsets like `1d, 1h, 10m`.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
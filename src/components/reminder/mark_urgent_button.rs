@@ -0,0 +1,29 @@
+use poise::serenity_prelude::{ButtonStyle, CreateButton, ReactionType};
+
+use crate::components::interaction_custom_id::InteractionCustomId;
+
+pub const MARK_URGENT_EMOJI: &str = "🚨";
+
+pub fn get_mark_urgent_button(reminder_pk: i64) -> CreateButton {
+    CreateButton::new(InteractionCustomId::MarkReminderUrgent(reminder_pk))
+        .label("Mark as urgent (ignores quiet hours)")
+        .emoji(ReactionType::Unicode(MARK_URGENT_EMOJI.to_string()))
+        .style(ButtonStyle::Secondary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_mark_urgent_button() {
+        let button = get_mark_urgent_button(1);
+
+        let expected_button = CreateButton::new("mark_reminder_urgent:1")
+            .label("Mark as urgent (ignores quiet hours)")
+            .emoji(ReactionType::Unicode("🚨".to_string()))
+            .style(ButtonStyle::Secondary);
+
+        assert_eq!(button, expected_button);
+    }
+}
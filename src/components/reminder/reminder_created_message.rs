@@ -0,0 +1,61 @@
+use poise::serenity_prelude::{Colour, CreateEmbed, CreateMessage};
+
+use crate::{
+    components::{
+        relative_timestamp_string::get_discord_relative_timestamp_string,
+        reminder::mark_urgent_button::get_mark_urgent_button,
+    },
+    models::reminder::PersistedReminder,
+};
+
+/// The DM counterpart of [`super::reminder_created_reply::get_reminder_created_reply`], for reminders set
+/// without an interaction to reply to (e.g. by a reaction shortcut)
+pub fn get_reminder_created_message(reminder: &PersistedReminder) -> CreateMessage {
+    CreateMessage::default()
+        .embed(get_reminder_created_embed(reminder))
+        .button(get_mark_urgent_button(reminder.pk()))
+}
+
+pub fn get_reminder_created_embed(reminder: &PersistedReminder) -> CreateEmbed {
+    CreateEmbed::default()
+        .title("Reminder set")
+        .description(format!(
+            "I'll remind you about {} {}",
+            reminder.message().link(),
+            get_discord_relative_timestamp_string(reminder.remind_at())
+        ))
+        .colour(Colour::TEAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::Message;
+
+    use super::*;
+    use crate::models::reminder::Reminder;
+
+    /// [`CreateMessage`] doesn't impl [`PartialEq`] nor does it
+    /// expose any of it's fields, so I can't actually test any values of type
+    /// [`CreateMessage`] (e.g. the return value of [`get_reminder_created_message`]).
+    /// The best I can do is test the [`CreateEmbed`] returned by [`get_reminder_created_embed`].
+    #[test]
+    fn test_get_reminder_created_embed() {
+        let remind_at = chrono::Utc::now() + chrono::Duration::hours(24);
+        let reminder = PersistedReminder::from_reminder(
+            Reminder::new(123456789, Message::default(), remind_at),
+            1,
+        );
+
+        let embed = get_reminder_created_embed(&reminder);
+
+        let expected_embed = CreateEmbed::default()
+            .title("Reminder set")
+            .description(format!(
+                "I'll remind you about {} <t:{}:R>",
+                Message::default().link(),
+                remind_at.timestamp()
+            ))
+            .colour(Colour::TEAL);
+        assert_eq!(embed, expected_embed);
+    }
+}
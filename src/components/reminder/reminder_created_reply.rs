@@ -1,13 +1,22 @@
-use poise::CreateReply;
+use poise::{serenity_prelude::CreateActionRow, CreateReply};
 
-use crate::components::relative_timestamp_string::get_discord_relative_timestamp_string;
+use crate::components::{
+    relative_timestamp_string::get_discord_relative_timestamp_string,
+    reminder::mark_urgent_button::get_mark_urgent_button,
+};
 
-pub fn get_reminder_created_reply(remind_at: &chrono::DateTime<chrono::Utc>) -> CreateReply {
+pub fn get_reminder_created_reply(
+    remind_at: &chrono::DateTime<chrono::Utc>,
+    reminder_pk: i64,
+) -> CreateReply {
     CreateReply::default()
         .content(format!(
             "Reminder set for {}",
             get_discord_relative_timestamp_string(remind_at)
         ))
+        .components(vec![CreateActionRow::Buttons(vec![
+            get_mark_urgent_button(reminder_pk),
+        ])])
         .reply(true)
         .ephemeral(true)
 }
@@ -19,12 +28,18 @@ mod tests {
     #[test]
     fn test_get_reminder_created_reply() {
         let remind_at = chrono::Utc::now();
-        let result = get_reminder_created_reply(&remind_at);
+        let result = get_reminder_created_reply(&remind_at, 1);
         assert_eq!(result.ephemeral, Some(true));
-        assert_eq!(result.reply, true);
+        assert!(result.reply);
         assert_eq!(
             result.content.unwrap(),
             format!("Reminder set for <t:{}:R>", remind_at.timestamp())
         );
+        assert_eq!(
+            result.components,
+            Some(vec![CreateActionRow::Buttons(vec![
+                get_mark_urgent_button(1)
+            ])])
+        );
     }
 }
@@ -0,0 +1,41 @@
+use poise::CreateReply;
+
+pub fn get_reminder_marked_urgent_reply() -> CreateReply {
+    CreateReply::default()
+        .content("Reminder marked as urgent! It will be delivered even during your quiet hours.")
+        .ephemeral(true)
+}
+
+pub fn get_reminder_not_found_reply() -> CreateReply {
+    CreateReply::default()
+        .content("That reminder no longer exists.")
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_reminder_marked_urgent_reply() {
+        let reply = get_reminder_marked_urgent_reply();
+        assert_eq!(
+            reply.content,
+            Some(
+                "Reminder marked as urgent! It will be delivered even during your quiet hours."
+                    .to_string()
+            )
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+
+    #[test]
+    fn test_get_reminder_not_found_reply() {
+        let reply = get_reminder_not_found_reply();
+        assert_eq!(
+            reply.content,
+            Some("That reminder no longer exists.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
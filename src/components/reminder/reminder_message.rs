@@ -2,7 +2,10 @@ use poise::serenity_prelude::{Colour, CreateEmbed, CreateMessage};
 
 use crate::{
     components::{
-        delete_message_button::get_delete_button, trim_embed_description, trim_embed_title,
+        delete_message_button::get_delete_button,
+        relative_timestamp_string::get_discord_relative_timestamp_string,
+        reminder::cancel_countdown_button::get_cancel_countdown_button, trim_embed_description,
+        trim_embed_title,
     },
     models::reminder::PersistedReminder,
 };
@@ -13,15 +16,23 @@ pub fn get_reminder_message(
     delete_interaction_custom_id: impl Into<String>,
     delete_interaction_emoji: impl Into<String>,
 ) -> CreateMessage {
-    CreateMessage::default()
+    let message = CreateMessage::default()
         .embed(get_reminder_embed(reminder, reminder_channel_name))
         .button(get_delete_button(
             delete_interaction_custom_id,
             delete_interaction_emoji,
-        ))
+        ));
+
+    match reminder.countdown() {
+        Some(countdown) => message.button(get_cancel_countdown_button(countdown.countdown_id())),
+        None => message,
+    }
 }
 
-fn get_reminder_embed(reminder: &PersistedReminder, reminder_channel_name: &str) -> CreateEmbed {
+pub fn get_reminder_embed(
+    reminder: &PersistedReminder,
+    reminder_channel_name: &str,
+) -> CreateEmbed {
     let title = format!("Reminder from {}", reminder_channel_name);
     let trimmed_title = trim_embed_title(&title);
 
@@ -32,11 +43,20 @@ fn get_reminder_embed(reminder: &PersistedReminder, reminder_channel_name: &str)
     );
     let trimmed_description = trim_embed_description(&description);
 
-    CreateEmbed::default()
+    let embed = CreateEmbed::default()
         .title(trimmed_title)
         .description(trimmed_description)
         .timestamp(reminder.message().timestamp)
-        .colour(Colour::TEAL)
+        .colour(Colour::TEAL);
+
+    match reminder.countdown() {
+        Some(countdown) => embed.field(
+            "Deadline",
+            get_discord_relative_timestamp_string(countdown.deadline()),
+            false,
+        ),
+        None => embed,
+    }
 }
 
 #[cfg(test)]
@@ -44,7 +64,7 @@ mod tests {
     use poise::serenity_prelude::Message;
 
     use super::*;
-    use crate::models::reminder::Reminder;
+    use crate::models::reminder::{CountdownLink, Reminder};
 
     /// [`CreateMessage`] doesn't impl [`PartialEq`] nor does it
     /// expose any of it's fields, so I can't actually test any values of type
@@ -72,4 +92,37 @@ mod tests {
             .colour(Colour::TEAL);
         assert_eq!(embed, expected_embed);
     }
+
+    #[test]
+    fn test_get_reminder_embed_for_countdown_alert() {
+        let timestamp = chrono::Utc::now();
+        let deadline = timestamp + chrono::Duration::hours(1);
+        let reminder = PersistedReminder::from_reminder(
+            Reminder::new_countdown_alert(
+                123456789,
+                Message::default(),
+                timestamp,
+                CountdownLink::new(
+                    uuid::Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                    deadline,
+                ),
+            ),
+            1,
+        );
+        let reminder_channel_name = "test_channel";
+
+        let embed = get_reminder_embed(&reminder, reminder_channel_name);
+
+        let expected_embed = CreateEmbed::default()
+            .title(format!("Reminder from {}", reminder_channel_name))
+            .description(format!(
+                "# {} \n # {}",
+                reminder.message().content,
+                reminder.message().link()
+            ))
+            .timestamp(reminder.message().timestamp)
+            .colour(Colour::TEAL)
+            .field("Deadline", format!("<t:{}:R>", deadline.timestamp()), false);
+        assert_eq!(embed, expected_embed);
+    }
 }
@@ -0,0 +1,67 @@
+use poise::serenity_prelude::{Colour, CreateEmbed, CreateMessage, Message};
+
+use crate::components::{
+    interaction_custom_id::InteractionCustomId,
+    reminder::reminder_time_select_menu::select_menu::get_reminder_select_menu,
+    trim_embed_description,
+};
+
+/// Sent when a reaction shortcut asks for a reminder on a message, since reactions can't open a select menu in place.
+/// The select menu refers to the message itself, so nothing is stored until a time is picked.
+pub fn get_reminder_picker_message(message: &Message) -> CreateMessage {
+    CreateMessage::default()
+        .embed(get_reminder_picker_embed(message))
+        .select_menu(get_reminder_select_menu(
+            InteractionCustomId::SetMessageReminder {
+                guild_id: message.guild_id.map(|guild_id| guild_id.get()),
+                channel_id: message.channel_id.get(),
+                message_id: message.id.get(),
+            },
+        ))
+}
+
+pub fn get_reminder_picker_embed(message: &Message) -> CreateEmbed {
+    let description = format!("{}\n{}", message.link(), message.content);
+    let trimmed_description = trim_embed_description(description.trim_end());
+
+    CreateEmbed::default()
+        .title("When should I remind you about this?")
+        .description(trimmed_description)
+        .timestamp(message.timestamp)
+        .colour(Colour::TEAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// [`CreateMessage`] doesn't impl [`PartialEq`] nor does it
+    /// expose any of it's fields, so I can't actually test any values of type
+    /// [`CreateMessage`] (e.g. the return value of [`get_reminder_picker_message`]).
+    /// The best I can do is test the [`CreateEmbed`] returned by [`get_reminder_picker_embed`].
+    #[test]
+    fn test_get_reminder_picker_embed() {
+        let mut message = Message::default();
+        message.content = "Don't forget".to_string();
+        let embed = get_reminder_picker_embed(&message);
+
+        let expected_embed = CreateEmbed::default()
+            .title("When should I remind you about this?")
+            .description(format!("{}\nDon't forget", message.link()))
+            .timestamp(message.timestamp)
+            .colour(Colour::TEAL);
+        assert_eq!(embed, expected_embed);
+    }
+
+    #[test]
+    fn test_get_reminder_picker_embed_without_content() {
+        let embed = get_reminder_picker_embed(&Message::default());
+
+        let expected_embed = CreateEmbed::default()
+            .title("When should I remind you about this?")
+            .description(Message::default().link())
+            .timestamp(Message::default().timestamp)
+            .colour(Colour::TEAL);
+        assert_eq!(embed, expected_embed);
+    }
+}
@@ -0,0 +1,60 @@
+use poise::{serenity_prelude::CreateActionRow, CreateReply};
+
+use crate::{
+    components::{
+        interaction_custom_id::InteractionCustomId,
+        reminder::reminder_time_select_menu::select_menu::get_reminder_select_menu,
+    },
+    models::bookmark::PersistedBookmarkedMessage,
+};
+
+/// Sent after a bookmark was picked from the bookmark list, so its reminder is set like one from the bookmark's DM
+pub fn get_reminder_time_reply(bookmark: &PersistedBookmarkedMessage) -> CreateReply {
+    CreateReply::default()
+        .content(format!(
+            "When should I remind you about {}?",
+            bookmark.message().link()
+        ))
+        .components(vec![CreateActionRow::SelectMenu(get_reminder_select_menu(
+            InteractionCustomId::SetReminder(bookmark.bookmark_id()),
+        ))])
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::Message;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::bookmark::BookmarkedMessage;
+
+    #[test]
+    fn test_get_reminder_time_reply() {
+        let bookmark = PersistedBookmarkedMessage::from_bookmarked_message(
+            BookmarkedMessage::new(
+                Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                123456789,
+                Message::default(),
+            ),
+            1,
+        );
+
+        let reply = get_reminder_time_reply(&bookmark);
+
+        assert_eq!(reply.ephemeral, Some(true));
+        assert_eq!(
+            reply.content,
+            Some(format!(
+                "When should I remind you about {}?",
+                Message::default().link()
+            ))
+        );
+        assert_eq!(
+            reply.components,
+            Some(vec![CreateActionRow::SelectMenu(get_reminder_select_menu(
+                InteractionCustomId::SetReminder(bookmark.bookmark_id())
+            ))])
+        );
+    }
+}
@@ -42,6 +42,7 @@ mod tests {
     }
 
     #[test]
+    #[expect(clippy::needless_match)]
     fn test_reminder_select_menu_value_parse() {
         ReminderSelectMenuValue::iter().for_each(|value| {
             let parsed_value = ReminderSelectMenuValue::from_str(&value.to_string()).unwrap();
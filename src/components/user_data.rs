@@ -0,0 +1,2 @@
+pub mod forget_me_reply;
+pub mod my_data_message;
@@ -0,0 +1,137 @@
+use poise::{
+    serenity_prelude::{ButtonStyle, CreateActionRow, CreateButton},
+    CreateReply,
+};
+use uuid::Uuid;
+
+use crate::{
+    components::interaction_custom_id::InteractionCustomId, database::user_data::DeletedUserData,
+};
+
+pub fn get_forget_me_confirmation_reply(confirmation_id: Uuid) -> CreateReply {
+    CreateReply::default()
+        .content(
+            "This will permanently delete all of your bookmarks, reminders, reminder history and settings. Are you sure?",
+        )
+        .components(vec![CreateActionRow::Buttons(vec![
+            CreateButton::new(InteractionCustomId::ConfirmForgetMe(confirmation_id))
+                .label("Yes, delete everything")
+                .style(ButtonStyle::Danger),
+            CreateButton::new(InteractionCustomId::CancelForgetMe(confirmation_id))
+                .label("Cancel")
+                .style(ButtonStyle::Secondary),
+        ])])
+        .ephemeral(true)
+}
+
+pub fn get_forget_me_completed_reply(deleted_user_data: &DeletedUserData) -> CreateReply {
+    let pluralize = |count: usize| if count == 1 { "" } else { "s" };
+
+    CreateReply::default()
+        .content(format!(
+            "All of your data has been deleted:\n- {} bookmark{}\n- {} pending reminder{}\n- {} delivered reminder record{}\n- {}",
+            deleted_user_data.bookmark_count,
+            pluralize(deleted_user_data.bookmark_count),
+            deleted_user_data.reminder_count,
+            pluralize(deleted_user_data.reminder_count),
+            deleted_user_data.reminder_delivery_count,
+            pluralize(deleted_user_data.reminder_delivery_count),
+            if deleted_user_data.settings_count > 0 {
+                "your settings"
+            } else {
+                "no settings (you hadn't changed any)"
+            }
+        ))
+        .components(vec![])
+        .ephemeral(true)
+}
+
+pub fn get_forget_me_cancelled_reply() -> CreateReply {
+    CreateReply::default()
+        .content("Nothing was deleted.")
+        .components(vec![])
+        .ephemeral(true)
+}
+
+pub fn get_forget_me_timed_out_reply() -> CreateReply {
+    CreateReply::default()
+        .content("Nothing was deleted because the confirmation timed out.")
+        .components(vec![])
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_forget_me_confirmation_reply() {
+        let confirmation_id = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+
+        let reply = get_forget_me_confirmation_reply(confirmation_id);
+
+        assert_eq!(reply.ephemeral, Some(true));
+        assert_eq!(
+            reply.components,
+            Some(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(format!("confirm_forget_me:{}", confirmation_id))
+                    .label("Yes, delete everything")
+                    .style(ButtonStyle::Danger),
+                CreateButton::new(format!("cancel_forget_me:{}", confirmation_id))
+                    .label("Cancel")
+                    .style(ButtonStyle::Secondary),
+            ])])
+        );
+    }
+
+    #[test]
+    fn test_get_forget_me_completed_reply() {
+        let reply = get_forget_me_completed_reply(&DeletedUserData {
+            bookmark_count: 1,
+            reminder_count: 2,
+            reminder_delivery_count: 0,
+            settings_count: 1,
+        });
+
+        assert_eq!(
+            reply.content,
+            Some(
+                "All of your data has been deleted:\n- 1 bookmark\n- 2 pending reminders\n- 0 delivered reminder records\n- your settings"
+                    .to_string()
+            )
+        );
+        // The confirmation buttons are removed
+        assert_eq!(reply.components, Some(vec![]));
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+
+    #[test]
+    fn test_get_forget_me_completed_reply_without_settings() {
+        let reply = get_forget_me_completed_reply(&DeletedUserData::default());
+
+        assert_eq!(
+            reply.content,
+            Some(
+                "All of your data has been deleted:\n- 0 bookmarks\n- 0 pending reminders\n- 0 delivered reminder records\n- no settings (you hadn't changed any)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_get_forget_me_cancelled_and_timed_out_replies() {
+        let cancelled_reply = get_forget_me_cancelled_reply();
+        assert_eq!(
+            cancelled_reply.content,
+            Some("Nothing was deleted.".to_string())
+        );
+        assert_eq!(cancelled_reply.components, Some(vec![]));
+
+        let timed_out_reply = get_forget_me_timed_out_reply();
+        assert_eq!(
+            timed_out_reply.content,
+            Some("Nothing was deleted because the confirmation timed out.".to_string())
+        );
+        assert_eq!(timed_out_reply.components, Some(vec![]));
+    }
+}
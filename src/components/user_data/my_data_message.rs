@@ -0,0 +1,38 @@
+use poise::{
+    serenity_prelude::{CreateAttachment, CreateMessage},
+    CreateReply,
+};
+
+pub const MY_DATA_ARCHIVE_FILE_NAME: &str = "my_data.json";
+
+pub fn get_my_data_message(
+    archive: &serde_json::Value,
+) -> Result<CreateMessage, serde_json::Error> {
+    Ok(CreateMessage::default()
+        .content("Here's everything I have stored about you. The archive's format is described in my README.")
+        .add_file(CreateAttachment::bytes(
+            serde_json::to_vec_pretty(archive)?,
+            MY_DATA_ARCHIVE_FILE_NAME,
+        )))
+}
+
+pub fn get_my_data_sent_reply() -> CreateReply {
+    CreateReply::default()
+        .content("I've sent you a DM with everything I have stored about you.")
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_my_data_sent_reply() {
+        let reply = get_my_data_sent_reply();
+        assert_eq!(
+            reply.content,
+            Some("I've sent you a DM with everything I have stored about you.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod guild_settings_reply;
+pub mod invalid_setting_reply;
+pub mod user_settings_reply;
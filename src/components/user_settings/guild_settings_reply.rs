@@ -0,0 +1,83 @@
+use poise::{
+    serenity_prelude::{self as serenity, CreateEmbed},
+    CreateReply,
+};
+
+use crate::models::guild_settings::GuildSettings;
+
+pub fn get_guild_settings_reply(settings: &GuildSettings) -> CreateReply {
+    CreateReply::default()
+        .embed(
+            CreateEmbed::default()
+                .title("Server settings")
+                .field(
+                    "Privacy mode",
+                    if settings.privacy_mode() { "On" } else { "Off" },
+                    true,
+                )
+                .field(
+                    "Reaction shortcuts",
+                    get_reaction_shortcuts_field_value(settings),
+                    true,
+                )
+                .colour(serenity::Colour::TEAL),
+        )
+        .ephemeral(true)
+}
+
+fn get_reaction_shortcuts_field_value(settings: &GuildSettings) -> String {
+    if !settings.reaction_shortcuts_enabled() {
+        return "Off".to_string();
+    }
+    let emojis = settings.reaction_shortcut_emojis();
+    format!(
+        "{} Bookmark\n{} Remind me\n{} Remind me tomorrow",
+        emojis.bookmark(),
+        emojis.remind(),
+        emojis.remind_tomorrow()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_guild_settings_reply() {
+        let mut settings = GuildSettings::new(123456789);
+        settings.set_privacy_mode(true);
+
+        let reply = get_guild_settings_reply(&settings);
+
+        assert_eq!(reply.ephemeral, Some(true));
+        assert_eq!(
+            reply.embeds,
+            vec![CreateEmbed::default()
+                .title("Server settings")
+                .field("Privacy mode", "On", true)
+                .field(
+                    "Reaction shortcuts",
+                    "🔖 Bookmark\n⏰ Remind me\n📌 Remind me tomorrow",
+                    true
+                )
+                .colour(serenity::Colour::TEAL)]
+        );
+    }
+
+    #[test]
+    fn test_get_guild_settings_reply_with_reaction_shortcuts_disabled() {
+        let mut settings = GuildSettings::new(123456789);
+        settings.set_reaction_shortcuts_enabled(false);
+
+        let reply = get_guild_settings_reply(&settings);
+
+        assert_eq!(
+            reply.embeds,
+            vec![CreateEmbed::default()
+                .title("Server settings")
+                .field("Privacy mode", "Off", true)
+                .field("Reaction shortcuts", "Off", true)
+                .colour(serenity::Colour::TEAL)]
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use poise::CreateReply;
+
+pub fn get_invalid_setting_reply(setting_name: &str, received_value: &str) -> CreateReply {
+    CreateReply::default()
+        .content(format!(
+            "`{}` is not a valid {}.",
+            received_value, setting_name
+        ))
+        .ephemeral(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_invalid_setting_reply() {
+        let reply = get_invalid_setting_reply("timezone", "Mars/Olympus_Mons");
+        assert_eq!(
+            reply.content,
+            Some("`Mars/Olympus_Mons` is not a valid timezone.".to_string())
+        );
+        assert_eq!(reply.ephemeral, Some(true));
+    }
+}
@@ -0,0 +1,125 @@
+use poise::{
+    serenity_prelude::{self as serenity, CreateEmbed},
+    CreateReply,
+};
+
+use crate::models::user_settings::{RetentionOverride, UserSettings, TIME_OF_DAY_FORMAT};
+
+pub fn get_user_settings_reply(settings: &UserSettings) -> CreateReply {
+    CreateReply::default()
+        .embed(get_user_settings_embed(settings))
+        .ephemeral(true)
+}
+
+fn get_user_settings_embed(settings: &UserSettings) -> CreateEmbed {
+    let quiet_hours = settings
+        .quiet_hours()
+        .map_or("Not set".to_string(), |quiet_hours| quiet_hours.to_string());
+
+    let digest_time = settings
+        .digest_time()
+        .map_or("Off".to_string(), |digest_time| {
+            digest_time.format(TIME_OF_DAY_FORMAT).to_string()
+        });
+
+    CreateEmbed::default()
+        .title("Your settings")
+        .field("Timezone", settings.timezone().name(), true)
+        .field("Quiet hours", quiet_hours, true)
+        .field("Daily digest", digest_time, true)
+        .field(
+            "Privacy mode",
+            if settings.privacy_mode() { "On" } else { "Off" },
+            true,
+        )
+        .field(
+            "Bookmarks kept for",
+            get_retention_field_value(settings.bookmark_retention()),
+            true,
+        )
+        .field(
+            "Message content kept for",
+            get_retention_field_value(settings.message_content_retention()),
+            true,
+        )
+        .field(
+            "Spaced repetition",
+            if settings.spaced_repetition() {
+                "On"
+            } else {
+                "Off"
+            },
+            true,
+        )
+        .colour(serenity::Colour::TEAL)
+}
+
+fn get_retention_field_value(retention: Option<RetentionOverride>) -> String {
+    retention.map_or("Server default".to_string(), |retention| {
+        retention.to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use crate::models::user_settings::QuietHours;
+
+    use super::*;
+
+    #[test]
+    fn test_get_user_settings_reply_for_default_settings() {
+        let reply = get_user_settings_reply(&UserSettings::new(123456789));
+
+        assert_eq!(reply.ephemeral, Some(true));
+
+        assert_eq!(reply.embeds.len(), 1);
+        let embed = reply.embeds.first().unwrap().to_owned();
+
+        let expected_embed = CreateEmbed::default()
+            .title("Your settings")
+            .field("Timezone", "UTC", true)
+            .field("Quiet hours", "Not set", true)
+            .field("Daily digest", "Off", true)
+            .field("Privacy mode", "Off", true)
+            .field("Bookmarks kept for", "Server default", true)
+            .field("Message content kept for", "Server default", true)
+            .field("Spaced repetition", "Off", true)
+            .colour(serenity::Colour::TEAL);
+
+        assert_eq!(embed, expected_embed);
+    }
+
+    #[test]
+    fn test_get_user_settings_reply_for_customized_settings() {
+        let mut settings = UserSettings::new(123456789);
+        settings.set_timezone(chrono_tz::Europe::Berlin);
+        settings.set_quiet_hours(Some(QuietHours::new(
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(7, 30, 0).unwrap(),
+        )));
+        settings.set_digest_time(Some(NaiveTime::from_hms_opt(8, 0, 0).unwrap()));
+        settings.set_privacy_mode(true);
+        settings.set_bookmark_retention(Some(RetentionOverride::Forever));
+        settings.set_message_content_retention(Some(RetentionOverride::Days(30)));
+        settings.set_spaced_repetition(true);
+
+        let reply = get_user_settings_reply(&settings);
+
+        let embed = reply.embeds.first().unwrap().to_owned();
+
+        let expected_embed = CreateEmbed::default()
+            .title("Your settings")
+            .field("Timezone", "Europe/Berlin", true)
+            .field("Quiet hours", "22:00 to 07:30", true)
+            .field("Daily digest", "08:00", true)
+            .field("Privacy mode", "On", true)
+            .field("Bookmarks kept for", "Forever", true)
+            .field("Message content kept for", "30 days", true)
+            .field("Spaced repetition", "On", true)
+            .colour(serenity::Colour::TEAL);
+
+        assert_eq!(embed, expected_embed);
+    }
+}
@@ -0,0 +1,844 @@
+use std::{fmt, net::SocketAddr, path::PathBuf, str::FromStr};
+
+use serde::Deserialize;
+use strum_macros::{Display, EnumString};
+
+use crate::{catch_up::CatchUpBehavior, database::message_encryption::MessageKeyring};
+
+/// Where the config file is read from, unless [`CONFIG_FILE_ENV_VAR_NAME`] names another file.
+/// Unlike a file named explicitly, this one doesn't have to exist.
+const DEFAULT_CONFIG_FILE_PATH: &str = "./config.toml";
+
+const CONFIG_FILE_ENV_VAR_NAME: &str = "CONFIG_FILE";
+const DISCORD_TOKEN_ENV_VAR_NAME: &str = "DISCORD_TOKEN";
+const DATABASE_PATH_ENV_VAR_NAME: &str = "DATABASE_PATH";
+const TEST_GUILD_IDS_ENV_VAR_NAME: &str = "TEST_GUILD_IDS";
+/// Still read (when [`TEST_GUILD_IDS_ENV_VAR_NAME`] isn't set) from back when only one test guild was supported
+const TEST_GUILD_ID_ENV_VAR_NAME: &str = "TEST_GUILD_ID";
+const GLOBAL_COMMAND_REGISTRATION_ENV_VAR_NAME: &str = "GLOBAL_COMMAND_REGISTRATION";
+const DEFAULT_BOOKMARK_LIST_SIZE_ENV_VAR_NAME: &str = "DEFAULT_BOOKMARK_LIST_SIZE";
+const DEFAULT_REMINDER_LIST_SIZE_ENV_VAR_NAME: &str = "DEFAULT_REMINDER_LIST_SIZE";
+const DELIVERY_WORKER_COUNT_ENV_VAR_NAME: &str = "DELIVERY_WORKER_COUNT";
+const DELIVERY_QUEUE_CAPACITY_ENV_VAR_NAME: &str = "DELIVERY_QUEUE_CAPACITY";
+const CATCH_UP_BEHAVIOR_ENV_VAR_NAME: &str = "CATCH_UP_BEHAVIOR";
+const CATCH_UP_THRESHOLD_SECONDS_ENV_VAR_NAME: &str = "CATCH_UP_THRESHOLD_SECONDS";
+const BOOKMARK_RETENTION_DAYS_ENV_VAR_NAME: &str = "BOOKMARK_RETENTION_DAYS";
+const MESSAGE_CONTENT_RETENTION_DAYS_ENV_VAR_NAME: &str = "MESSAGE_CONTENT_RETENTION_DAYS";
+const REMINDER_HISTORY_RETENTION_DAYS_ENV_VAR_NAME: &str = "REMINDER_HISTORY_RETENTION_DAYS";
+const RETENTION_DRY_RUN_ENV_VAR_NAME: &str = "RETENTION_DRY_RUN";
+const MESSAGE_ENCRYPTION_KEYS_ENV_VAR_NAME: &str = "MESSAGE_ENCRYPTION_KEYS";
+const MESSAGE_ENCRYPTION_KEY_FILE_ENV_VAR_NAME: &str = "MESSAGE_ENCRYPTION_KEY_FILE";
+const LOG_LEVEL_ENV_VAR_NAME: &str = "LOG_LEVEL";
+const LOG_FORMAT_ENV_VAR_NAME: &str = "LOG_FORMAT";
+const LOG_MESSAGE_CONTENT_ENV_VAR_NAME: &str = "LOG_MESSAGE_CONTENT";
+const METRICS_ADDRESS_ENV_VAR_NAME: &str = "METRICS_ADDRESS";
+const SHUTDOWN_TIMEOUT_SECONDS_ENV_VAR_NAME: &str = "SHUTDOWN_TIMEOUT_SECONDS";
+
+/// The largest list size the list commands accept
+const MAX_LIST_SIZE: u64 = 100;
+
+/// Everything configurable about the bot, read from a TOML file with environment variables taking precedence.
+/// See the README for the file's layout.
+///
+/// Its [`Debug`] output leaves out the secrets, so it's safe to log.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Best left to the `DISCORD_TOKEN` environment variable rather than written in the file
+    pub discord_token: String,
+    pub database: DatabaseConfig,
+    pub commands: CommandsConfig,
+    pub lists: ListsConfig,
+    pub delivery: DeliveryConfig,
+    pub retention: RetentionConfig,
+    pub encryption: EncryptionConfig,
+    pub logging: LoggingConfig,
+    pub metrics: MetricsConfig,
+    pub shutdown: ShutdownConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandsConfig {
+    /// Servers whose commands are brought up to date on every start-up, so changes show up there immediately
+    pub test_guild_ids: Vec<u64>,
+    pub global_registration: GlobalCommandRegistration,
+}
+
+/// When to register commands with Discord globally.
+/// Discord limits how many commands can be registered a day, so this is best left to [`Self::OnRequest`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum GlobalCommandRegistration {
+    /// Only when the `register-commands --global` subcommand is run
+    #[default]
+    OnRequest,
+    /// Every time the bot starts
+    OnStartup,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListsConfig {
+    /// How many bookmarks `/get_bookmarks` shows when the user doesn't say
+    pub default_bookmark_count: u64,
+    /// How many reminders `/get_reminders` shows when the user doesn't say
+    pub default_reminder_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeliveryConfig {
+    /// How many reminders may be delivered concurrently. Each user's reminders are still delivered in order.
+    pub worker_count: usize,
+    /// How many newly set reminders may wait to be picked up by the scheduler before setting another one waits too
+    pub queue_capacity: usize,
+    /// What to do with reminders that came due while the bot was offline
+    pub catch_up_behavior: CatchUpBehavior,
+    /// How overdue a reminder must be at start-up to count as missed
+    pub catch_up_threshold_seconds: i64,
+}
+
+/// How many days stored data is kept for. Data is kept forever when its field isn't set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Unstarred bookmarks older than this are deleted
+    pub bookmark_days: Option<u32>,
+    /// Bookmarks older than this keep only a link to their message
+    pub message_content_days: Option<u32>,
+    /// Records of delivered reminders older than this are deleted
+    pub reminder_history_days: Option<u32>,
+    /// When set, what would be removed is only logged
+    pub dry_run: bool,
+}
+
+/// Only used when the bot is built with the `metrics` feature
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Where `/healthz` and `/metrics` are served. Nothing is served when this isn't set.
+    pub address: Option<SocketAddr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    /// How long to wait for in-flight deliveries to finish after being asked to stop, before exiting anyway
+    pub timeout_seconds: u64,
+}
+
+/// Keys for encrypting stored messages, in the format read by [`MessageKeyring::parse`].
+/// At most one of the fields may be set; messages are stored unencrypted when neither is.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EncryptionConfig {
+    /// Best left to the `MESSAGE_ENCRYPTION_KEYS` environment variable rather than written in the file
+    pub keys: Option<String>,
+    pub key_file: Option<PathBuf>,
+}
+
+/// Shown in place of secrets in [`Config`]'s [`Debug`] output
+const REDACTED_SECRET: &str = "[redacted]";
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field(
+                "discord_token",
+                &get_redacted_secret(Some(&self.discord_token).filter(|token| !token.is_empty())),
+            )
+            .field("database", &self.database)
+            .field("commands", &self.commands)
+            .field("lists", &self.lists)
+            .field("delivery", &self.delivery)
+            .field("retention", &self.retention)
+            .field("encryption", &self.encryption)
+            .field("logging", &self.logging)
+            .field("metrics", &self.metrics)
+            .field("shutdown", &self.shutdown)
+            .finish()
+    }
+}
+
+impl fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionConfig")
+            .field("keys", &get_redacted_secret(self.keys.as_ref()))
+            .field("key_file", &self.key_file)
+            .finish()
+    }
+}
+
+/// Still shows whether the secret is set, which is usually what's being debugged
+fn get_redacted_secret(secret: Option<&String>) -> Option<&'static str> {
+    secret.map(|_| REDACTED_SECRET)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Only applies to the bot's own logs, unless overridden by the `RUST_LOG` environment variable
+    pub level: LogLevel,
+    pub format: LogFormat,
+    /// Message contents are replaced with a placeholder in logs unless this is set
+    pub include_message_content: bool,
+}
+
+/// From least to most verbose
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    /// Also logs every command that's run
+    #[default]
+    Info,
+    /// Also logs every event received from Discord
+    Debug,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LogFormat {
+    /// One human-readable line per event
+    #[default]
+    Text,
+    /// One JSON object per event, including the fields of the spans it happened in
+    Json,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    ReadFile {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    ParseFile {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    InvalidEnvVar {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+    Invalid {
+        key: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadFile { path, error } => {
+                write!(
+                    f,
+                    "Failed to read config file `{}`: {}",
+                    path.display(),
+                    error
+                )
+            }
+            Self::ParseFile { path, error } => {
+                write!(f, "Invalid config file `{}`: {}", path.display(), error)
+            }
+            Self::InvalidEnvVar {
+                name,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Environment variable `{}` must be {}, but was `{}`",
+                name, expected, value
+            ),
+            Self::Invalid { key, reason } => {
+                write!(f, "Invalid config value `{}`: {}", key, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("./data.db"),
+        }
+    }
+}
+
+impl Default for ListsConfig {
+    fn default() -> Self {
+        Self {
+            default_bookmark_count: 20,
+            default_reminder_count: 20,
+        }
+    }
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            worker_count: 4,
+            queue_capacity: 32,
+            catch_up_behavior: CatchUpBehavior::Summary,
+            catch_up_threshold_seconds: 5 * 60,
+        }
+    }
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 30,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file, applies environment variable overrides on top of it and validates the result
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_with_env(|name| std::env::var(name).ok())
+    }
+
+    fn load_with_env(get_env: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let (path, is_path_explicit) = match get_env(CONFIG_FILE_ENV_VAR_NAME) {
+            Some(path) => (PathBuf::from(path), true),
+            None => (PathBuf::from(DEFAULT_CONFIG_FILE_PATH), false),
+        };
+
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                Self::parse(&contents).map_err(|error| ConfigError::ParseFile { path, error })?
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound && !is_path_explicit => {
+                Self::default()
+            }
+            Err(error) => return Err(ConfigError::ReadFile { path, error }),
+        };
+
+        config.apply_env_overrides(get_env)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    fn apply_env_overrides(
+        &mut self,
+        get_env: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        let get_env = &get_env;
+
+        if let Some(discord_token) = get_env(DISCORD_TOKEN_ENV_VAR_NAME) {
+            self.discord_token = discord_token;
+        }
+        if let Some(path) = get_env(DATABASE_PATH_ENV_VAR_NAME) {
+            self.database.path = PathBuf::from(path);
+        }
+
+        if let Some(guild_ids) = get_env(TEST_GUILD_IDS_ENV_VAR_NAME) {
+            self.commands.test_guild_ids = guild_ids
+                .split(',')
+                .map(str::trim)
+                .filter(|guild_id| !guild_id.is_empty())
+                .map(str::parse::<u64>)
+                .collect::<Result<_, _>>()
+                .map_err(|_| ConfigError::InvalidEnvVar {
+                    name: TEST_GUILD_IDS_ENV_VAR_NAME,
+                    value: guild_ids.clone(),
+                    expected: "a comma-separated list of server IDs",
+                })?;
+        } else if let Some(guild_id) =
+            parse_env_var::<u64>(get_env, TEST_GUILD_ID_ENV_VAR_NAME, "a server ID")?
+        {
+            self.commands.test_guild_ids = vec![guild_id];
+        }
+        if let Some(global_registration) = parse_env_var(
+            get_env,
+            GLOBAL_COMMAND_REGISTRATION_ENV_VAR_NAME,
+            "one of `on_request` or `on_startup`",
+        )? {
+            self.commands.global_registration = global_registration;
+        }
+
+        if let Some(count) = parse_env_var(
+            get_env,
+            DEFAULT_BOOKMARK_LIST_SIZE_ENV_VAR_NAME,
+            "a positive integer",
+        )? {
+            self.lists.default_bookmark_count = count;
+        }
+        if let Some(count) = parse_env_var(
+            get_env,
+            DEFAULT_REMINDER_LIST_SIZE_ENV_VAR_NAME,
+            "a positive integer",
+        )? {
+            self.lists.default_reminder_count = count;
+        }
+
+        if let Some(worker_count) = parse_env_var(
+            get_env,
+            DELIVERY_WORKER_COUNT_ENV_VAR_NAME,
+            "a positive integer",
+        )? {
+            self.delivery.worker_count = worker_count;
+        }
+        if let Some(queue_capacity) = parse_env_var(
+            get_env,
+            DELIVERY_QUEUE_CAPACITY_ENV_VAR_NAME,
+            "a positive integer",
+        )? {
+            self.delivery.queue_capacity = queue_capacity;
+        }
+        if let Some(catch_up_behavior) = parse_env_var(
+            get_env,
+            CATCH_UP_BEHAVIOR_ENV_VAR_NAME,
+            "one of `summary` or `individual`",
+        )? {
+            self.delivery.catch_up_behavior = catch_up_behavior;
+        }
+        if let Some(threshold) = parse_env_var(
+            get_env,
+            CATCH_UP_THRESHOLD_SECONDS_ENV_VAR_NAME,
+            "a whole number of seconds",
+        )? {
+            self.delivery.catch_up_threshold_seconds = threshold;
+        }
+
+        if let Some(days) = parse_env_var(
+            get_env,
+            BOOKMARK_RETENTION_DAYS_ENV_VAR_NAME,
+            "a whole number of days",
+        )? {
+            self.retention.bookmark_days = Some(days);
+        }
+        if let Some(days) = parse_env_var(
+            get_env,
+            MESSAGE_CONTENT_RETENTION_DAYS_ENV_VAR_NAME,
+            "a whole number of days",
+        )? {
+            self.retention.message_content_days = Some(days);
+        }
+        if let Some(days) = parse_env_var(
+            get_env,
+            REMINDER_HISTORY_RETENTION_DAYS_ENV_VAR_NAME,
+            "a whole number of days",
+        )? {
+            self.retention.reminder_history_days = Some(days);
+        }
+        if let Some(dry_run) = parse_env_var(
+            get_env,
+            RETENTION_DRY_RUN_ENV_VAR_NAME,
+            "one of `true` or `false`",
+        )? {
+            self.retention.dry_run = dry_run;
+        }
+
+        // Either environment variable replaces both keys settings from the file, so the file can't conflict with them
+        let keys = get_env(MESSAGE_ENCRYPTION_KEYS_ENV_VAR_NAME);
+        let key_file = get_env(MESSAGE_ENCRYPTION_KEY_FILE_ENV_VAR_NAME);
+        if keys.is_some() || key_file.is_some() {
+            self.encryption = EncryptionConfig {
+                keys,
+                key_file: key_file.map(PathBuf::from),
+            };
+        }
+
+        if let Some(level) = parse_env_var(
+            get_env,
+            LOG_LEVEL_ENV_VAR_NAME,
+            "one of `error`, `warn`, `info` or `debug`",
+        )? {
+            self.logging.level = level;
+        }
+        if let Some(format) =
+            parse_env_var(get_env, LOG_FORMAT_ENV_VAR_NAME, "one of `text` or `json`")?
+        {
+            self.logging.format = format;
+        }
+        if let Some(include_message_content) = parse_env_var(
+            get_env,
+            LOG_MESSAGE_CONTENT_ENV_VAR_NAME,
+            "one of `true` or `false`",
+        )? {
+            self.logging.include_message_content = include_message_content;
+        }
+
+        if let Some(address) = parse_env_var(
+            get_env,
+            METRICS_ADDRESS_ENV_VAR_NAME,
+            "an address with a port, like `127.0.0.1:9090`",
+        )? {
+            self.metrics.address = Some(address);
+        }
+
+        if let Some(timeout) = parse_env_var(
+            get_env,
+            SHUTDOWN_TIMEOUT_SECONDS_ENV_VAR_NAME,
+            "a whole number of seconds",
+        )? {
+            self.shutdown.timeout_seconds = timeout;
+        }
+
+        Ok(())
+    }
+
+    /// Only the commands that talk to Discord need the token, so it isn't checked by [`Self::load`]
+    pub fn require_discord_token(&self) -> Result<&str, ConfigError> {
+        if self.discord_token.trim().is_empty() {
+            return Err(ConfigError::Invalid {
+                key: "discord_token",
+                reason: format!(
+                    "must be set, either in the config file or with the `{}` environment variable",
+                    DISCORD_TOKEN_ENV_VAR_NAME
+                ),
+            });
+        }
+
+        Ok(&self.discord_token)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.commands.test_guild_ids.contains(&0) {
+            return Err(ConfigError::Invalid {
+                key: "commands.test_guild_ids",
+                reason: "server IDs can't be 0".to_string(),
+            });
+        }
+
+        for (key, count) in [
+            (
+                "lists.default_bookmark_count",
+                self.lists.default_bookmark_count,
+            ),
+            (
+                "lists.default_reminder_count",
+                self.lists.default_reminder_count,
+            ),
+        ] {
+            if !(1..=MAX_LIST_SIZE).contains(&count) {
+                return Err(ConfigError::Invalid {
+                    key,
+                    reason: format!("must be between 1 and {}, but was {}", MAX_LIST_SIZE, count),
+                });
+            }
+        }
+
+        if self.delivery.worker_count == 0 {
+            return Err(ConfigError::Invalid {
+                key: "delivery.worker_count",
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        if self.delivery.queue_capacity == 0 {
+            return Err(ConfigError::Invalid {
+                key: "delivery.queue_capacity",
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        if self.delivery.catch_up_threshold_seconds < 0 {
+            return Err(ConfigError::Invalid {
+                key: "delivery.catch_up_threshold_seconds",
+                reason: "can't be negative".to_string(),
+            });
+        }
+
+        self.load_message_keyring()?;
+
+        Ok(())
+    }
+
+    /// Returns [`None`] if no keys are configured, leaving messages unencrypted
+    pub fn load_message_keyring(&self) -> Result<Option<MessageKeyring>, ConfigError> {
+        let keys = match (&self.encryption.keys, &self.encryption.key_file) {
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::Invalid {
+                    key: "encryption",
+                    reason: "only one of `keys` and `key_file` may be set".to_string(),
+                })
+            }
+            (Some(keys), None) => keys.clone(),
+            (None, Some(key_file)) => {
+                std::fs::read_to_string(key_file).map_err(|error| ConfigError::Invalid {
+                    key: "encryption.key_file",
+                    reason: format!("`{}` couldn't be read: {}", key_file.display(), error),
+                })?
+            }
+            (None, None) => return Ok(None),
+        };
+
+        MessageKeyring::parse(&keys)
+            .map(Some)
+            .map_err(|error| ConfigError::Invalid {
+                key: "encryption.keys",
+                reason: format!(
+                    "must be `<key ID>:<base64 encoded 32 byte key>` entries: {:?}",
+                    error
+                ),
+            })
+    }
+}
+
+fn parse_env_var<T: FromStr>(
+    get_env: impl Fn(&str) -> Option<String>,
+    name: &'static str,
+    expected: &'static str,
+) -> Result<Option<T>, ConfigError> {
+    get_env(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse::<T>()
+                .map_err(|_| ConfigError::InvalidEnvVar {
+                    name,
+                    value,
+                    expected,
+                })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn get_env_from<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        let vars = vars.iter().copied().collect::<HashMap<_, _>>();
+        move |name| vars.get(name).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn test_parse_full_config_file() {
+        let config = Config::parse(
+            r#"
+            discord_token = "token"
+
+            [database]
+            path = "/var/lib/bot/data.db"
+
+            [commands]
+            test_guild_ids = [1, 2]
+            global_registration = "on_startup"
+
+            [lists]
+            default_bookmark_count = 10
+            default_reminder_count = 5
+
+            [delivery]
+            worker_count = 8
+            queue_capacity = 64
+            catch_up_behavior = "individual"
+            catch_up_threshold_seconds = 60
+
+            [retention]
+            bookmark_days = 365
+            message_content_days = 30
+            reminder_history_days = 90
+            dry_run = true
+
+            [logging]
+            level = "debug"
+            format = "json"
+
+            [metrics]
+            address = "127.0.0.1:9090"
+
+            [shutdown]
+            timeout_seconds = 10
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                discord_token: "token".to_string(),
+                database: DatabaseConfig {
+                    path: PathBuf::from("/var/lib/bot/data.db"),
+                },
+                commands: CommandsConfig {
+                    test_guild_ids: vec![1, 2],
+                    global_registration: GlobalCommandRegistration::OnStartup,
+                },
+                lists: ListsConfig {
+                    default_bookmark_count: 10,
+                    default_reminder_count: 5,
+                },
+                delivery: DeliveryConfig {
+                    worker_count: 8,
+                    queue_capacity: 64,
+                    catch_up_behavior: CatchUpBehavior::Individual,
+                    catch_up_threshold_seconds: 60,
+                },
+                retention: RetentionConfig {
+                    bookmark_days: Some(365),
+                    message_content_days: Some(30),
+                    reminder_history_days: Some(90),
+                    dry_run: true,
+                },
+                encryption: EncryptionConfig::default(),
+                logging: LoggingConfig {
+                    level: LogLevel::Debug,
+                    format: LogFormat::Json,
+                    include_message_content: false,
+                },
+                metrics: MetricsConfig {
+                    address: Some(SocketAddr::from(([127, 0, 0, 1], 9090))),
+                },
+                shutdown: ShutdownConfig {
+                    timeout_seconds: 10,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_config_file() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_parse_config_file_with_unknown_key() {
+        assert!(Config::parse("[delivery]\nworkers = 8").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config =
+            Config::parse("[delivery]\nworker_count = 8\nqueue_capacity = 64").unwrap();
+
+        config
+            .apply_env_overrides(get_env_from(&[
+                ("DISCORD_TOKEN", "token"),
+                ("TEST_GUILD_IDS", "1, 2,3"),
+                ("DELIVERY_WORKER_COUNT", "2"),
+                ("BOOKMARK_RETENTION_DAYS", "7"),
+                ("LOG_LEVEL", "warn"),
+                ("LOG_MESSAGE_CONTENT", "true"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.discord_token, "token");
+        assert_eq!(config.commands.test_guild_ids, vec![1, 2, 3]);
+        assert_eq!(config.delivery.worker_count, 2);
+        assert_eq!(config.delivery.queue_capacity, 64);
+        assert_eq!(config.retention.bookmark_days, Some(7));
+        assert_eq!(config.logging.level, LogLevel::Warn);
+        assert!(config.logging.include_message_content);
+    }
+
+    #[test]
+    fn test_legacy_test_guild_id_env_var() {
+        let mut config = Config::default();
+        config
+            .apply_env_overrides(get_env_from(&[("TEST_GUILD_ID", "42")]))
+            .unwrap();
+        assert_eq!(config.commands.test_guild_ids, vec![42]);
+
+        let mut config = Config::default();
+        config
+            .apply_env_overrides(get_env_from(&[
+                ("TEST_GUILD_ID", "42"),
+                ("TEST_GUILD_IDS", "1,2"),
+            ]))
+            .unwrap();
+        assert_eq!(config.commands.test_guild_ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_invalid_env_var() {
+        let error = Config::default()
+            .apply_env_overrides(get_env_from(&[("CATCH_UP_BEHAVIOR", "sometimes")]))
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Environment variable `CATCH_UP_BEHAVIOR` must be one of `summary` or `individual`, but was `sometimes`"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid_config = Config::default();
+        assert!(valid_config.validate().is_ok());
+
+        let mut config = valid_config.clone();
+        config.delivery.worker_count = 0;
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid config value `delivery.worker_count`: must be at least 1"
+        );
+
+        let mut config = valid_config.clone();
+        config.lists.default_bookmark_count = 101;
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Invalid config value `lists.default_bookmark_count`: must be between 1 and 100, but was 101"
+        );
+
+        let mut config = valid_config.clone();
+        config.encryption.keys = Some("not a key".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_require_discord_token() {
+        let config = Config {
+            discord_token: "token".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.require_discord_token().unwrap(), "token");
+
+        assert_eq!(
+            Config::default().require_discord_token().unwrap_err().to_string(),
+            "Invalid config value `discord_token`: must be set, either in the config file or with the `DISCORD_TOKEN` environment variable"
+        );
+    }
+
+    #[test]
+    fn test_load_with_missing_explicit_config_file() {
+        let error = Config::load_with_env(get_env_from(&[
+            ("CONFIG_FILE", "./this/file/does/not/exist.toml"),
+            ("DISCORD_TOKEN", "token"),
+        ]))
+        .unwrap_err();
+
+        assert!(matches!(error, ConfigError::ReadFile { .. }));
+    }
+
+    #[test]
+    fn test_debug_output_redacts_secrets() {
+        let config = Config {
+            discord_token: "discord-secret".to_string(),
+            encryption: EncryptionConfig {
+                keys: Some("1:encryption-secret".to_string()),
+                key_file: None,
+            },
+            ..Default::default()
+        };
+
+        let debug_output = format!("{:?}", config);
+        assert!(!debug_output.contains("discord-secret"));
+        assert!(!debug_output.contains("encryption-secret"));
+        assert!(debug_output.contains(r#"discord_token: Some("[redacted]")"#));
+        assert!(debug_output.contains(r#"keys: Some("[redacted]")"#));
+        assert!(format!("{:?}", Config::default()).contains("discord_token: None"));
+    }
+}
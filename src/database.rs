@@ -1,2 +1,112 @@
 pub mod bookmark;
+pub mod guild_settings;
+pub mod message_encryption;
 pub mod reminder;
+pub mod reminder_delivery;
+pub mod user_data;
+pub mod user_settings;
+
+use rusqlite::Row;
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+/// For rows that were read fine but hold a value that doesn't parse (e.g. after the database was edited by hand),
+/// so that a single bad row fails the query it's in rather than taking down the bot
+pub fn get_row_parse_error(
+    row: &Row<'_>,
+    column_name: &str,
+    err: impl std::error::Error + Send + Sync + 'static,
+) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        row.as_ref().column_index(column_name).unwrap_or_default(),
+        rusqlite::types::Type::Text,
+        Box::new(err),
+    )
+}
+
+/// Creates every table the bot uses. New tables should be created here, which also ensures
+/// the [`user_data`] tests notice them.
+pub async fn create_all_tables_if_nonexistent(
+    db_connection: &Mutex<Connection>,
+) -> Result<(), tokio_rusqlite::Error> {
+    tokio::try_join!(
+        reminder::create_reminders_table_if_nonexistent(db_connection),
+        bookmark::create_bookmarks_table_if_nonexistent(db_connection),
+        user_settings::create_user_settings_table_if_nonexistent(db_connection),
+        reminder_delivery::create_reminder_deliveries_table_if_nonexistent(db_connection),
+        guild_settings::create_guild_settings_table_if_nonexistent(db_connection),
+    )?;
+
+    Ok(())
+}
+
+/// Lets queries that were already queued finish, after which any other query fails with
+/// [`tokio_rusqlite::Error::ConnectionClosed`]
+pub async fn close_database(
+    db_connection: &Mutex<Connection>,
+) -> Result<(), tokio_rusqlite::Error> {
+    let connection = db_connection.lock().await.clone();
+    connection.close().await
+}
+
+/// Copies the whole database to `path`, which mustn't exist yet. Safe to run while the bot is using the database.
+pub async fn backup_database(
+    db_connection: &Mutex<Connection>,
+    path: std::path::PathBuf,
+) -> Result<(), tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            conn.execute(
+                "VACUUM INTO ?1",
+                tokio_rusqlite::params![path.to_string_lossy()],
+            )?;
+
+            Ok(())
+        })
+        .await
+}
+
+/// Rebuilds the database file, reclaiming the space left behind by deleted rows
+pub async fn vacuum_database(
+    db_connection: &Mutex<Connection>,
+) -> Result<(), tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            conn.execute("VACUUM", [])?;
+
+            Ok(())
+        })
+        .await
+}
+
+/// `ALTER TABLE ... ADD COLUMN` has no `IF NOT EXISTS` form in Sqlite, so this checks the table's
+/// columns first. This lets columns be added to tables that were created by older versions of the bot.
+fn add_column_if_nonexistent(
+    conn: &rusqlite::Connection,
+    table_name: &str,
+    column_name: &str,
+    column_definition: &str,
+) -> Result<(), rusqlite::Error> {
+    let column_exists = conn
+        .prepare(&format!("PRAGMA table_info({})", table_name))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|existing_column_name| existing_column_name == column_name);
+
+    if !column_exists {
+        conn.execute(
+            &format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table_name, column_name, column_definition
+            ),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,10 +1,22 @@
+use poise::serenity_prelude as serenity;
 use rusqlite::{OptionalExtension, Row};
 use tokio::sync::Mutex;
 use tokio_rusqlite::Connection;
 use uuid::Uuid;
 
-use crate::models::bookmark::{
-    BookmarkedMessage, PersistedBookmarkedMessage, PersistedBookmarkedMessageFromRowError,
+use crate::{
+    database::get_row_parse_error,
+    database::{
+        add_column_if_nonexistent,
+        message_encryption::{encrypt_message_column, get_message_column},
+    },
+    models::{
+        bookmark::{
+            BookmarkCounts, BookmarkRetentionInfo, BookmarkedMessage, PersistedBookmarkedMessage,
+            PersistedBookmarkedMessageFromRowError,
+        },
+        get_message_without_content,
+    },
 };
 
 pub async fn create_bookmarks_table_if_nonexistent(
@@ -23,24 +35,53 @@ pub async fn create_bookmarks_table_if_nonexistent(
                     user_id TEXT NOT NULL,
                     message TEXT NOT NULL
                 ) STRICT;
-                 
-                CREATE UNIQUE INDEX IF NOT EXISTS one_bookmark_per_message_per_user ON bookmarks(json_extract(message, '$.id'), user_id);
                 COMMIT;
                  ",
             )?;
+            add_column_if_nonexistent(
+                conn,
+                "bookmarks",
+                "content_omitted",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            // The message may be encrypted, so its ID is kept in its own column for enforcing one bookmark per message.
+            // Bookmarks from before this column existed can only be plaintext, so their IDs are copied out of the JSON.
+            add_column_if_nonexistent(conn, "bookmarks", "message_id", "TEXT")?;
+            add_column_if_nonexistent(
+                conn,
+                "bookmarks",
+                "starred",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            add_column_if_nonexistent(conn, "bookmarks", "read", "INTEGER NOT NULL DEFAULT 0")?;
+            add_column_if_nonexistent(conn, "bookmarks", "review_step", "INTEGER")?;
+            // Kept apart from `content_omitted` so that purged bookmarks can still be told apart from privacy mode ones
+            add_column_if_nonexistent(
+                conn,
+                "bookmarks",
+                "content_purged",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            conn.execute_batch(
+                "
+                BEGIN;
+                UPDATE bookmarks SET message_id = json_extract(message, '$.id') WHERE message_id IS NULL AND json_valid(message);
+                DROP INDEX IF EXISTS one_bookmark_per_message_per_user;
+                CREATE UNIQUE INDEX IF NOT EXISTS one_bookmark_per_message_id_per_user ON bookmarks(message_id, user_id);
+                COMMIT;
+                ",
+            )?;
             Ok(())
         })
         .await
 }
 
 #[expect(clippy::large_enum_variant)]
-// Noticing the #[expect(dead_code)] on the 2nd variant, only the first variant (the large one) is actually in-use.
-// That means there isn't any size wasted when passing around the 2nd variant (as it is never used).
-// So, until that changes, I don't super see a need to address this large size discrepancy
+// The large variant is by far the more common one (the other only comes up when the database fails),
+// so there's little size wasted by not boxing it
 #[derive(Debug)]
 pub enum InsertBookmarkError {
     BookmarkAlreadyExists(PersistedBookmarkedMessage),
-    #[expect(dead_code)]
     TokioRusqliteError(tokio_rusqlite::Error),
 }
 
@@ -60,40 +101,37 @@ pub async fn insert_bookmark(
         serde_json::to_string(&bookmark.message()).expect("Failed to serialize message");
 
     let message_id = bookmark.message().id.to_string();
-    let existing_bookmark = db_connection
-        .lock()
-        .await
-        .call(move |conn| {
-            let bookmark = conn
-                .query_row(
-                    "SELECT * FROM bookmarks WHERE user_id = ?1 AND json_extract(message, '$.id') = ?2",
-                    tokio_rusqlite::params![user_id, message_id],
-                    bookmark_from_row,
-                )
-                .optional()?;
-
-            Ok(bookmark)
-        })
-        .await?;
-
-    if let Some(existing_bookmark) = existing_bookmark {
-        return Err(InsertBookmarkError::BookmarkAlreadyExists(
-            existing_bookmark,
-        ));
-    }
-
+    let content_omitted = bookmark.is_content_omitted();
+    // Checking for an existing bookmark and inserting happen in one go, with the unique index having the final say,
+    // so bookmarking the same message twice at once (e.g. by double tapping a reaction shortcut) can't create two
     let pk = db_connection
         .lock()
         .await
         .call(move |conn| {
-            conn.execute(
-                "INSERT INTO bookmarks (bookmark_id, user_id, message) VALUES (?1, ?2, ?3)",
-                tokio_rusqlite::params![bookmark_id, user_id, stringified_message],
+            let inserted_row_count = conn.execute(
+                "INSERT INTO bookmarks (bookmark_id, user_id, message, content_omitted, message_id) VALUES (?1, ?2, ?3, ?4, ?5)
+                    ON CONFLICT(message_id, user_id) DO NOTHING",
+                tokio_rusqlite::params![
+                    bookmark_id,
+                    user_id,
+                    encrypt_message_column(stringified_message),
+                    content_omitted,
+                    message_id
+                ],
             )?;
+            if inserted_row_count == 0 {
+                let existing_bookmark = conn.query_row(
+                    "SELECT * FROM bookmarks WHERE user_id = ?1 AND message_id = ?2",
+                    tokio_rusqlite::params![user_id, message_id],
+                    bookmark_from_row,
+                )?;
+                return Ok(Err(existing_bookmark));
+            }
 
-            Ok(conn.last_insert_rowid())
+            Ok(Ok(conn.last_insert_rowid()))
         })
-        .await?;
+        .await?
+        .map_err(InsertBookmarkError::BookmarkAlreadyExists)?;
 
     Ok(PersistedBookmarkedMessage::from_bookmarked_message(
         bookmark, pk,
@@ -121,11 +159,13 @@ pub async fn get_bookmark_by_id(
         .await
 }
 
+/// Newest first, leaving out the ones marked as read if `unread_only` is set
 pub async fn get_bookmarks_for_user(
     db_connection: &Mutex<Connection>,
     user_id: u64,
     max_quantity_to_retrieve: u64,
     offset: u64,
+    unread_only: bool,
 ) -> Result<Vec<PersistedBookmarkedMessage>, tokio_rusqlite::Error> {
     db_connection
         .lock()
@@ -133,9 +173,12 @@ pub async fn get_bookmarks_for_user(
         .call(move |conn| {
             let reminders = conn
                 .prepare(
-                    "SELECT * FROM bookmarks WHERE user_id = ?1 ORDER BY bookmark_id DESC LIMIT ?2 OFFSET ?3",
+                    "SELECT * FROM bookmarks WHERE user_id = ?1 AND (read = 0 OR NOT ?4) ORDER BY bookmark_id DESC LIMIT ?2 OFFSET ?3",
+                )?
+                .query_map(
+                    tokio_rusqlite::params![user_id, max_quantity_to_retrieve, offset, unread_only],
+                    bookmark_from_row,
                 )?
-                .query_map([user_id, max_quantity_to_retrieve, offset], bookmark_from_row)?
                 .collect::<Result<Vec<_>, _>>()?;
 
             Ok(reminders)
@@ -143,6 +186,291 @@ pub async fn get_bookmarks_for_user(
         .await
 }
 
+/// The bookmark that's been waiting to be read the longest, for working through bookmarks like a reading list
+pub async fn get_oldest_unread_bookmark_for_user(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+) -> Result<Option<PersistedBookmarkedMessage>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let bookmark = conn
+                .query_row(
+                    "SELECT * FROM bookmarks WHERE user_id = ?1 AND read = 0 ORDER BY bookmark_id ASC LIMIT 1",
+                    [user_id],
+                    bookmark_from_row,
+                )
+                .optional()?;
+
+            Ok(bookmark)
+        })
+        .await
+}
+
+pub async fn count_bookmarks_for_user(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+) -> Result<BookmarkCounts, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let counts = conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(read = 0), 0) FROM bookmarks WHERE user_id = ?1",
+                [user_id],
+                |row| Ok(BookmarkCounts::new(row.get(0)?, row.get(1)?)),
+            )?;
+
+            Ok(counts)
+        })
+        .await
+}
+
+/// Used to resurface unread bookmarks the user may have forgotten about
+pub async fn get_random_unread_bookmarks_for_user(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+    quantity_to_retrieve: u64,
+) -> Result<Vec<PersistedBookmarkedMessage>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let bookmarks = conn
+                .prepare("SELECT * FROM bookmarks WHERE user_id = ?1 AND read = 0 ORDER BY RANDOM() LIMIT ?2")?
+                .query_map([user_id, quantity_to_retrieve], bookmark_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(bookmarks)
+        })
+        .await
+}
+
+/// Used by the retention policy, which has to look at every bookmark
+pub async fn get_all_bookmarks(
+    db_connection: &Mutex<Connection>,
+) -> Result<Vec<PersistedBookmarkedMessage>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            let bookmarks = conn
+                .prepare("SELECT * FROM bookmarks")?
+                .query_map([], bookmark_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(bookmarks)
+        })
+        .await
+}
+
+/// The user's bookmarks that have no review waiting in the reminders table, oldest first
+pub async fn get_bookmarks_without_pending_review_for_user(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+) -> Result<Vec<PersistedBookmarkedMessage>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let bookmarks = conn
+                .prepare(
+                    "SELECT * FROM bookmarks WHERE user_id = ?1 AND NOT EXISTS (
+                        SELECT 1 FROM reminders WHERE reminders.review_bookmark_id = bookmarks.bookmark_id
+                    ) ORDER BY bookmark_id ASC",
+                )?
+                .query_map([user_id], bookmark_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(bookmarks)
+        })
+        .await
+}
+
+/// Flips whether the bookmark is starred, returning its new state
+/// (or [`None`] if `user_id` has no such bookmark)
+pub async fn toggle_bookmark_star(
+    db_connection: &Mutex<Connection>,
+    bookmark_id: Uuid,
+    user_id: u64,
+) -> Result<Option<bool>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let starred = conn
+                .query_row(
+                    "UPDATE bookmarks SET starred = NOT starred WHERE bookmark_id = ?1 AND user_id = ?2 RETURNING starred",
+                    tokio_rusqlite::params![bookmark_id.to_string(), user_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            Ok(starred)
+        })
+        .await
+}
+
+/// Flips whether the bookmark is marked as read, returning its new state
+/// (or [`None`] if `user_id` has no such bookmark)
+pub async fn toggle_bookmark_read(
+    db_connection: &Mutex<Connection>,
+    bookmark_id: Uuid,
+    user_id: u64,
+) -> Result<Option<bool>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let read = conn
+                .query_row(
+                    "UPDATE bookmarks SET read = NOT read WHERE bookmark_id = ?1 AND user_id = ?2 RETURNING read",
+                    tokio_rusqlite::params![bookmark_id.to_string(), user_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            Ok(read)
+        })
+        .await
+}
+
+/// Returns how many bookmarks were deleted
+pub async fn delete_bookmarks_by_pk(
+    db_connection: &Mutex<Connection>,
+    pks: Vec<i64>,
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let transaction = conn.transaction()?;
+            let mut deleted_count = 0;
+            {
+                let mut statement = transaction.prepare("DELETE FROM bookmarks WHERE pk = ?1")?;
+                for pk in pks {
+                    deleted_count += statement.execute([pk])?;
+                }
+            }
+            transaction.commit()?;
+
+            Ok(deleted_count)
+        })
+        .await
+}
+
+/// For the retention policy. Starred bookmarks whose message content is already gone are left out,
+/// since there's nothing left to do with them.
+pub async fn get_bookmark_retention_infos(
+    db_connection: &Mutex<Connection>,
+) -> Result<Vec<BookmarkRetentionInfo>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            let bookmarks = conn
+                .prepare(
+                    "SELECT pk, bookmark_id, user_id, starred, content_omitted = 0 AND content_purged = 0 AS has_message_content
+                        FROM bookmarks
+                        WHERE starred = 0 OR (content_omitted = 0 AND content_purged = 0)",
+                )?
+                .query_map([], |row| {
+                    let bookmark_id = Uuid::parse_str(&row.get::<_, String>("bookmark_id")?)
+                        .map_err(|err| get_row_parse_error(row, "bookmark_id", err))?;
+                    let user_id = row
+                        .get::<_, String>("user_id")?
+                        .parse::<u64>()
+                        .map_err(|err| get_row_parse_error(row, "user_id", err))?;
+
+                    Ok(BookmarkRetentionInfo::new(
+                        row.get("pk")?,
+                        bookmark_id,
+                        user_id,
+                        row.get("starred")?,
+                        row.get("has_message_content")?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(bookmarks)
+        })
+        .await
+}
+
+/// Like [`delete_bookmarks_by_pk`], but skips bookmarks that were starred in the meantime.
+/// Returns how many bookmarks were deleted.
+pub async fn delete_expired_bookmarks_by_pk(
+    db_connection: &Mutex<Connection>,
+    pks: Vec<i64>,
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let transaction = conn.transaction()?;
+            let mut deleted_count = 0;
+            {
+                let mut statement =
+                    transaction.prepare("DELETE FROM bookmarks WHERE pk = ?1 AND starred = 0")?;
+                for pk in pks {
+                    deleted_count += statement.execute([pk])?;
+                }
+            }
+            transaction.commit()?;
+
+            Ok(deleted_count)
+        })
+        .await
+}
+
+/// Overwrites the stored messages of the bookmarks with ones that contain only their IDs,
+/// so that only links to the messages are kept. Returns how many bookmarks were updated.
+pub async fn purge_bookmark_message_contents(
+    db_connection: &Mutex<Connection>,
+    pks: Vec<i64>,
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let transaction = conn.transaction()?;
+            let mut updated_count = 0;
+            {
+                let mut select_statement = transaction.prepare(
+                    "SELECT message FROM bookmarks WHERE pk = ?1 AND content_omitted = 0 AND content_purged = 0",
+                )?;
+                let mut update_statement = transaction.prepare(
+                    "UPDATE bookmarks SET message = ?1, content_purged = 1 WHERE pk = ?2",
+                )?;
+                for pk in pks {
+                    let message = select_statement
+                        .query_row([pk], |row| {
+                            serde_json::from_str::<serenity::Message>(&get_message_column(
+                                row, "message",
+                            )?)
+                            .map_err(|err| get_row_parse_error(row, "message", err))
+                        })
+                        .optional()?;
+                    let Some(message) = message else {
+                        continue;
+                    };
+
+                    let purged_message = serde_json::to_string(&get_message_without_content(&message))
+                        .expect("Failed to serialize message");
+                    updated_count += update_statement.execute(tokio_rusqlite::params![
+                        encrypt_message_column(purged_message),
+                        pk
+                    ])?;
+                }
+            }
+            transaction.commit()?;
+
+            Ok(updated_count)
+        })
+        .await
+}
+
 fn bookmark_from_row(row: &Row<'_>) -> Result<PersistedBookmarkedMessage, rusqlite::Error> {
     match PersistedBookmarkedMessage::try_from(row) {
         Ok(bookmark) => Ok(bookmark),
@@ -150,7 +478,127 @@ fn bookmark_from_row(row: &Row<'_>) -> Result<PersistedBookmarkedMessage, rusqli
             Err(rusqlite_error)
         }
         Err(PersistedBookmarkedMessageFromRowError::ParseBookmarkedMessageError(err)) => {
-            panic!("Failed to parse existing bookmark: {:?}", err)
+            Err(get_row_parse_error(row, err.column_name(), err))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::get_test_db_connection;
+
+    #[tokio::test]
+    async fn test_insert_bookmark_only_once_per_message() {
+        let db_connection = get_test_db_connection().await;
+        let mut message = serenity::Message::default();
+        message.id = 3.into();
+        let get_bookmark = || {
+            BookmarkedMessage::new(
+                Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                123456789,
+                message.clone(),
+            )
+        };
+
+        let (first, second) = tokio::join!(
+            insert_bookmark(&db_connection, get_bookmark()),
+            insert_bookmark(&db_connection, get_bookmark())
+        );
+
+        let (inserted, existing) = match (first, second) {
+            (Ok(inserted), Err(InsertBookmarkError::BookmarkAlreadyExists(existing)))
+            | (Err(InsertBookmarkError::BookmarkAlreadyExists(existing)), Ok(inserted)) => {
+                (inserted, existing)
+            }
+            results => panic!("Expected one bookmark to be inserted, got {:?}", results),
+        };
+        assert_eq!(existing.bookmark_id(), inserted.bookmark_id());
+
+        let other_users_bookmark = insert_bookmark(
+            &db_connection,
+            BookmarkedMessage::new(
+                Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+                987654321,
+                message.clone(),
+            ),
+        )
+        .await;
+        assert!(other_users_bookmark.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bookmarks_can_be_read_in_order() {
+        let db_connection = get_test_db_connection().await;
+        let user_id = 123456789;
+        let mut bookmark_ids = Vec::new();
+        for message_id in 1..=3 {
+            let mut message = serenity::Message::default();
+            message.id = message_id.into();
+            let bookmark = insert_bookmark(
+                &db_connection,
+                BookmarkedMessage::new(
+                    Uuid::new_v7(uuid::Timestamp::from_unix(
+                        uuid::NoContext,
+                        1_700_000_000 + message_id,
+                        0,
+                    )),
+                    user_id,
+                    message,
+                ),
+            )
+            .await
+            .unwrap();
+            bookmark_ids.push(bookmark.bookmark_id());
+        }
+
+        let oldest_unread = get_oldest_unread_bookmark_for_user(&db_connection, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(oldest_unread.bookmark_id(), bookmark_ids[0]);
+        assert!(!oldest_unread.is_read());
+
+        let read = toggle_bookmark_read(&db_connection, bookmark_ids[0], user_id)
+            .await
+            .unwrap();
+        assert_eq!(read, Some(true));
+        let other_users_toggle = toggle_bookmark_read(&db_connection, bookmark_ids[1], 987654321)
+            .await
+            .unwrap();
+        assert_eq!(other_users_toggle, None);
+
+        let oldest_unread = get_oldest_unread_bookmark_for_user(&db_connection, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(oldest_unread.bookmark_id(), bookmark_ids[1]);
+        assert_eq!(
+            count_bookmarks_for_user(&db_connection, user_id)
+                .await
+                .unwrap(),
+            BookmarkCounts::new(3, 2)
+        );
+
+        let unread_bookmark_ids = get_bookmarks_for_user(&db_connection, user_id, 10, 0, true)
+            .await
+            .unwrap()
+            .iter()
+            .map(PersistedBookmarkedMessage::bookmark_id)
+            .collect::<Vec<_>>();
+        assert_eq!(unread_bookmark_ids, vec![bookmark_ids[2], bookmark_ids[1]]);
+        let all_bookmarks = get_bookmarks_for_user(&db_connection, user_id, 10, 0, false)
+            .await
+            .unwrap();
+        assert_eq!(all_bookmarks.len(), 3);
+
+        let random_unread_bookmarks =
+            get_random_unread_bookmarks_for_user(&db_connection, user_id, 10)
+                .await
+                .unwrap();
+        assert_eq!(random_unread_bookmarks.len(), 2);
+        assert!(random_unread_bookmarks
+            .iter()
+            .all(|bookmark| !bookmark.is_read()));
+    }
+}
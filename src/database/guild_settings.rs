@@ -0,0 +1,123 @@
+use rusqlite::{OptionalExtension, Row};
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+use crate::{
+    database::{add_column_if_nonexistent, get_row_parse_error},
+    models::guild_settings::GuildSettings,
+};
+
+pub async fn create_guild_settings_table_if_nonexistent(
+    db_connection: &Mutex<Connection>,
+) -> Result<(), tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS guild_settings (
+                        guild_id TEXT PRIMARY KEY NOT NULL,
+                        privacy_mode INTEGER NOT NULL DEFAULT 0
+                    ) STRICT",
+                [],
+            )?;
+            add_column_if_nonexistent(
+                conn,
+                "guild_settings",
+                "reaction_shortcuts_enabled",
+                "INTEGER NOT NULL DEFAULT 1",
+            )?;
+            // NULL means the default emoji
+            add_column_if_nonexistent(conn, "guild_settings", "bookmark_reaction_emoji", "TEXT")?;
+            add_column_if_nonexistent(conn, "guild_settings", "remind_reaction_emoji", "TEXT")?;
+            add_column_if_nonexistent(
+                conn,
+                "guild_settings",
+                "remind_tomorrow_reaction_emoji",
+                "TEXT",
+            )?;
+            Ok(())
+        })
+        .await
+}
+
+pub async fn get_guild_settings(
+    db_connection: &Mutex<Connection>,
+    guild_id: u64,
+) -> Result<Option<GuildSettings>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let settings = conn
+                .query_row(
+                    "SELECT * FROM guild_settings WHERE guild_id = ?1",
+                    [guild_id],
+                    guild_settings_from_row,
+                )
+                .optional()?;
+
+            Ok(settings)
+        })
+        .await
+}
+
+pub async fn upsert_guild_settings(
+    db_connection: &Mutex<Connection>,
+    settings: GuildSettings,
+) -> Result<GuildSettings, tokio_rusqlite::Error> {
+    let guild_id = settings.guild_id();
+    let privacy_mode = settings.privacy_mode();
+    let reaction_shortcuts_enabled = settings.reaction_shortcuts_enabled();
+    let emojis = settings.reaction_shortcut_emojis();
+    let bookmark_reaction_emoji = emojis.bookmark().to_string();
+    let remind_reaction_emoji = emojis.remind().to_string();
+    let remind_tomorrow_reaction_emoji = emojis.remind_tomorrow().to_string();
+
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            conn.execute(
+                "INSERT INTO guild_settings (
+                        guild_id,
+                        privacy_mode,
+                        reaction_shortcuts_enabled,
+                        bookmark_reaction_emoji,
+                        remind_reaction_emoji,
+                        remind_tomorrow_reaction_emoji
+                    )
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    ON CONFLICT(guild_id) DO UPDATE SET
+                        privacy_mode = excluded.privacy_mode,
+                        reaction_shortcuts_enabled = excluded.reaction_shortcuts_enabled,
+                        bookmark_reaction_emoji = excluded.bookmark_reaction_emoji,
+                        remind_reaction_emoji = excluded.remind_reaction_emoji,
+                        remind_tomorrow_reaction_emoji = excluded.remind_tomorrow_reaction_emoji",
+                tokio_rusqlite::params![
+                    guild_id,
+                    privacy_mode,
+                    reaction_shortcuts_enabled,
+                    bookmark_reaction_emoji,
+                    remind_reaction_emoji,
+                    remind_tomorrow_reaction_emoji
+                ],
+            )?;
+            Ok(())
+        })
+        .await?;
+
+    Ok(settings)
+}
+
+fn guild_settings_from_row(row: &Row<'_>) -> Result<GuildSettings, rusqlite::Error> {
+    GuildSettings::from_row(
+        row.get("guild_id")?,
+        row.get("privacy_mode")?,
+        row.get("reaction_shortcuts_enabled")?,
+        row.get("bookmark_reaction_emoji")?,
+        row.get("remind_reaction_emoji")?,
+        row.get("remind_tomorrow_reaction_emoji")?,
+    )
+    .map_err(|err| get_row_parse_error(row, err.column_name(), err))
+}
@@ -0,0 +1,493 @@
+//! Optional envelope encryption of the `message` column of the `bookmarks` and `reminders` tables.
+//!
+//! Each message is encrypted with its own randomly generated data key, and that data key is in turn encrypted
+//! ("wrapped") with a master key supplied by whoever runs the bot. Rotating the master key then only requires
+//! re-wrapping every row's data key rather than re-encrypting every message (see [`reencrypt_all_messages`]).
+//!
+//! Rows written before encryption was enabled stay readable, since anything without the
+//! [`ENCRYPTED_MESSAGE_PREFIX`] is treated as plaintext.
+
+use std::{cell::RefCell, collections::HashMap, fmt};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rusqlite::{Row, RowIndex};
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+/// Marks (and versions the format of) an encrypted `message` column:
+/// `enc:v1:<master key ID>:<base64 nonce + wrapped data key>:<base64 nonce + ciphertext>`
+pub const ENCRYPTED_MESSAGE_PREFIX: &str = "enc:v1:";
+/// Tables with a `message` column that gets encrypted
+const ENCRYPTED_TABLES: [&str; 2] = ["bookmarks", "reminders"];
+/// AES-GCM's standard nonce length in bytes
+const NONCE_LENGTH: usize = 12;
+
+thread_local! {
+    /// Set by [`attach_message_keyring`]. Every [`Connection`] runs its calls on a thread of its own,
+    /// so this is effectively the keyring of whichever connection's thread it's read on.
+    static MESSAGE_KEYRING: RefCell<Option<MessageKeyring>> = const { RefCell::new(None) };
+}
+
+/// The master keys messages may be encrypted with. New messages are always encrypted with the current (first) key,
+/// while the others are kept so that messages encrypted before a key rotation can still be read.
+pub struct MessageKeyring {
+    current_key_id: String,
+    keys: HashMap<String, Aes256Gcm>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseMessageKeyringError {
+    Empty,
+    /// Holds the offending entry, minus its key
+    InvalidEntry(String),
+    DuplicateKeyId(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MessageEncryptionError {
+    /// An encrypted message was found but no keys are configured
+    NoKeysConfigured,
+    /// The message was encrypted with a master key that isn't configured (anymore)
+    UnknownKeyId(String),
+    Malformed,
+    /// The message or its data key didn't decrypt, e.g. because it was tampered with
+    DecryptionFailed,
+}
+
+impl fmt::Display for MessageEncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoKeysConfigured => write!(
+                f,
+                "found an encrypted message, but no encryption keys are configured"
+            ),
+            Self::UnknownKeyId(key_id) => write!(
+                f,
+                "found a message encrypted with key `{}`, which isn't configured",
+                key_id
+            ),
+            Self::Malformed => write!(f, "found a malformed encrypted message"),
+            Self::DecryptionFailed => write!(f, "failed to decrypt a message"),
+        }
+    }
+}
+
+impl std::error::Error for MessageEncryptionError {}
+
+impl MessageKeyring {
+    /// Parses one `<key ID>:<base64 encoded 32 byte key>` entry per line (or comma-separated),
+    /// with the current key first. Blank lines and lines starting with `#` are ignored.
+    pub fn parse(input: &str) -> Result<Self, ParseMessageKeyringError> {
+        let mut current_key_id = None;
+        let mut keys = HashMap::new();
+
+        let entries = input
+            .split(['\n', ','])
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty() && !entry.starts_with('#'));
+        for entry in entries {
+            let invalid_entry = || {
+                ParseMessageKeyringError::InvalidEntry(
+                    entry.split(':').next().unwrap_or_default().to_string(),
+                )
+            };
+
+            let (key_id, encoded_key) = entry.split_once(':').ok_or_else(invalid_entry)?;
+            let key_id = key_id.trim();
+            if key_id.is_empty() {
+                return Err(invalid_entry());
+            }
+
+            let key = BASE64
+                .decode(encoded_key.trim())
+                .ok()
+                .filter(|key| key.len() == 32)
+                .ok_or_else(invalid_entry)?;
+
+            let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+            if keys.insert(key_id.to_string(), cipher).is_some() {
+                return Err(ParseMessageKeyringError::DuplicateKeyId(key_id.to_string()));
+            }
+            current_key_id.get_or_insert_with(|| key_id.to_string());
+        }
+
+        Ok(Self {
+            current_key_id: current_key_id.ok_or(ParseMessageKeyringError::Empty)?,
+            keys,
+        })
+    }
+
+    pub fn current_key_id(&self) -> &str {
+        &self.current_key_id
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let data_key_cipher = Aes256Gcm::new(&data_key);
+        let message_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = data_key_cipher
+            .encrypt(&message_nonce, plaintext.as_bytes())
+            .expect("Encrypting a message in memory shouldn't fail");
+
+        format!(
+            "{}{}:{}:{}",
+            ENCRYPTED_MESSAGE_PREFIX,
+            self.current_key_id,
+            self.wrap_data_key(&data_key),
+            BASE64.encode([message_nonce.as_slice(), &ciphertext].concat())
+        )
+    }
+
+    /// Messages that were stored before encryption was enabled are returned as-is.
+    pub fn decrypt(&self, stored: &str) -> Result<String, MessageEncryptionError> {
+        let Some(encrypted_message) = EncryptedMessage::parse(stored)? else {
+            return Ok(stored.to_string());
+        };
+
+        let data_key = self.unwrap_data_key(&encrypted_message)?;
+        let (nonce, ciphertext) = split_nonce(&encrypted_message.encrypted_message)?;
+        let plaintext = Aes256Gcm::new(&data_key)
+            .decrypt(nonce, ciphertext)
+            .map_err(|_| MessageEncryptionError::DecryptionFailed)?;
+
+        String::from_utf8(plaintext).map_err(|_| MessageEncryptionError::Malformed)
+    }
+
+    /// Brings a stored message up to date with the current key: plaintext messages are encrypted, and the data keys of
+    /// messages encrypted with an older key are re-wrapped (leaving the message's ciphertext as it was).
+    ///
+    /// Returns [`None`] if the message is already encrypted with the current key.
+    pub fn reencrypt(&self, stored: &str) -> Result<Option<String>, MessageEncryptionError> {
+        let Some(encrypted_message) = EncryptedMessage::parse(stored)? else {
+            return Ok(Some(self.encrypt(stored)));
+        };
+        if encrypted_message.key_id == self.current_key_id {
+            return Ok(None);
+        }
+
+        let data_key = self.unwrap_data_key(&encrypted_message)?;
+        Ok(Some(format!(
+            "{}{}:{}:{}",
+            ENCRYPTED_MESSAGE_PREFIX,
+            self.current_key_id,
+            self.wrap_data_key(&data_key),
+            BASE64.encode(&encrypted_message.encrypted_message)
+        )))
+    }
+
+    fn wrap_data_key(&self, data_key: &Key<Aes256Gcm>) -> String {
+        let wrapping_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped_data_key = self.keys[&self.current_key_id]
+            .encrypt(&wrapping_nonce, data_key.as_slice())
+            .expect("Wrapping a data key in memory shouldn't fail");
+        BASE64.encode([wrapping_nonce.as_slice(), &wrapped_data_key].concat())
+    }
+
+    fn unwrap_data_key(
+        &self,
+        encrypted_message: &EncryptedMessage,
+    ) -> Result<Key<Aes256Gcm>, MessageEncryptionError> {
+        let master_key = self.keys.get(&encrypted_message.key_id).ok_or_else(|| {
+            MessageEncryptionError::UnknownKeyId(encrypted_message.key_id.clone())
+        })?;
+        let (nonce, wrapped_data_key) = split_nonce(&encrypted_message.wrapped_data_key)?;
+        let data_key = master_key
+            .decrypt(nonce, wrapped_data_key)
+            .map_err(|_| MessageEncryptionError::DecryptionFailed)?;
+
+        if data_key.len() != 32 {
+            return Err(MessageEncryptionError::Malformed);
+        }
+        Ok(*Key::<Aes256Gcm>::from_slice(&data_key))
+    }
+}
+
+struct EncryptedMessage {
+    key_id: String,
+    /// Prefixed with its nonce
+    wrapped_data_key: Vec<u8>,
+    /// Prefixed with its nonce
+    encrypted_message: Vec<u8>,
+}
+
+impl EncryptedMessage {
+    /// Returns [`None`] for plaintext messages
+    fn parse(stored: &str) -> Result<Option<Self>, MessageEncryptionError> {
+        let Some(encrypted) = stored.strip_prefix(ENCRYPTED_MESSAGE_PREFIX) else {
+            return Ok(None);
+        };
+
+        let [key_id, wrapped_data_key, encrypted_message] = encrypted
+            .split(':')
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| MessageEncryptionError::Malformed)?;
+        let decode = |encoded: &str| {
+            BASE64
+                .decode(encoded)
+                .map_err(|_| MessageEncryptionError::Malformed)
+        };
+
+        Ok(Some(Self {
+            key_id: key_id.to_string(),
+            wrapped_data_key: decode(wrapped_data_key)?,
+            encrypted_message: decode(encrypted_message)?,
+        }))
+    }
+}
+
+fn split_nonce(
+    bytes: &[u8],
+) -> Result<(&Nonce<aes_gcm::aead::consts::U12>, &[u8]), MessageEncryptionError> {
+    if bytes.len() < NONCE_LENGTH {
+        return Err(MessageEncryptionError::Malformed);
+    }
+    let (nonce, rest) = bytes.split_at(NONCE_LENGTH);
+    Ok((Nonce::from_slice(nonce), rest))
+}
+
+/// Makes the connection encrypt the messages written through it and decrypt the ones read through it.
+/// Should be called right after opening the connection, and replaces any keyring attached before.
+pub async fn attach_message_keyring(
+    db_connection: &Mutex<Connection>,
+    keyring: MessageKeyring,
+) -> Result<(), tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |_| {
+            MESSAGE_KEYRING.set(Some(keyring));
+            Ok(())
+        })
+        .await
+}
+
+/// Encrypts a serialized message for storage if the connection has a [`MessageKeyring`], otherwise stores it as-is.
+/// Only works inside a [`Connection::call`], like everything else reading the connection's keyring.
+pub fn encrypt_message_column(serialized_message: String) -> String {
+    MESSAGE_KEYRING.with_borrow(|keyring| match keyring {
+        Some(keyring) => keyring.encrypt(&serialized_message),
+        None => serialized_message,
+    })
+}
+
+pub fn decrypt_message_column(stored: &str) -> Result<String, MessageEncryptionError> {
+    MESSAGE_KEYRING.with_borrow(|keyring| match keyring {
+        Some(keyring) => keyring.decrypt(stored),
+        None if stored.starts_with(ENCRYPTED_MESSAGE_PREFIX) => {
+            Err(MessageEncryptionError::NoKeysConfigured)
+        }
+        None => Ok(stored.to_string()),
+    })
+}
+
+/// Reads and decrypts a `message` column, so the rest of the code only ever sees serialized messages
+pub fn get_message_column(row: &Row<'_>, index: impl RowIndex) -> Result<String, rusqlite::Error> {
+    let column_index = index.idx(row.as_ref())?;
+    let stored = row.get_ref(column_index)?.as_str()?.to_string();
+    decrypt_message_column(&stored).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(
+            column_index,
+            rusqlite::types::Type::Text,
+            Box::new(err),
+        )
+    })
+}
+
+/// Maintenance task for after encryption is enabled or the master key is rotated: encrypts every plaintext message
+/// and re-wraps every data key with the current key, after which older keys can be removed.
+///
+/// Returns how many rows were updated.
+pub async fn reencrypt_all_messages(
+    db_connection: &Mutex<Connection>,
+) -> Result<usize, crate::Error> {
+    let updated_row_count = db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            MESSAGE_KEYRING.with_borrow(|keyring| {
+                keyring
+                    .as_ref()
+                    .map(|keyring| reencrypt_rows(conn, keyring))
+                    .transpose()
+            })
+        })
+        .await?;
+
+    updated_row_count.ok_or_else(|| MessageEncryptionError::NoKeysConfigured.into())
+}
+
+fn reencrypt_rows(
+    conn: &mut rusqlite::Connection,
+    keyring: &MessageKeyring,
+) -> Result<usize, tokio_rusqlite::Error> {
+    let transaction = conn.transaction()?;
+    let mut updated_row_count = 0;
+
+    for table_name in ENCRYPTED_TABLES {
+        let rows = transaction
+            .prepare(&format!("SELECT rowid, message FROM {}", table_name))?
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (rowid, stored) in rows {
+            let reencrypted = keyring.reencrypt(&stored).map_err(|err| {
+                tokio_rusqlite::Error::Other(
+                    format!("Row {} of {}: {}", rowid, table_name, err).into(),
+                )
+            })?;
+            if let Some(reencrypted) = reencrypted {
+                transaction.execute(
+                    &format!("UPDATE {} SET message = ?1 WHERE rowid = ?2", table_name),
+                    tokio_rusqlite::params![reencrypted, rowid],
+                )?;
+                updated_row_count += 1;
+            }
+        }
+    }
+
+    transaction.commit()?;
+    Ok(updated_row_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded_key(byte: u8) -> String {
+        BASE64.encode([byte; 32])
+    }
+
+    #[test]
+    fn test_parse_message_keyring() {
+        let keyring = MessageKeyring::parse(&format!(
+            "# rotated in January\n2:{}\n\n1:{}\n",
+            encoded_key(2),
+            encoded_key(1)
+        ))
+        .unwrap();
+        assert_eq!(keyring.current_key_id(), "2");
+        assert_eq!(keyring.keys.len(), 2);
+
+        let keyring =
+            MessageKeyring::parse(&format!("b:{}, a:{}", encoded_key(2), encoded_key(1))).unwrap();
+        assert_eq!(keyring.current_key_id(), "b");
+
+        assert_eq!(
+            MessageKeyring::parse("# nothing here").err(),
+            Some(ParseMessageKeyringError::Empty)
+        );
+        assert_eq!(
+            MessageKeyring::parse("1:dG9vIHNob3J0").err(),
+            Some(ParseMessageKeyringError::InvalidEntry("1".to_string()))
+        );
+        assert_eq!(
+            MessageKeyring::parse(&encoded_key(1)).err(),
+            Some(ParseMessageKeyringError::InvalidEntry(encoded_key(1)))
+        );
+        assert_eq!(
+            MessageKeyring::parse(&format!("1:{}\n1:{}", encoded_key(1), encoded_key(2))).err(),
+            Some(ParseMessageKeyringError::DuplicateKeyId("1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let keyring = MessageKeyring::parse(&format!("1:{}", encoded_key(1))).unwrap();
+        let message = r#"{"id":"1","content":"secret"}"#;
+
+        let encrypted = keyring.encrypt(message);
+
+        assert!(encrypted.starts_with("enc:v1:1:"));
+        assert!(!encrypted.contains("secret"));
+        // Every message gets its own data key and nonce
+        assert_ne!(encrypted, keyring.encrypt(message));
+        assert_eq!(keyring.decrypt(&encrypted).unwrap(), message);
+    }
+
+    #[test]
+    fn test_decrypt_plaintext_passes_through() {
+        let keyring = MessageKeyring::parse(&format!("1:{}", encoded_key(1))).unwrap();
+        let message = r#"{"id":"1","content":"not secret"}"#;
+
+        assert_eq!(keyring.decrypt(message).unwrap(), message);
+    }
+
+    #[test]
+    fn test_decrypt_with_wrong_or_missing_key_fails() {
+        let keyring = MessageKeyring::parse(&format!("1:{}", encoded_key(1))).unwrap();
+        let encrypted = keyring.encrypt("secret");
+
+        let other_keyring = MessageKeyring::parse(&format!("2:{}", encoded_key(2))).unwrap();
+        assert_eq!(
+            other_keyring.decrypt(&encrypted),
+            Err(MessageEncryptionError::UnknownKeyId("1".to_string()))
+        );
+
+        let impostor_keyring = MessageKeyring::parse(&format!("1:{}", encoded_key(3))).unwrap();
+        assert_eq!(
+            impostor_keyring.decrypt(&encrypted),
+            Err(MessageEncryptionError::DecryptionFailed)
+        );
+
+        assert_eq!(
+            keyring.decrypt("enc:v1:1:not base64"),
+            Err(MessageEncryptionError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_reencrypt_after_key_rotation() {
+        let old_keyring = MessageKeyring::parse(&format!("1:{}", encoded_key(1))).unwrap();
+        let rotated_keyring =
+            MessageKeyring::parse(&format!("2:{}\n1:{}", encoded_key(2), encoded_key(1))).unwrap();
+        let new_keyring = MessageKeyring::parse(&format!("2:{}", encoded_key(2))).unwrap();
+        let encrypted = old_keyring.encrypt("secret");
+
+        let reencrypted = rotated_keyring.reencrypt(&encrypted).unwrap().unwrap();
+
+        assert!(reencrypted.starts_with("enc:v1:2:"));
+        // Only the data key is re-wrapped, the message itself isn't re-encrypted
+        assert_eq!(reencrypted.rsplit(':').next(), encrypted.rsplit(':').next());
+        assert_eq!(new_keyring.decrypt(&reencrypted).unwrap(), "secret");
+        assert_eq!(rotated_keyring.reencrypt(&reencrypted), Ok(None));
+
+        let encrypted_plaintext = rotated_keyring.reencrypt("plaintext").unwrap().unwrap();
+        assert_eq!(
+            new_keyring.decrypt(&encrypted_plaintext).unwrap(),
+            "plaintext"
+        );
+    }
+
+    async fn encrypt_on(db_connection: &Mutex<Connection>, serialized_message: &str) -> String {
+        let serialized_message = serialized_message.to_string();
+        db_connection
+            .lock()
+            .await
+            .call(move |_| Ok(encrypt_message_column(serialized_message)))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_keyring_is_attached_to_one_connection() {
+        let encrypting_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+        let plain_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+        let keyring = MessageKeyring::parse(&format!("1:{}", encoded_key(1))).unwrap();
+
+        attach_message_keyring(&encrypting_connection, keyring)
+            .await
+            .unwrap();
+
+        assert!(encrypt_on(&encrypting_connection, "secret")
+            .await
+            .starts_with(ENCRYPTED_MESSAGE_PREFIX));
+        assert_eq!(encrypt_on(&plain_connection, "secret").await, "secret");
+    }
+}
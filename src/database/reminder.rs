@@ -1,7 +1,18 @@
+use rusqlite::{OptionalExtension, Row};
 use tokio::sync::Mutex;
 use tokio_rusqlite::Connection;
 
-use crate::models::reminder::{PersistedReminder, Reminder};
+use crate::{
+    database::get_row_parse_error,
+    database::{
+        add_column_if_nonexistent,
+        message_encryption::{encrypt_message_column, get_message_column},
+    },
+    models::{
+        bookmark_review::ReviewStep,
+        reminder::{PersistedReminder, Reminder},
+    },
+};
 
 pub async fn create_reminders_table_if_nonexistent(
     db_connection: &Mutex<Connection>,
@@ -19,6 +30,16 @@ pub async fn create_reminders_table_if_nonexistent(
                     ) STRICT",
                 [],
             )?;
+            add_column_if_nonexistent(conn, "reminders", "urgent", "INTEGER NOT NULL DEFAULT 0")?;
+            add_column_if_nonexistent(conn, "reminders", "countdown_id", "TEXT")?;
+            add_column_if_nonexistent(conn, "reminders", "deadline", "TEXT")?;
+            add_column_if_nonexistent(
+                conn,
+                "reminders",
+                "content_omitted",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            add_column_if_nonexistent(conn, "reminders", "review_bookmark_id", "TEXT")?;
             Ok(())
         })
         .await
@@ -34,15 +55,7 @@ pub async fn get_all_reminders(
             let mut stmt = conn.prepare("SELECT * FROM reminders")?;
             let reminders_from_database = stmt
                 .query(())?
-                .mapped(|row| {
-                    Ok(PersistedReminder::from_row(
-                        row.get(0)?,
-                        row.get(1)?,
-                        row.get(2)?,
-                        row.get(3)?,
-                    )
-                    .expect("Failed to parse reminder from row"))
-                })
+                .mapped(reminder_from_row)
                 .filter_map(Result::ok)
                 .collect::<Vec<_>>();
 
@@ -51,25 +64,19 @@ pub async fn get_all_reminders(
         .await
 }
 
+/// Returns how many reminders were deleted, which is 0 if the reminder was already deleted (e.g. by `/forget_me`)
 pub async fn delete_reminder_by_id(
     db_connection: &Mutex<Connection>,
     reminder_id: i64,
-) -> Result<(), tokio_rusqlite::Error> {
-    let rows_changed = db_connection
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
         .lock()
         .await
         .call(move |conn| Ok(conn.execute("DELETE FROM reminders WHERE id = ?", [reminder_id])?))
-        .await?;
-
-    assert!(
-        rows_changed == 1,
-        "Expected exactly one row to be deleted but instead {} were deleted.",
-        rows_changed
-    );
-
-    Ok(())
+        .await
 }
 
+/// Leaves out spaced repetition reviews, which resurface bookmarks rather than being reminders the user set
 pub async fn get_reminders_for_user(
     db_connection: &Mutex<Connection>,
     user_id: u64,
@@ -81,17 +88,9 @@ pub async fn get_reminders_for_user(
         .call(move |conn| {
             let reminders = conn
                 .prepare(
-                    "SELECT * FROM reminders WHERE user_id = ?1 ORDER BY remind_at DESC LIMIT ?2",
+                    "SELECT * FROM reminders WHERE user_id = ?1 AND review_bookmark_id IS NULL ORDER BY remind_at DESC LIMIT ?2",
                 )?
-                .query_map([user_id, max_quantity_to_retrieve], |row| {
-                    Ok(PersistedReminder::from_row(
-                        row.get(0)?,
-                        row.get(1)?,
-                        row.get(2)?,
-                        row.get(3)?,
-                    )
-                    .expect("Failed to parse reminder from row"))
-                })?
+                .query_map([user_id, max_quantity_to_retrieve], reminder_from_row)?
                 .collect::<Result<Vec<_>, _>>()?;
 
             Ok(reminders)
@@ -103,23 +102,226 @@ pub async fn insert_reminder(
     db_connection: &Mutex<Connection>,
     reminder: Reminder,
 ) -> Result<PersistedReminder, tokio_rusqlite::Error> {
-    let user_id = reminder.user_id();
-    let stringified_message =
-        serde_json::to_string(&reminder.message()).expect("Failed to serialize message");
-    let remind_at = *reminder.remind_at();
+    let (reminder, pk) = db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let pk = insert_reminder_row(conn, &reminder)?;
+            Ok((reminder, pk))
+        })
+        .await?;
+
+    Ok(PersistedReminder::from_reminder(reminder, pk))
+}
+
+/// Records each review's step on its bookmark and replaces the bookmark's pending review (if any) with it.
+/// It all happens in one transaction, so rescheduling the same bookmark twice at once can't leave it with two reviews queued.
+///
+/// Each reminder should be a review, i.e. have [`Reminder::review_bookmark_id()`] set.
+pub async fn replace_bookmark_reviews(
+    db_connection: &Mutex<Connection>,
+    reviews: Vec<(ReviewStep, Reminder)>,
+) -> Result<Vec<PersistedReminder>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let transaction = conn.transaction()?;
+            let mut persisted_reviews = Vec::with_capacity(reviews.len());
+            for (review_step, review) in reviews {
+                let bookmark_id = review
+                    .review_bookmark_id()
+                    .expect("Only reviews should be replaced")
+                    .to_string();
+                // Can't truncate, the schedule only has a few steps
+                let review_step = review_step.index() as u32;
+
+                transaction.execute(
+                    "UPDATE bookmarks SET review_step = ?1 WHERE bookmark_id = ?2",
+                    tokio_rusqlite::params![review_step, bookmark_id],
+                )?;
+                transaction.execute(
+                    "DELETE FROM reminders WHERE review_bookmark_id = ?1",
+                    [&bookmark_id],
+                )?;
+                let pk = insert_reminder_row(&transaction, &review)?;
+                persisted_reviews.push(PersistedReminder::from_reminder(review, pk));
+            }
+            transaction.commit()?;
 
-    let pk = db_connection
+            Ok(persisted_reviews)
+        })
+        .await
+}
+
+fn insert_reminder_row(
+    conn: &rusqlite::Connection,
+    reminder: &Reminder,
+) -> Result<i64, rusqlite::Error> {
+    let stringified_message = encrypt_message_column(
+        serde_json::to_string(&reminder.message()).expect("Failed to serialize message"),
+    );
+    let countdown_id = reminder
+        .countdown()
+        .map(|countdown| countdown.countdown_id().to_string());
+    let deadline = reminder
+        .countdown()
+        .map(|countdown| countdown.deadline().to_rfc3339());
+    let review_bookmark_id = reminder
+        .review_bookmark_id()
+        .map(|bookmark_id| bookmark_id.to_string());
+
+    conn.execute(
+        "INSERT INTO reminders (user_id, message, remind_at, countdown_id, deadline, content_omitted, review_bookmark_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        tokio_rusqlite::params![
+            reminder.user_id(),
+            stringified_message,
+            reminder.remind_at().to_rfc3339(),
+            countdown_id,
+            deadline,
+            reminder.is_content_omitted(),
+            review_bookmark_id
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+pub async fn get_reminder_by_id(
+    db_connection: &Mutex<Connection>,
+    reminder_id: i64,
+) -> Result<Option<PersistedReminder>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let reminder = conn
+                .query_row(
+                    "SELECT * FROM reminders WHERE id = ?1",
+                    [reminder_id],
+                    reminder_from_row,
+                )
+                .optional()?;
+
+            Ok(reminder)
+        })
+        .await
+}
+
+pub async fn update_reminder_remind_at(
+    db_connection: &Mutex<Connection>,
+    reminder_id: i64,
+    remind_at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), tokio_rusqlite::Error> {
+    db_connection
         .lock()
         .await
         .call(move |conn| {
             conn.execute(
-                "INSERT INTO reminders (user_id, message, remind_at) VALUES (?1, ?2, ?3)",
-                tokio_rusqlite::params![user_id, stringified_message, remind_at.to_rfc3339(),],
+                "UPDATE reminders SET remind_at = ?1 WHERE id = ?2",
+                tokio_rusqlite::params![remind_at.to_rfc3339(), reminder_id],
             )?;
+            Ok(())
+        })
+        .await
+}
 
-            Ok(conn.last_insert_rowid())
+/// Returns whether a reminder belonging to `user_id` was found and updated.
+pub async fn mark_reminder_as_urgent(
+    db_connection: &Mutex<Connection>,
+    reminder_id: i64,
+    user_id: u64,
+) -> Result<bool, tokio_rusqlite::Error> {
+    let rows_changed = db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            Ok(conn.execute(
+                "UPDATE reminders SET urgent = 1 WHERE id = ?1 AND user_id = ?2",
+                tokio_rusqlite::params![reminder_id, user_id],
+            )?)
         })
         .await?;
 
-    Ok(PersistedReminder::from_reminder(reminder, pk))
+    Ok(rows_changed == 1)
+}
+
+/// Includes urgent reminders - filter them out if that isn't desired.
+pub async fn get_reminders_due_before_for_user(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+    due_before: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<PersistedReminder>, tokio_rusqlite::Error> {
+    let reminders = db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let reminders = conn
+                .prepare("SELECT * FROM reminders WHERE user_id = ?1")?
+                .query_map([user_id], reminder_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(reminders)
+        })
+        .await?;
+
+    // Filtering here rather than in SQL because comparing the stored RFC 3339 strings
+    // lexicographically breaks down when their fractional seconds differ in length
+    let mut reminders = reminders
+        .into_iter()
+        .filter(|reminder| *reminder.remind_at() < due_before)
+        .collect::<Vec<_>>();
+    reminders.sort();
+
+    Ok(reminders)
+}
+
+/// Deletes all of a countdown's remaining alerts belonging to `user_id`, returning how many were deleted.
+pub async fn delete_countdown(
+    db_connection: &Mutex<Connection>,
+    countdown_id: uuid::Uuid,
+    user_id: u64,
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            Ok(conn.execute(
+                "DELETE FROM reminders WHERE countdown_id = ?1 AND user_id = ?2",
+                tokio_rusqlite::params![countdown_id.to_string(), user_id],
+            )?)
+        })
+        .await
+}
+
+/// Deletes all of the user's pending spaced repetition reviews, returning how many were deleted
+pub async fn delete_bookmark_reviews_for_user(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            Ok(conn.execute(
+                "DELETE FROM reminders WHERE user_id = ?1 AND review_bookmark_id IS NOT NULL",
+                [user_id],
+            )?)
+        })
+        .await
+}
+
+fn reminder_from_row(row: &Row<'_>) -> Result<PersistedReminder, rusqlite::Error> {
+    PersistedReminder::from_row(
+        row.get("id")?,
+        row.get("user_id")?,
+        get_message_column(row, "message")?,
+        row.get("remind_at")?,
+        row.get("urgent")?,
+        row.get("countdown_id")?,
+        row.get("deadline")?,
+        row.get("content_omitted")?,
+        row.get("review_bookmark_id")?,
+    )
+    .map_err(|err| get_row_parse_error(row, err.column_name(), err))
 }
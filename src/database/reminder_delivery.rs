@@ -0,0 +1,154 @@
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+pub async fn create_reminder_deliveries_table_if_nonexistent(
+    db_connection: &Mutex<Connection>,
+) -> Result<(), tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS reminder_deliveries (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        reminder_id INTEGER NOT NULL,
+                        user_id TEXT NOT NULL,
+                        remind_at TEXT NOT NULL,
+                        delivered_at TEXT NOT NULL
+                    ) STRICT",
+                [],
+            )?;
+            Ok(())
+        })
+        .await
+}
+
+/// Removes a delivered reminder from the queue and records when it was delivered (compared to when it was due),
+/// both in one transaction so a reminder is never both delivered and still queued.
+///
+/// Returns whether the reminder still existed.
+pub async fn mark_reminder_delivered(
+    db_connection: &Mutex<Connection>,
+    reminder_id: i64,
+    delivered_at: chrono::DateTime<chrono::Utc>,
+) -> Result<bool, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let transaction = conn.transaction()?;
+            transaction.execute(
+                "INSERT INTO reminder_deliveries (reminder_id, user_id, remind_at, delivered_at)
+                    SELECT id, user_id, remind_at, ?2 FROM reminders WHERE id = ?1",
+                tokio_rusqlite::params![reminder_id, delivered_at.to_rfc3339()],
+            )?;
+            let rows_changed =
+                transaction.execute("DELETE FROM reminders WHERE id = ?1", [reminder_id])?;
+            transaction.commit()?;
+
+            Ok(rows_changed == 1)
+        })
+        .await
+}
+
+/// Delivery times are compared with `julianday` rather than as strings, since the stored RFC 3339 strings don't sort
+/// correctly when their fractional seconds differ in length. A delivery time that doesn't parse is never matched,
+/// since it can't be known to be old enough.
+const DELIVERED_BEFORE_CONDITION: &str = "julianday(delivered_at) < julianday(?1)";
+
+/// Used by the retention policy to report what it would prune in a dry run
+pub async fn count_reminder_deliveries_delivered_before(
+    db_connection: &Mutex<Connection>,
+    delivered_before: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let count = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM reminder_deliveries WHERE {}",
+                    DELIVERED_BEFORE_CONDITION
+                ),
+                [delivered_before.to_rfc3339()],
+                |row| row.get(0),
+            )?;
+
+            Ok(count)
+        })
+        .await
+}
+
+/// Used by the retention policy to prune old delivery history. Returns how many deliveries were deleted.
+pub async fn delete_reminder_deliveries_delivered_before(
+    db_connection: &Mutex<Connection>,
+    delivered_before: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let deleted_count = conn.execute(
+                &format!(
+                    "DELETE FROM reminder_deliveries WHERE {}",
+                    DELIVERED_BEFORE_CONDITION
+                ),
+                [delivered_before.to_rfc3339()],
+            )?;
+
+            Ok(deleted_count)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_delete_reminder_deliveries_delivered_before() {
+        let db_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+        create_reminder_deliveries_table_if_nonexistent(&db_connection)
+            .await
+            .unwrap();
+        db_connection
+            .lock()
+            .await
+            .call(|conn| {
+                for delivered_at in [
+                    "2024-01-01T00:00:00.5+00:00",
+                    "2024-01-01T00:00:01+00:00",
+                    "2024-01-01T01:00:00+01:00",
+                    "not a time",
+                ] {
+                    conn.execute(
+                        "INSERT INTO reminder_deliveries (reminder_id, user_id, remind_at, delivered_at) VALUES (1, '1', ?1, ?1)",
+                        [delivered_at],
+                    )?;
+                }
+                Ok(())
+            })
+            .await
+            .unwrap();
+        let delivered_before = "2024-01-01T00:00:00.75Z".parse().unwrap();
+
+        assert_eq!(
+            count_reminder_deliveries_delivered_before(&db_connection, delivered_before)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            delete_reminder_deliveries_delivered_before(&db_connection, delivered_before)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            count_reminder_deliveries_delivered_before(&db_connection, delivered_before)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+}
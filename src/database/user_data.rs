@@ -0,0 +1,284 @@
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+use crate::database::message_encryption::get_message_column;
+
+/// How many rows of each kind were removed by [`delete_all_user_data`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeletedUserData {
+    pub bookmark_count: usize,
+    pub reminder_count: usize,
+    pub reminder_delivery_count: usize,
+    pub settings_count: usize,
+}
+
+/// Deletes everything stored about `user_id` in one transaction, so either all of it is gone or none of it is.
+///
+/// This only removes the rows - reminders already queued in the scheduler have to be cancelled separately.
+pub async fn delete_all_user_data(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+) -> Result<DeletedUserData, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let transaction = conn.transaction()?;
+            let deleted_user_data = DeletedUserData {
+                bookmark_count: transaction
+                    .execute("DELETE FROM bookmarks WHERE user_id = ?1", [user_id])?,
+                reminder_count: transaction
+                    .execute("DELETE FROM reminders WHERE user_id = ?1", [user_id])?,
+                reminder_delivery_count: transaction.execute(
+                    "DELETE FROM reminder_deliveries WHERE user_id = ?1",
+                    [user_id],
+                )?,
+                settings_count: transaction
+                    .execute("DELETE FROM user_settings WHERE user_id = ?1", [user_id])?,
+            };
+            transaction.commit()?;
+
+            Ok(deleted_user_data)
+        })
+        .await
+}
+
+/// Every table that holds rows tied to a `user_id`, all of which are included in [`get_user_data_archive`]
+pub const USER_DATA_TABLES: [&str; 4] = [
+    "bookmarks",
+    "reminders",
+    "reminder_deliveries",
+    "user_settings",
+];
+/// Tables without a `user_id`, which are deliberately left out of [`get_user_data_archive`]
+/// and [`delete_all_user_data`]
+pub const NON_USER_DATA_TABLES: [&str; 1] = ["guild_settings"];
+/// Bumped whenever the archive's layout changes in a way that could break something reading it.
+/// Columns being added to a table doesn't count, since each row is exported as an object keyed by column name.
+pub const USER_DATA_ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// Builds a machine-readable archive of every row tied to `user_id`:
+///
+/// ```json
+/// {
+///     "schema_version": 1,
+///     "user_id": "123456789",
+///     "exported_at": "2025-01-01T00:00:00+00:00",
+///     "tables": {
+///         "bookmarks": [{ "<column name>": <column value>, ... }, ...],
+///         ...
+///     }
+/// }
+/// ```
+///
+/// Every table in [`USER_DATA_TABLES`] is always present, even when the user has no rows in it.
+/// Column values are exported as Sqlite stores them (apart from messages being decrypted),
+/// so e.g. messages are JSON strings and timestamps are RFC 3339 strings.
+pub async fn get_user_data_archive(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+    exported_at: chrono::DateTime<chrono::Utc>,
+) -> Result<serde_json::Value, tokio_rusqlite::Error> {
+    let tables = db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let mut tables = serde_json::Map::new();
+
+            for table_name in USER_DATA_TABLES {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT * FROM {} WHERE user_id = ?1 ORDER BY rowid",
+                    table_name
+                ))?;
+                let column_names = stmt
+                    .column_names()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+
+                let rows = stmt
+                    .query_map([user_id], |row| {
+                        column_names
+                            .iter()
+                            .enumerate()
+                            .map(|(index, column_name)| {
+                                let value = if column_name == "message" {
+                                    get_message_column(row, index)?.into()
+                                } else {
+                                    sql_value_to_json(row.get_ref(index)?)
+                                };
+                                Ok((column_name.clone(), value))
+                            })
+                            .collect::<Result<serde_json::Map<_, _>, rusqlite::Error>>()
+                            .map(serde_json::Value::Object)
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                tables.insert(table_name.to_string(), serde_json::Value::Array(rows));
+            }
+
+            Ok(tables)
+        })
+        .await?;
+
+    Ok(serde_json::json!({
+        "schema_version": USER_DATA_ARCHIVE_SCHEMA_VERSION,
+        "user_id": user_id.to_string(),
+        "exported_at": exported_at.to_rfc3339(),
+        "tables": tables,
+    }))
+}
+
+fn sql_value_to_json(value: rusqlite::types::ValueRef<'_>) -> serde_json::Value {
+    match value {
+        rusqlite::types::ValueRef::Null => serde_json::Value::Null,
+        rusqlite::types::ValueRef::Integer(integer) => integer.into(),
+        rusqlite::types::ValueRef::Real(real) => real.into(),
+        rusqlite::types::ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+        rusqlite::types::ValueRef::Blob(blob) => blob.to_vec().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude as serenity;
+
+    use super::*;
+    use crate::{
+        database::{
+            bookmark::insert_bookmark, reminder::insert_reminder,
+            reminder_delivery::mark_reminder_delivered, user_settings::upsert_user_settings,
+        },
+        models::{bookmark::BookmarkedMessage, reminder::Reminder, user_settings::UserSettings},
+        test_fixtures::get_test_db_connection,
+    };
+
+    /// Gives `user_id` at least one row in every table
+    async fn insert_user_data(db_connection: &Mutex<Connection>, user_id: u64) {
+        let bookmark_id = uuid::Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+        insert_bookmark(
+            db_connection,
+            BookmarkedMessage::new(bookmark_id, user_id, serenity::Message::default()),
+        )
+        .await
+        .unwrap();
+
+        let remind_at = chrono::Utc::now();
+        insert_reminder(
+            db_connection,
+            Reminder::new(user_id, serenity::Message::default(), remind_at),
+        )
+        .await
+        .unwrap();
+        let delivered_reminder = insert_reminder(
+            db_connection,
+            Reminder::new(user_id, serenity::Message::default(), remind_at),
+        )
+        .await
+        .unwrap();
+        mark_reminder_delivered(db_connection, delivered_reminder.pk(), remind_at)
+            .await
+            .unwrap();
+
+        upsert_user_settings(db_connection, UserSettings::new(user_id))
+            .await
+            .unwrap();
+    }
+
+    /// If this fails, a table was added without being added to [`USER_DATA_TABLES`] (or, if it
+    /// really holds nothing about any user, [`NON_USER_DATA_TABLES`]).
+    /// Make sure user data tables are also covered by [`delete_all_user_data`] and [`insert_user_data`].
+    #[tokio::test]
+    async fn test_user_data_tables_include_every_table() {
+        let db_connection = get_test_db_connection().await;
+
+        let mut table_names = db_connection
+            .lock()
+            .await
+            .call(|conn| {
+                let table_names = conn
+                    .prepare(
+                        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+                    )?
+                    .query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(table_names)
+            })
+            .await
+            .unwrap();
+        table_names.sort();
+
+        let mut known_tables = USER_DATA_TABLES
+            .into_iter()
+            .chain(NON_USER_DATA_TABLES)
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        known_tables.sort();
+
+        assert_eq!(table_names, known_tables);
+    }
+
+    #[tokio::test]
+    async fn test_get_user_data_archive() {
+        let db_connection = get_test_db_connection().await;
+        let user_id = 123456789;
+        insert_user_data(&db_connection, user_id).await;
+        insert_user_data(&db_connection, 987654321).await;
+        let exported_at = chrono::Utc::now();
+
+        let archive = get_user_data_archive(&db_connection, user_id, exported_at)
+            .await
+            .unwrap();
+
+        assert_eq!(archive["schema_version"], USER_DATA_ARCHIVE_SCHEMA_VERSION);
+        assert_eq!(archive["user_id"], "123456789");
+        assert_eq!(archive["exported_at"], exported_at.to_rfc3339());
+        for table_name in USER_DATA_TABLES {
+            let rows = archive["tables"][table_name].as_array().unwrap();
+            assert!(!rows.is_empty(), "No rows exported from {}", table_name);
+            assert!(
+                rows.iter().all(|row| row["user_id"] == "123456789"),
+                "Another user's rows were exported from {}",
+                table_name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_all_user_data() {
+        let db_connection = get_test_db_connection().await;
+        let user_id = 123456789;
+        insert_user_data(&db_connection, user_id).await;
+        insert_user_data(&db_connection, 987654321).await;
+
+        let deleted_user_data = delete_all_user_data(&db_connection, user_id).await.unwrap();
+
+        assert_eq!(
+            deleted_user_data,
+            DeletedUserData {
+                bookmark_count: 1,
+                reminder_count: 1,
+                reminder_delivery_count: 1,
+                settings_count: 1,
+            }
+        );
+        let archive = get_user_data_archive(&db_connection, user_id, chrono::Utc::now())
+            .await
+            .unwrap();
+        for table_name in USER_DATA_TABLES {
+            assert_eq!(
+                archive["tables"][table_name],
+                serde_json::json!([]),
+                "Rows left behind in {}",
+                table_name
+            );
+        }
+        let other_archive = get_user_data_archive(&db_connection, 987654321, chrono::Utc::now())
+            .await
+            .unwrap();
+        assert!(!other_archive["tables"]["bookmarks"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use rusqlite::{OptionalExtension, Row};
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+use crate::{
+    database::add_column_if_nonexistent,
+    database::get_row_parse_error,
+    models::user_settings::{UserSettings, DATE_FORMAT, TIME_OF_DAY_FORMAT},
+};
+
+pub async fn create_user_settings_table_if_nonexistent(
+    db_connection: &Mutex<Connection>,
+) -> Result<(), tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS user_settings (
+                        user_id TEXT PRIMARY KEY NOT NULL,
+                        timezone TEXT NOT NULL,
+                        quiet_hours_start TEXT,
+                        quiet_hours_end TEXT
+                    ) STRICT",
+                [],
+            )?;
+            add_column_if_nonexistent(conn, "user_settings", "digest_time", "TEXT")?;
+            add_column_if_nonexistent(conn, "user_settings", "last_digest_sent_on", "TEXT")?;
+            add_column_if_nonexistent(
+                conn,
+                "user_settings",
+                "privacy_mode",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            add_column_if_nonexistent(conn, "user_settings", "bookmark_retention_days", "INTEGER")?;
+            add_column_if_nonexistent(
+                conn,
+                "user_settings",
+                "message_content_retention_days",
+                "INTEGER",
+            )?;
+            add_column_if_nonexistent(
+                conn,
+                "user_settings",
+                "spaced_repetition",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            Ok(())
+        })
+        .await
+}
+
+pub async fn get_user_settings(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+) -> Result<Option<UserSettings>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            let settings = conn
+                .query_row(
+                    "SELECT * FROM user_settings WHERE user_id = ?1",
+                    [user_id],
+                    user_settings_from_row,
+                )
+                .optional()?;
+
+            Ok(settings)
+        })
+        .await
+}
+
+pub async fn upsert_user_settings(
+    db_connection: &Mutex<Connection>,
+    settings: UserSettings,
+) -> Result<UserSettings, tokio_rusqlite::Error> {
+    let user_id = settings.user_id();
+    let timezone = settings.timezone().name().to_string();
+    let quiet_hours_start = settings
+        .quiet_hours()
+        .map(|quiet_hours| quiet_hours.start().format(TIME_OF_DAY_FORMAT).to_string());
+    let quiet_hours_end = settings
+        .quiet_hours()
+        .map(|quiet_hours| quiet_hours.end().format(TIME_OF_DAY_FORMAT).to_string());
+    let digest_time = settings
+        .digest_time()
+        .map(|digest_time| digest_time.format(TIME_OF_DAY_FORMAT).to_string());
+    let privacy_mode = settings.privacy_mode();
+    let bookmark_retention_days = settings
+        .bookmark_retention()
+        .map(|retention| retention.to_days());
+    let message_content_retention_days = settings
+        .message_content_retention()
+        .map(|retention| retention.to_days());
+    let spaced_repetition = settings.spaced_repetition();
+
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            conn.execute(
+                "INSERT INTO user_settings (user_id, timezone, quiet_hours_start, quiet_hours_end, digest_time, privacy_mode, bookmark_retention_days, message_content_retention_days, spaced_repetition)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                    ON CONFLICT(user_id) DO UPDATE SET
+                        timezone = excluded.timezone,
+                        quiet_hours_start = excluded.quiet_hours_start,
+                        quiet_hours_end = excluded.quiet_hours_end,
+                        digest_time = excluded.digest_time,
+                        privacy_mode = excluded.privacy_mode,
+                        bookmark_retention_days = excluded.bookmark_retention_days,
+                        message_content_retention_days = excluded.message_content_retention_days,
+                        spaced_repetition = excluded.spaced_repetition",
+                tokio_rusqlite::params![
+                    user_id,
+                    timezone,
+                    quiet_hours_start,
+                    quiet_hours_end,
+                    digest_time,
+                    privacy_mode,
+                    bookmark_retention_days,
+                    message_content_retention_days,
+                    spaced_repetition
+                ],
+            )?;
+            Ok(())
+        })
+        .await?;
+
+    Ok(settings)
+}
+
+pub async fn get_all_user_settings_with_digest_enabled(
+    db_connection: &Mutex<Connection>,
+) -> Result<Vec<UserSettings>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            let settings = conn
+                .prepare("SELECT * FROM user_settings WHERE digest_time IS NOT NULL")?
+                .query_map([], user_settings_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(settings)
+        })
+        .await
+}
+
+/// Used by the retention policy to find each user's overrides
+pub async fn get_all_user_settings(
+    db_connection: &Mutex<Connection>,
+) -> Result<Vec<UserSettings>, tokio_rusqlite::Error> {
+    db_connection
+        .lock()
+        .await
+        .call(|conn| {
+            let settings = conn
+                .prepare("SELECT * FROM user_settings")?
+                .query_map([], user_settings_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(settings)
+        })
+        .await
+}
+
+/// Kept separate from [`upsert_user_settings`] so that recording a sent digest can't clobber
+/// settings the user changed in the meantime
+pub async fn update_last_digest_sent_on(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+    last_digest_sent_on: chrono::NaiveDate,
+) -> Result<(), tokio_rusqlite::Error> {
+    let last_digest_sent_on = last_digest_sent_on.format(DATE_FORMAT).to_string();
+
+    db_connection
+        .lock()
+        .await
+        .call(move |conn| {
+            conn.execute(
+                "UPDATE user_settings SET last_digest_sent_on = ?1 WHERE user_id = ?2",
+                tokio_rusqlite::params![last_digest_sent_on, user_id],
+            )?;
+            Ok(())
+        })
+        .await
+}
+
+fn user_settings_from_row(row: &Row<'_>) -> Result<UserSettings, rusqlite::Error> {
+    UserSettings::from_row(
+        row.get("user_id")?,
+        row.get("timezone")?,
+        row.get("quiet_hours_start")?,
+        row.get("quiet_hours_end")?,
+        row.get("digest_time")?,
+        row.get("last_digest_sent_on")?,
+        row.get("privacy_mode")?,
+        row.get("bookmark_retention_days")?,
+        row.get("message_content_retention_days")?,
+        row.get("spaced_repetition")?,
+    )
+    .map_err(|err| get_row_parse_error(row, err.column_name(), err))
+}
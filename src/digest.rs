@@ -0,0 +1,229 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use poise::serenity_prelude::{self as serenity, CreateEmbed};
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    components::{
+        digest::digest_message::{
+            get_digest_message, get_digest_page_components, get_digest_pages,
+            get_expired_digest_components,
+        },
+        interaction_custom_id::InteractionCustomId,
+        DELETE_MESSAGE_EMOJI,
+    },
+    database::{
+        bookmark::get_random_unread_bookmarks_for_user,
+        reminder::{delete_reminder_by_id, get_reminders_due_before_for_user},
+        user_settings::{get_all_user_settings_with_digest_enabled, update_last_digest_sent_on},
+    },
+    models::user_settings::UserSettings,
+    privacy::{with_live_bookmark_message, with_live_reminder_message},
+    Error,
+};
+
+/// How often to check whether anybody's digest is due
+const DIGEST_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// How many unread bookmarks to resurface in each digest
+const DIGEST_BOOKMARK_COUNT: u64 = 3;
+/// How long the page buttons on a digest keep working
+const DIGEST_PAGINATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60 * 60 * 24);
+
+/// The digests whose page buttons are currently being listened for, shared between [`send_digests`] and the event handler.
+/// Lost on restart, so presses on any other digest are answered as expired.
+#[derive(Clone, Default)]
+pub struct PaginatedDigests(Arc<std::sync::Mutex<BTreeSet<uuid::Uuid>>>);
+
+impl PaginatedDigests {
+    /// Whether presses on the digest's page buttons are handled by its paginator
+    pub fn contains(&self, digest_id: uuid::Uuid) -> bool {
+        self.lock().contains(&digest_id)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeSet<uuid::Uuid>> {
+        self.0
+            .lock()
+            .expect("Digest registry lock shouldn't be poisoned")
+    }
+}
+
+/// Periodically sends each user who has enabled digest mode their digest once their configured local time has passed.
+///
+/// Takes a whole [`serenity::Context`] rather than just a [`serenity::Http`] because paginating
+/// the digest requires listening for button presses. `paginated_digests` should be the same as the [`crate::Data`]'s.
+///
+/// Returns once `shutdown` is cancelled, after finishing the digest it's in the middle of sending.
+pub async fn send_digests(
+    ctx: serenity::Context,
+    db_connection: Arc<Mutex<Connection>>,
+    paginated_digests: PaginatedDigests,
+    shutdown: CancellationToken,
+) -> Result<(), Error> {
+    let mut interval = tokio::time::interval(DIGEST_CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = interval.tick() => {}
+        }
+
+        let now = chrono::Utc::now();
+        for settings in get_all_user_settings_with_digest_enabled(&db_connection).await? {
+            if !settings.is_digest_due(&now) {
+                continue;
+            }
+
+            if let Err(err) =
+                send_digest(&ctx, &db_connection, &paginated_digests, &settings, &now).await
+            {
+                tracing::error!(user_id = settings.user_id(), error = %err, "Failed to send digest");
+            }
+        }
+    }
+}
+
+async fn send_digest(
+    ctx: &serenity::Context,
+    db_connection: &Mutex<Connection>,
+    paginated_digests: &PaginatedDigests,
+    settings: &UserSettings,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> Result<(), Error> {
+    let user_id = settings.user_id();
+    let local_date = settings.get_local_date(now);
+
+    // Only reminders that are already due are included, the rest of today's are delivered on time by the scheduler.
+    // Urgent reminders, countdown alerts and bookmark reviews are delivered on their own, so they're left to the scheduler too
+    let reminders = get_reminders_due_before_for_user(db_connection, user_id, *now)
+        .await?
+        .into_iter()
+        .filter(|reminder| {
+            !reminder.is_urgent()
+                && reminder.countdown().is_none()
+                && reminder.review_bookmark_id().is_none()
+        })
+        .collect::<Vec<_>>();
+    let bookmarks =
+        get_random_unread_bookmarks_for_user(db_connection, user_id, DIGEST_BOOKMARK_COUNT).await?;
+
+    if !reminders.is_empty() || !bookmarks.is_empty() {
+        let mut reminders_with_channel_names = Vec::with_capacity(reminders.len());
+        for reminder in reminders {
+            let reminder = with_live_reminder_message(ctx, reminder).await;
+            let channel_name = get_channel_name(ctx, reminder.message().channel_id).await;
+            reminders_with_channel_names.push((reminder, channel_name));
+        }
+
+        let mut bookmarks_with_channel_names = Vec::with_capacity(bookmarks.len());
+        for bookmark in bookmarks {
+            let bookmark = with_live_bookmark_message(ctx, bookmark).await;
+            let channel_name = get_channel_name(ctx, bookmark.message().channel_id).await;
+            bookmarks_with_channel_names.push((bookmark, channel_name));
+        }
+
+        let pages = get_digest_pages(
+            local_date,
+            &reminders_with_channel_names,
+            &bookmarks_with_channel_names,
+        );
+        let digest_id = uuid::Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+
+        let digest_message = serenity::UserId::new(user_id)
+            .create_dm_channel(ctx)
+            .await?
+            .send_message(
+                ctx,
+                get_digest_message(&pages, digest_id, DELETE_MESSAGE_EMOJI),
+            )
+            .await?;
+
+        for (reminder, _) in &reminders_with_channel_names {
+            delete_reminder_by_id(db_connection, reminder.pk()).await?;
+        }
+
+        paginated_digests.lock().insert(digest_id);
+        tokio::spawn(paginate_digest(
+            ctx.clone(),
+            paginated_digests.clone(),
+            digest_message,
+            digest_id,
+            pages,
+        ));
+    }
+
+    update_last_digest_sent_on(db_connection, user_id, local_date).await?;
+
+    Ok(())
+}
+
+async fn get_channel_name(ctx: &serenity::Context, channel_id: serenity::ChannelId) -> String {
+    channel_id.name(ctx).await.unwrap_or_else(|err| {
+        tracing::warn!(%channel_id, error = %err, "Failed to get channel name");
+        "the past!".to_string()
+    })
+}
+
+/// Modeled after [`poise::builtins::paginate`], which can't be used directly because a digest isn't sent in response to a command.
+///
+/// Once the buttons time out, they're removed from `message` so they don't linger unanswered.
+async fn paginate_digest(
+    ctx: serenity::Context,
+    paginated_digests: PaginatedDigests,
+    mut message: serenity::Message,
+    digest_id: uuid::Uuid,
+    pages: Vec<CreateEmbed>,
+) {
+    let previous_button_id = String::from(InteractionCustomId::PreviousDigestPage(digest_id));
+    let next_button_id = String::from(InteractionCustomId::NextDigestPage(digest_id));
+
+    let mut current_page: usize = 0;
+    loop {
+        let (previous_button_id, next_button_id) =
+            (previous_button_id.clone(), next_button_id.clone());
+        let Some(press) = serenity::ComponentInteractionCollector::new(&ctx)
+            .filter(move |press| {
+                press.data.custom_id == previous_button_id || press.data.custom_id == next_button_id
+            })
+            .timeout(DIGEST_PAGINATION_TIMEOUT)
+            .await
+        else {
+            break;
+        };
+
+        current_page = match InteractionCustomId::try_from(press.data.custom_id.as_str()) {
+            Ok(InteractionCustomId::PreviousDigestPage(_)) => current_page.saturating_sub(1),
+            _ => (current_page + 1).min(pages.len() - 1),
+        };
+
+        let response = press
+            .create_response(
+                &ctx,
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .embed(pages[current_page].clone())
+                        .components(get_digest_page_components(
+                            digest_id,
+                            current_page,
+                            pages.len(),
+                            DELETE_MESSAGE_EMOJI,
+                        )),
+                ),
+            )
+            .await;
+
+        if let Err(err) = response {
+            tracing::warn!(error = %err, "Failed to change digest page");
+        }
+    }
+
+    paginated_digests.lock().remove(&digest_id);
+
+    let edit = serenity::EditMessage::new()
+        .components(get_expired_digest_components(DELETE_MESSAGE_EMOJI));
+    if let Err(err) = message.edit(&ctx, edit).await {
+        // The user may well have deleted the digest by now
+        tracing::debug!(error = %err, "Failed to remove expired digest page buttons");
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+
+use poise::serenity_prelude as serenity;
+
+use crate::{config::ConfigError, database::message_encryption::MessageEncryptionError};
+
+/// Every error the bot can run into, split by what can be done about it. Each kind gets its own reply
+/// (see [`crate::components::error_reply`]), so users are never left without a response.
+#[derive(Debug)]
+pub enum Error {
+    /// The user asked for something that can't be done. Its message is shown to them as is.
+    User(UserError),
+    /// A request to Discord failed. Boxed since [`serenity::Error`] is far larger than the other variants.
+    Discord(Box<serenity::Error>),
+    /// Reading from or writing to the database failed
+    Storage(tokio_rusqlite::Error),
+    /// Anything else, e.g. the scheduler having stopped. Its message is only logged.
+    Internal(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Mistakes (or outdated messages) on the user's end. Each message is written to be shown to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserError {
+    /// The bookmark was deleted, e.g. by its retention policy, since the message being interacted with was sent
+    BookmarkNotFound,
+    /// Only whoever made a bookmark may act on it
+    NotYourBookmark,
+    /// An interaction came from a component that doesn't support it, e.g. a button with a select menu's ID
+    UnsupportedInteraction,
+    /// A select menu option that doesn't exist (anymore) was chosen
+    InvalidSelection(String),
+    /// Holds what was given instead of a message link
+    InvalidMessageLink(String),
+    /// The linked message doesn't exist, or either the user or the bot can't see it. These aren't told apart
+    /// so the bot can't be used to find out whether messages exist in channels the user can't see.
+    MessageNotAccessible,
+    /// Holds what was given instead of a duration like `1h30m`
+    InvalidDuration(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(err) => write!(f, "{}", err),
+            Self::Discord(err) => write!(f, "Discord error: {}", err),
+            Self::Storage(err) => write!(f, "storage error: {}", err),
+            Self::Internal(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::User(_) => None,
+            Self::Discord(err) => Some(err.as_ref()),
+            Self::Storage(err) => Some(err),
+            Self::Internal(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BookmarkNotFound => write!(f, "That bookmark doesn't exist anymore."),
+            Self::NotYourBookmark => write!(f, "That's someone else's bookmark."),
+            Self::UnsupportedInteraction => write!(f, "I don't know what to do with that."),
+            Self::InvalidSelection(value) => {
+                write!(f, "`{}` isn't one of the options anymore.", value)
+            }
+            Self::InvalidMessageLink(value) => write!(
+                f,
+                "`{}` isn't a message link. Use \"Copy Message Link\" on a message to get one.",
+                value
+            ),
+            Self::MessageNotAccessible => write!(
+                f,
+                "I couldn't find that message, or one of us can't see its channel."
+            ),
+            Self::InvalidDuration(value) => write!(
+                f,
+                "`{}` isn't a duration. Try something like `10m`, `1h30m` or `2d`.",
+                value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UserError {}
+
+impl From<UserError> for Error {
+    fn from(err: UserError) -> Self {
+        Self::User(err)
+    }
+}
+
+impl From<serenity::Error> for Error {
+    fn from(err: serenity::Error) -> Self {
+        Self::Discord(Box::new(err))
+    }
+}
+
+impl From<tokio_rusqlite::Error> for Error {
+    fn from(err: tokio_rusqlite::Error) -> Self {
+        Self::Storage(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Storage(tokio_rusqlite::Error::Rusqlite(err))
+    }
+}
+
+/// Implements [`From`] for errors that all end up as [`Error::Internal`]
+macro_rules! impl_from_for_internal_error {
+    ($($error:ty),* $(,)?) => {
+        $(
+            impl From<$error> for Error {
+                fn from(err: $error) -> Self {
+                    Self::Internal(Box::new(err))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_internal_error!(
+    ConfigError,
+    MessageEncryptionError,
+    serde_json::Error,
+    tokio::sync::mpsc::error::SendError<crate::models::reminder::PersistedReminder>,
+    tokio::sync::mpsc::error::SendError<u64>,
+);
+
+#[cfg(feature = "metrics")]
+impl_from_for_internal_error!(hyper::Error);
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self::Internal(message.into())
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Self::Internal(message.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_errors_are_shown_as_is() {
+        let error = Error::from(UserError::InvalidSelection("tomorrow".to_string()));
+
+        assert_eq!(
+            error.to_string(),
+            "`tomorrow` isn't one of the options anymore."
+        );
+    }
+
+    #[test]
+    fn test_from_rusqlite_error_is_a_storage_error() {
+        let error = Error::from(rusqlite::Error::QueryReturnedNoRows);
+
+        assert!(matches!(error, Error::Storage(_)));
+    }
+}
@@ -0,0 +1,547 @@
+use std::{str::FromStr, sync::Arc};
+
+use poise::{
+    samples::create_application_commands,
+    serenity_prelude::{
+        self as serenity, ComponentInteractionDataKind, CreateInteractionResponseMessage, FullEvent,
+    },
+    CreateReply, FrameworkContext,
+};
+
+use crate::{
+    commands,
+    components::{
+        bookmark::{
+            bookmark_read_toggled_reply::get_bookmark_read_toggled_reply,
+            bookmark_review_answered_reply::get_bookmark_review_answered_reply,
+            bookmark_star_toggled_reply::get_bookmark_star_toggled_reply,
+        },
+        digest::digest_expired_reply::get_digest_expired_reply,
+        error_reply::get_error_reply,
+        interaction_custom_id::InteractionCustomId,
+        reminder::{
+            countdown_cancelled_reply::get_countdown_cancelled_reply,
+            reminder_created_reply::get_reminder_created_reply,
+            reminder_marked_urgent_reply::{
+                get_reminder_marked_urgent_reply, get_reminder_not_found_reply,
+            },
+            reminder_time_reply::get_reminder_time_reply,
+            reminder_time_select_menu::menu_value::ReminderSelectMenuValue,
+        },
+        DELETE_MESSAGE_EMOJI,
+    },
+    database::{
+        self,
+        reminder::{delete_countdown, mark_reminder_as_urgent},
+    },
+    error::UserError,
+    linked_message,
+    models::{self, bookmark_review::ReviewAnswer},
+    privacy, reaction_shortcuts, spaced_repetition, AttendantState, Data, Error,
+};
+
+/// The attendant's data, for the errors that come with it (i.e. not setup errors)
+fn get_error_data<'a, U: AttendantState>(
+    error: &poise::FrameworkError<'a, U, Error>,
+) -> Option<&'a Data> {
+    match error {
+        poise::FrameworkError::EventHandler { framework, .. }
+        | poise::FrameworkError::UnknownCommand { framework, .. }
+        | poise::FrameworkError::UnknownInteraction { framework, .. } => {
+            Some(framework.user_data.attendant())
+        }
+        _ => error.ctx().map(|ctx| ctx.data().attendant()),
+    }
+}
+
+pub async fn on_error<U: AttendantState>(error: poise::FrameworkError<'_, U, Error>) {
+    if let Some(data) = get_error_data(&error) {
+        data.metrics.record_error(&error);
+    }
+    match error {
+        poise::FrameworkError::Setup {
+            error, framework, ..
+        } => {
+            tracing::error!(error = %error, "Failed to start bot");
+            framework.shard_manager().shutdown_all().await;
+        }
+        poise::FrameworkError::Command { error, ctx, .. } => {
+            log_error(&error, "Command failed");
+            if let Err(err) = ctx.send(get_error_reply(&error)).await {
+                tracing::error!(error = %err, "Failed to reply with error");
+            }
+        }
+        poise::FrameworkError::EventHandler {
+            error, ctx, event, ..
+        } => {
+            log_error(&error, "Failed to handle event");
+            if let FullEvent::InteractionCreate {
+                interaction: serenity::Interaction::Component(component_interaction),
+            } = event
+            {
+                if let Err(err) = respond_to_component_interaction(
+                    ctx,
+                    component_interaction,
+                    get_error_reply(&error),
+                )
+                .await
+                {
+                    tracing::error!(error = %err, "Failed to reply with error");
+                }
+            }
+        }
+        error => {
+            if let Err(e) = poise::builtins::on_error(error).await {
+                tracing::error!(error = %e, "Failed to handle error");
+            }
+        }
+    }
+}
+
+/// User errors are expected (e.g. clicking a button on an old message), so they're not logged as errors
+fn log_error(error: &Error, message: &str) {
+    match error {
+        Error::User(_) => tracing::info!(error = %error, "{}", message),
+        _ => tracing::error!(error = %error, "{}", message),
+    }
+}
+
+/// Handles the reactions and component interactions on the messages this crate's commands send,
+/// ignoring any other events so it can be called from a bot's own event handler
+pub async fn on_event<U: AttendantState>(
+    ctx: &serenity::Context,
+    event: &FullEvent,
+    framework: FrameworkContext<'_, U, Error>,
+    data: &U,
+) -> Result<(), Error> {
+    tracing::debug!(event = event.snake_case_name(), "Received event");
+    let data = data.attendant();
+
+    match event {
+        serenity::FullEvent::ReactionAdd { add_reaction } => {
+            let is_reactor_not_the_bot = add_reaction.user_id != Some(framework.bot_id);
+            let is_message_from_the_bot = add_reaction.message_author_id == Some(framework.bot_id);
+            let is_reaction_emoji_delete = add_reaction.emoji.unicode_eq(DELETE_MESSAGE_EMOJI);
+
+            if is_reactor_not_the_bot && !is_message_from_the_bot {
+                reaction_shortcuts::handle_reaction_shortcut(ctx, add_reaction, data).await?;
+            } else if is_reactor_not_the_bot && is_message_from_the_bot && is_reaction_emoji_delete
+            {
+                delete_message_with_audit_log_reason(
+                    ctx.http.clone(),
+                    add_reaction.channel_id,
+                    add_reaction.message_id,
+                    &add_reaction
+                        .message_author_id
+                        .map_or("Unknown".to_string(), |id| id.to_string()),
+                )
+                .await?;
+            }
+        }
+        serenity::FullEvent::InteractionCreate { interaction } => {
+            if let Some(component_interaction) = interaction.as_message_component() {
+                match InteractionCustomId::try_from(&component_interaction.data.custom_id[..]) {
+                    Ok(InteractionCustomId::DeleteMessage) => {
+                        delete_message_with_audit_log_reason(
+                            ctx.http.clone(),
+                            component_interaction.channel_id,
+                            component_interaction.message.id,
+                            &component_interaction.user.name,
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::SetReminder(bookmark_id)) => {
+                        tracing::Span::current().record("bookmark_id", bookmark_id.to_string());
+                        let db_connection = data.db_connection.clone();
+                        let bookmark =
+                            database::bookmark::get_bookmark_by_id(&db_connection, bookmark_id)
+                                .await?
+                                .ok_or(UserError::BookmarkNotFound)?;
+                        if component_interaction.user.id != bookmark.user_id() {
+                            return Err(UserError::NotYourBookmark.into());
+                        }
+                        let omit_message_content = bookmark.is_content_omitted()
+                            || privacy::is_privacy_mode_enabled(
+                                &db_connection,
+                                bookmark.user_id(),
+                                bookmark.message().guild_id,
+                            )
+                            .await?;
+                        set_reminder_from_select_menu(
+                            ctx,
+                            component_interaction,
+                            data,
+                            bookmark.message().clone(),
+                            omit_message_content,
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::SetMessageReminder {
+                        guild_id,
+                        channel_id,
+                        message_id,
+                    }) => {
+                        let guild_id = guild_id.map(serenity::GuildId::new);
+                        let message = linked_message::fetch_message(
+                            ctx,
+                            guild_id,
+                            serenity::ChannelId::new(channel_id),
+                            serenity::MessageId::new(message_id),
+                        )
+                        .await?;
+                        let omit_message_content = privacy::is_privacy_mode_enabled(
+                            &data.db_connection,
+                            component_interaction.user.id.get(),
+                            guild_id,
+                        )
+                        .await?;
+                        set_reminder_from_select_menu(
+                            ctx,
+                            component_interaction,
+                            data,
+                            message,
+                            omit_message_content,
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::ChooseBookmarkToRemind) => {
+                        let ComponentInteractionDataKind::StringSelect { values } =
+                            &component_interaction.data.kind
+                        else {
+                            return Err(UserError::UnsupportedInteraction.into());
+                        };
+                        let selected_value =
+                            values.first().ok_or(UserError::UnsupportedInteraction)?;
+                        let bookmark_id = uuid::Uuid::parse_str(selected_value)
+                            .map_err(|_| UserError::InvalidSelection(selected_value.clone()))?;
+                        tracing::Span::current().record("bookmark_id", bookmark_id.to_string());
+                        let bookmark = database::bookmark::get_bookmark_by_id(
+                            &data.db_connection,
+                            bookmark_id,
+                        )
+                        .await?
+                        .ok_or(UserError::BookmarkNotFound)?;
+                        if component_interaction.user.id != bookmark.user_id() {
+                            return Err(UserError::NotYourBookmark.into());
+                        }
+                        // Picking a time is handled like the select menu on the bookmark's DM
+                        respond_to_component_interaction(
+                            ctx,
+                            component_interaction,
+                            get_reminder_time_reply(&bookmark),
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::MarkReminderUrgent(reminder_pk)) => {
+                        tracing::Span::current().record("reminder_pk", reminder_pk);
+                        let was_marked = mark_reminder_as_urgent(
+                            &data.db_connection,
+                            reminder_pk,
+                            component_interaction.user.id.get(),
+                        )
+                        .await?;
+                        let reply = if was_marked {
+                            get_reminder_marked_urgent_reply()
+                        } else {
+                            get_reminder_not_found_reply()
+                        };
+                        respond_to_component_interaction(ctx, component_interaction, reply).await?;
+                    }
+                    Ok(InteractionCustomId::CancelCountdown(countdown_id)) => {
+                        // The scheduler skips reminders that no longer exist, so deleting the alerts is enough to cancel them
+                        let cancelled_alert_count = delete_countdown(
+                            &data.db_connection,
+                            countdown_id,
+                            component_interaction.user.id.get(),
+                        )
+                        .await?;
+                        respond_to_component_interaction(
+                            ctx,
+                            component_interaction,
+                            get_countdown_cancelled_reply(cancelled_alert_count),
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::ToggleBookmarkStar(bookmark_id)) => {
+                        tracing::Span::current().record("bookmark_id", bookmark_id.to_string());
+                        let starred = database::bookmark::toggle_bookmark_star(
+                            &data.db_connection,
+                            bookmark_id,
+                            component_interaction.user.id.get(),
+                        )
+                        .await?
+                        .ok_or(UserError::BookmarkNotFound)?;
+                        respond_to_component_interaction(
+                            ctx,
+                            component_interaction,
+                            get_bookmark_star_toggled_reply(starred),
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::ToggleBookmarkRead(bookmark_id)) => {
+                        tracing::Span::current().record("bookmark_id", bookmark_id.to_string());
+                        let read = database::bookmark::toggle_bookmark_read(
+                            &data.db_connection,
+                            bookmark_id,
+                            component_interaction.user.id.get(),
+                        )
+                        .await?
+                        .ok_or(UserError::BookmarkNotFound)?;
+                        respond_to_component_interaction(
+                            ctx,
+                            component_interaction,
+                            get_bookmark_read_toggled_reply(read),
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::BookmarkReviewGotIt(bookmark_id)) => {
+                        answer_bookmark_review(
+                            ctx,
+                            component_interaction,
+                            data,
+                            bookmark_id,
+                            ReviewAnswer::GotIt,
+                        )
+                        .await?;
+                    }
+                    Ok(InteractionCustomId::BookmarkReviewShowSooner(bookmark_id)) => {
+                        answer_bookmark_review(
+                            ctx,
+                            component_interaction,
+                            data,
+                            bookmark_id,
+                            ReviewAnswer::ShowSooner,
+                        )
+                        .await?;
+                    }
+                    Ok(
+                        InteractionCustomId::PreviousDigestPage(digest_id)
+                        | InteractionCustomId::NextDigestPage(digest_id),
+                    ) => {
+                        // Handled by the collector spawned when the digest was sent, as long as it's still running
+                        if !data.paginated_digests.contains(digest_id) {
+                            respond_to_component_interaction(
+                                ctx,
+                                component_interaction,
+                                get_digest_expired_reply(),
+                            )
+                            .await?;
+                        }
+                    }
+                    Ok(
+                        InteractionCustomId::ConfirmForgetMe(_)
+                        | InteractionCustomId::CancelForgetMe(_),
+                    ) => {
+                        // Handled by the collector in the `/forget_me` command
+                    }
+                    Err(e) => {
+                        // Could belong to a bot running these commands alongside its own
+                        tracing::debug!(error = %e, "Received an unrecognized component interaction");
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Sets a reminder on the message for the time picked from [`get_reminder_select_menu`], for the user who picked it
+///
+/// [`get_reminder_select_menu`]: crate::components::reminder::reminder_time_select_menu::select_menu::get_reminder_select_menu
+async fn set_reminder_from_select_menu(
+    ctx: &serenity::Context,
+    component_interaction: &serenity::ComponentInteraction,
+    data: &Data,
+    message: serenity::Message,
+    omit_message_content: bool,
+) -> Result<(), Error> {
+    let ComponentInteractionDataKind::StringSelect { values } = &component_interaction.data.kind
+    else {
+        return Err(UserError::UnsupportedInteraction.into());
+    };
+    let selected_value = values.first().ok_or(UserError::UnsupportedInteraction)?;
+    let reminder_wait_duration = chrono::Duration::from(
+        ReminderSelectMenuValue::from_str(selected_value.as_str())
+            .map_err(|_| UserError::InvalidSelection(selected_value.clone()))?,
+    );
+    let remind_at = chrono::Utc::now() + reminder_wait_duration;
+    let mut reminder =
+        models::reminder::Reminder::new(component_interaction.user.id.get(), message, remind_at);
+    if omit_message_content {
+        reminder = reminder.without_message_content();
+    }
+    let persisted_reminder =
+        database::reminder::insert_reminder(&data.db_connection, reminder).await?;
+    let reminder_pk = persisted_reminder.pk();
+    tracing::Span::current().record("reminder_pk", reminder_pk);
+    data.tx.send(persisted_reminder).await?;
+    respond_to_component_interaction(
+        ctx,
+        component_interaction,
+        get_reminder_created_reply(&remind_at, reminder_pk),
+    )
+    .await
+}
+
+async fn answer_bookmark_review(
+    ctx: &serenity::Context,
+    component_interaction: &serenity::ComponentInteraction,
+    data: &Data,
+    bookmark_id: uuid::Uuid,
+    answer: ReviewAnswer,
+) -> Result<(), Error> {
+    tracing::Span::current().record("bookmark_id", bookmark_id.to_string());
+    let bookmark = database::bookmark::get_bookmark_by_id(&data.db_connection, bookmark_id)
+        .await?
+        .ok_or(UserError::BookmarkNotFound)?;
+    if component_interaction.user.id != bookmark.user_id() {
+        return Err(UserError::NotYourBookmark.into());
+    }
+
+    let next_review_at =
+        spaced_repetition::answer_review(&data.db_connection, &data.tx, &bookmark, answer).await?;
+    respond_to_component_interaction(
+        ctx,
+        component_interaction,
+        get_bookmark_review_answered_reply(&next_review_at),
+    )
+    .await
+}
+
+async fn respond_to_component_interaction(
+    ctx: &serenity::Context,
+    component_interaction: &serenity::ComponentInteraction,
+    reply: CreateReply,
+) -> Result<(), Error> {
+    component_interaction
+        .create_response(
+            &ctx.http,
+            serenity::CreateInteractionResponse::Message(
+                reply.to_slash_initial_response(CreateInteractionResponseMessage::new()),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the commands to register with Discord alongside the same commands for the framework to run
+pub fn get_commands() -> (
+    Vec<serenity::CreateCommand>,
+    Vec<poise::Command<Data, Error>>,
+) {
+    let help = vec![commands::help()];
+    let (serenity_commands, commands) = get_embeddable_commands();
+
+    (
+        [create_application_commands(&help), serenity_commands].concat(),
+        help.into_iter().chain(commands).collect(),
+    )
+}
+
+/// [`get_commands`] without `/help` (which only lists the framework's commands, so a bot running these alongside
+/// its own likely has one already), for any framework data implementing [`AttendantState`]
+pub fn get_embeddable_commands<U: AttendantState>(
+) -> (Vec<serenity::CreateCommand>, Vec<poise::Command<U, Error>>) {
+    let commands = vec![commands::server_settings()];
+    let commands_available_in_dms = vec![
+        commands::get_reminders(),
+        commands::remind_me_in_10_seconds(),
+        commands::remind_by_link(),
+        commands::countdown(),
+        commands::bookmark(),
+        commands::bookmark_by_link(),
+        commands::get_bookmarks(),
+        commands::next_bookmark(),
+        commands::delete_bookmark(),
+        commands::settings(),
+        commands::forget_me(),
+        commands::my_data(),
+    ];
+
+    let serenity_commands = [
+        create_application_commands(&commands),
+        create_application_commands(&commands_available_in_dms)
+            .into_iter()
+            .map(|command| {
+                command
+                    .add_context(serenity::InteractionContext::PrivateChannel)
+                    .add_context(serenity::InteractionContext::BotDm)
+            })
+            .collect(),
+    ]
+    .concat();
+
+    (
+        serenity_commands,
+        commands
+            .into_iter()
+            .chain(commands_available_in_dms)
+            .collect(),
+    )
+}
+
+/// Runs `commands` with the bot's error handling, logging and component interaction handling
+pub fn get_framework_options<U: AttendantState>(
+    commands: Vec<poise::Command<U, Error>>,
+) -> poise::FrameworkOptions<U, Error> {
+    poise::FrameworkOptions {
+        commands,
+        on_error: |error| Box::pin(on_error(error)),
+        pre_command: |ctx| {
+            Box::pin(async move {
+                tracing::info!(command = %ctx.command().qualified_name, "Executing command");
+                ctx.data()
+                    .attendant()
+                    .metrics
+                    .record_command_invocation(&ctx.command().qualified_name);
+            })
+        },
+        post_command: |ctx| {
+            Box::pin(async move {
+                tracing::info!(command = %ctx.command().qualified_name, "Executed command");
+            })
+        },
+        skip_checks_for_owners: false,
+        event_handler: |ctx, event, framework, data| {
+            Box::pin(on_event(ctx, event, framework, data))
+        },
+        ..Default::default()
+    }
+}
+
+async fn delete_message_with_audit_log_reason(
+    http: Arc<serenity::Http>,
+    channel_id: serenity::ChannelId,
+    message_id: serenity::MessageId,
+    responsible_user_name: &str,
+) -> Result<(), Error> {
+    http.delete_message(
+        channel_id,
+        message_id,
+        Some(&get_delete_message_audit_log_reason(responsible_user_name)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn get_delete_message_audit_log_reason(responsible_user_name: &str) -> String {
+    format!("Deletion requested by: {}", responsible_user_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_delete_message_audit_log_reason() {
+        let responsible_user_name = "TestUser";
+        assert_eq!(
+            get_delete_message_audit_log_reason(responsible_user_name),
+            "Deletion requested by: TestUser"
+        );
+    }
+}
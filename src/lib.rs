@@ -0,0 +1,105 @@
+//! Bookmarks and reminders for Discord messages, as poise commands backed by SQLite.
+//!
+//! The `discord-attendant` binary runs these as a standalone bot. To run them in another bot instead, have its
+//! data implement [`AttendantState`], add [`framework::get_embeddable_commands`] to its framework's commands and
+//! pass events through [`framework::on_event`] so the buttons and select menus on the bot's messages keep working.
+//! [`background_tasks::init`] sets up the [`Data`] along with the tasks delivering the reminders.
+
+pub mod background_tasks;
+pub mod bookmark_search;
+pub mod catch_up;
+pub mod command_registration;
+pub mod commands;
+pub mod components;
+pub mod config;
+pub mod database;
+pub mod digest;
+pub mod error;
+pub mod framework;
+mod linked_message;
+pub mod logging;
+pub mod metrics;
+pub mod models;
+mod privacy;
+pub mod reaction_shortcuts;
+pub mod retention;
+pub mod scheduler;
+pub mod shutdown;
+pub mod spaced_repetition;
+#[cfg(test)]
+mod test_fixtures;
+
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, Mutex};
+use tokio_rusqlite::Connection;
+
+use bookmark_search::BookmarkSearchCache;
+use catch_up::HeldCatchUpSummaries;
+use config::Config;
+use digest::PaginatedDigests;
+pub use error::Error;
+use metrics::Metrics;
+use models::reminder::PersistedReminder;
+use reaction_shortcuts::ReactionShortcutEmojisCache;
+
+/// Generic over the framework's data so the commands can run in bots with their own data type, see [`AttendantState`]
+pub type Context<'a, U = Data> = poise::Context<'a, U, Error>;
+pub type ApplicationContext<'a, U = Data> = poise::ApplicationContext<'a, U, Error>;
+
+pub struct Data {
+    db_connection: Arc<Mutex<Connection>>,
+    tx: mpsc::Sender<PersistedReminder>,
+    /// Takes a user ID and drops all of that user's reminders from the scheduler's queue
+    cancel_user_reminders_tx: mpsc::Sender<u64>,
+    uuid_context: Arc<std::sync::Mutex<uuid::ContextV7>>,
+    config: Config,
+    metrics: Metrics,
+    paginated_digests: PaginatedDigests,
+    held_catch_up_summaries: HeldCatchUpSummaries,
+    reaction_shortcut_emojis: ReactionShortcutEmojisCache,
+    bookmark_search_cache: BookmarkSearchCache,
+}
+
+/// Implemented by a framework's data to run this crate's commands and event handler with it,
+/// usually by holding a [`Data`] alongside the bot's own state
+pub trait AttendantState: Send + Sync + 'static {
+    fn attendant(&self) -> &Data;
+}
+
+impl AttendantState for Data {
+    fn attendant(&self) -> &Data {
+        self
+    }
+}
+
+impl Data {
+    /// Usually called by [`background_tasks::init`], which sets up the other end of everything passed in.
+    ///
+    /// `tx` and `cancel_user_reminders_tx` are the senders for the receivers given to [`scheduler::send_reminders`],
+    /// `metrics` should be the same as the [`scheduler::DeliveryWorkers`]' so they end up in one place,
+    /// `paginated_digests` the same as [`digest::send_digests`]'
+    /// and `held_catch_up_summaries` the same as [`catch_up::send_catch_up_summaries`]'
+    pub fn new(
+        db_connection: Arc<Mutex<Connection>>,
+        tx: mpsc::Sender<PersistedReminder>,
+        cancel_user_reminders_tx: mpsc::Sender<u64>,
+        config: Config,
+        metrics: Metrics,
+        paginated_digests: PaginatedDigests,
+        held_catch_up_summaries: HeldCatchUpSummaries,
+    ) -> Self {
+        Self {
+            db_connection,
+            tx,
+            cancel_user_reminders_tx,
+            uuid_context: Arc::new(std::sync::Mutex::new(uuid::ContextV7::new())),
+            config,
+            metrics,
+            paginated_digests,
+            held_catch_up_summaries,
+            reaction_shortcut_emojis: ReactionShortcutEmojisCache::default(),
+            bookmark_search_cache: BookmarkSearchCache::default(),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use poise::serenity_prelude as serenity;
+
+use crate::{error::UserError, models::message_link::MessageLink, Error};
+
+/// Fetches the message `link` points to, as long as `user_id` can read it: guild messages need the
+/// View Channel and Read Message History permissions, and messages outside of guilds have to be in the
+/// user's DMs with the bot (the only ones it can fetch anyway).
+pub async fn fetch_linked_message(
+    ctx: &serenity::Context,
+    user_id: serenity::UserId,
+    link: &MessageLink,
+) -> Result<serenity::Message, Error> {
+    match link.guild_id() {
+        Some(guild_id) => {
+            ensure_user_can_read_channel(ctx, user_id, guild_id, link.channel_id()).await?
+        }
+        None => {
+            let dm_channel = user_id.create_dm_channel(ctx).await?;
+            if dm_channel.id != link.channel_id() {
+                return Err(UserError::MessageNotAccessible.into());
+            }
+        }
+    }
+
+    fetch_message(ctx, link.guild_id(), link.channel_id(), link.message_id())
+        .await
+        .map_err(get_fetch_error)
+}
+
+/// Fetches a message over HTTP, which leaves out its guild ID, so it's filled in from `guild_id` since
+/// the message's link needs it. Doesn't check whether anyone but the bot can read the message.
+pub async fn fetch_message(
+    cache_http: impl serenity::CacheHttp,
+    guild_id: Option<serenity::GuildId>,
+    channel_id: serenity::ChannelId,
+    message_id: serenity::MessageId,
+) -> Result<serenity::Message, serenity::Error> {
+    let mut message = channel_id.message(cache_http, message_id).await?;
+    message.guild_id = message.guild_id.or(guild_id);
+    Ok(message)
+}
+
+async fn ensure_user_can_read_channel(
+    ctx: &serenity::Context,
+    user_id: serenity::UserId,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+) -> Result<(), Error> {
+    let channel = get_guild_channel(ctx, channel_id).await?;
+    if channel.guild_id != guild_id {
+        return Err(UserError::MessageNotAccessible.into());
+    }
+    let member = guild_id
+        .member(ctx, user_id)
+        .await
+        .map_err(get_fetch_error)?;
+    let guild = guild_id.to_partial_guild(ctx).await?;
+
+    // Threads don't have permission overwrites of their own, they go by their parent channel's
+    let permissions = match channel
+        .parent_id
+        .filter(|_| channel.thread_metadata.is_some())
+    {
+        Some(parent_id) => {
+            guild.user_permissions_in(&get_guild_channel(ctx, parent_id).await?, &member)
+        }
+        None => guild.user_permissions_in(&channel, &member),
+    };
+    if !permissions.view_channel() || !permissions.read_message_history() {
+        return Err(UserError::MessageNotAccessible.into());
+    }
+
+    // Private threads can only be seen by their members and those who can manage threads
+    if channel.kind == serenity::ChannelType::PrivateThread && !permissions.manage_threads() {
+        channel
+            .id
+            .get_thread_member(ctx, user_id, false)
+            .await
+            .map_err(get_fetch_error)?;
+    }
+
+    Ok(())
+}
+
+async fn get_guild_channel(
+    ctx: &serenity::Context,
+    channel_id: serenity::ChannelId,
+) -> Result<serenity::GuildChannel, Error> {
+    channel_id
+        .to_channel(ctx)
+        .await
+        .map_err(get_fetch_error)?
+        .guild()
+        .ok_or_else(|| UserError::MessageNotAccessible.into())
+}
+
+/// Discord answers with 403 or 404 for things that don't exist or can't be seen, which the user should hear about
+fn get_fetch_error(err: serenity::Error) -> Error {
+    match &err {
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response))
+            if response.status_code == serenity::StatusCode::FORBIDDEN
+                || response.status_code == serenity::StatusCode::NOT_FOUND =>
+        {
+            UserError::MessageNotAccessible.into()
+        }
+        _ => err.into(),
+    }
+}
@@ -0,0 +1,124 @@
+use poise::serenity_prelude::{self as serenity, FullEvent, Interaction};
+use tracing::{field::Empty, Instrument, Span};
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    config::{LogFormat, LogLevel, LoggingConfig},
+    Error,
+};
+
+/// What message contents are replaced with in logs, unless `include_message_content` is set
+pub const REDACTED_MESSAGE_CONTENT: &str = "[redacted]";
+
+/// Logs go to stderr so they don't get mixed up with the output of CLI commands like `export`.
+///
+/// The `RUST_LOG` environment variable replaces the configured level entirely when set, e.g. to see
+/// serenity's and poise's own logs, which are otherwise only shown for warnings and errors.
+pub fn init_logging(config: &LoggingConfig) {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(get_default_log_directives(config.level)));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+
+    match config.format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+    }
+}
+
+fn get_default_log_directives(level: LogLevel) -> String {
+    format!("warn,{}={}", env!("CARGO_CRATE_NAME"), level)
+}
+
+/// Other people's messages shouldn't end up in logs by default, so anything logging a message's content goes through this
+/// with [`LoggingConfig::include_message_content`]
+pub fn get_loggable_message_content(content: &str, include_message_content: bool) -> &str {
+    if include_message_content {
+        content
+    } else {
+        REDACTED_MESSAGE_CONTENT
+    }
+}
+
+/// Wraps the poise framework so that everything done while handling an event (running a command included)
+/// happens inside a span describing it. Poise has no hook around a command's whole execution to do this in.
+pub struct InstrumentedFramework<U>(pub poise::Framework<U, Error>);
+
+#[serenity::async_trait]
+impl<U: Send + Sync + 'static> serenity::Framework for InstrumentedFramework<U> {
+    async fn init(&mut self, client: &serenity::Client) {
+        self.0.init(client).await;
+    }
+
+    async fn dispatch(&self, ctx: serenity::Context, event: FullEvent) {
+        let span = get_event_span(&event);
+        self.0.dispatch(ctx, event).instrument(span).await;
+    }
+}
+
+/// `bookmark_id` and `reminder_pk` are left empty for whatever handles the event to record once it knows them
+fn get_event_span(event: &FullEvent) -> Span {
+    let FullEvent::InteractionCreate { interaction } = event else {
+        return tracing::debug_span!("event", event = event.snake_case_name());
+    };
+
+    match interaction {
+        Interaction::Command(command) | Interaction::Autocomplete(command) => tracing::info_span!(
+            "command",
+            command = %command.data.name,
+            interaction_id = %command.id,
+            user_id = %command.user.id,
+            guild_id = command.guild_id.map(|guild_id| guild_id.get()),
+            autocomplete = matches!(interaction, Interaction::Autocomplete(_)),
+            bookmark_id = Empty,
+            reminder_pk = Empty,
+        ),
+        Interaction::Component(component) => tracing::info_span!(
+            "interaction",
+            custom_id = %component.data.custom_id,
+            interaction_id = %component.id,
+            user_id = %component.user.id,
+            guild_id = component.guild_id.map(|guild_id| guild_id.get()),
+            bookmark_id = Empty,
+            reminder_pk = Empty,
+        ),
+        Interaction::Modal(modal) => tracing::info_span!(
+            "interaction",
+            custom_id = %modal.data.custom_id,
+            interaction_id = %modal.id,
+            user_id = %modal.user.id,
+            guild_id = modal.guild_id.map(|guild_id| guild_id.get()),
+            bookmark_id = Empty,
+            reminder_pk = Empty,
+        ),
+        _ => tracing::info_span!("interaction", interaction_id = %interaction.id()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_default_log_directives() {
+        assert_eq!(
+            get_default_log_directives(LogLevel::Debug),
+            "warn,discord_attendant=debug"
+        );
+    }
+
+    #[test]
+    fn test_get_loggable_message_content() {
+        assert_eq!(
+            get_loggable_message_content("secret", false),
+            REDACTED_MESSAGE_CONTENT
+        );
+        assert_eq!(get_loggable_message_content("secret", true), "secret");
+    }
+}
@@ -1,394 +1,149 @@
-mod commands;
-mod components;
-mod database;
-mod models;
-
-use components::{
-    interaction_custom_id::{InteractionCustomId, DELETE_MESSAGE_INTERACTION_CUSTOM_ID},
-    relative_timestamp_string::get_discord_relative_timestamp_string,
-    reminder::{
-        reminder_message::get_reminder_message,
-        reminder_time_select_menu::menu_value::ReminderSelectMenuValue,
-    },
-    DELETE_MESSAGE_EMOJI,
-};
-use database::{
-    bookmark::create_bookmarks_table_if_nonexistent,
-    reminder::{create_reminders_table_if_nonexistent, delete_reminder_by_id, get_all_reminders},
+mod cli;
+
+use clap::Parser;
+use cli::{Cli, CliCommand};
+use discord_attendant::{
+    background_tasks, command_registration,
+    config::{Config, ConfigError, GlobalCommandRegistration},
+    database::message_encryption::attach_message_keyring,
+    framework::{get_commands, get_framework_options},
+    logging, shutdown, Error,
 };
-use poise::{
-    samples::create_application_commands,
-    serenity_prelude::{
-        self as serenity, ComponentInteractionDataKind, CreateInteractionResponseMessage, FullEvent,
-    },
-    FrameworkContext,
-};
-use std::{cmp::Reverse, collections::BinaryHeap, env, str::FromStr, sync::Arc};
+use poise::serenity_prelude as serenity;
 use tokio::sync::Mutex;
-use tokio_rusqlite::Connection;
+use tokio_rusqlite::{Connection, OpenFlags};
 
-type Error = Box<dyn std::error::Error + Send + Sync>;
-type Context<'a> = poise::Context<'a, Data, Error>;
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let command = Cli::parse().command.unwrap_or_default();
+    let _ = dotenvy::dotenv(); // Am discarding the result because I don't actually care if there isn't a literal .env file as long as the environment variable is set
 
-pub struct Data {
-    db_connection: Arc<Mutex<Connection>>,
-    tx: tokio::sync::mpsc::Sender<models::reminder::PersistedReminder>,
-    uuid_context: Arc<std::sync::Mutex<uuid::ContextV7>>,
-}
+    let config = Config::load().unwrap_or_else(|err| exit_with_config_error(err));
+    logging::init_logging(&config.logging);
 
-async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
-    match error {
-        poise::FrameworkError::Setup { error, .. } => panic!("Failed to start bot: {:?}", error),
-        poise::FrameworkError::Command { error, ctx, .. } => {
-            eprintln!("Error in command `{}`: {:?}", ctx.command().name, error,);
-            eprintln!("Error: {:?}", error.to_string());
+    let message_keyring = config
+        .load_message_keyring()
+        .unwrap_or_else(|err| exit_with_config_error(err));
+
+    match command {
+        CliCommand::Run => {
+            if let Err(err) = config.require_discord_token() {
+                exit_with_config_error(err);
+            }
+            run(config).await
         }
-        error => {
-            if let Err(e) = poise::builtins::on_error(error).await {
-                eprintln!("Error while handling error: {}", e)
+        CliCommand::RegisterCommands { global, guild_ids } => {
+            if let Err(err) = config.require_discord_token() {
+                exit_with_config_error(err);
             }
+            cli::register_commands(&config, get_commands().0, global, guild_ids).await
         }
-    }
-}
-
-async fn on_event(
-    ctx: &serenity::Context,
-    event: &FullEvent,
-    framework: FrameworkContext<'_, Data, Error>,
-    data: &Data,
-) -> Result<(), Error> {
-    println!("Received event: {}", event.snake_case_name());
-
-    match event {
-        serenity::FullEvent::ReactionAdd { add_reaction } => {
-            let is_reactor_not_the_bot = add_reaction.user_id != Some(framework.bot_id);
-            let is_message_from_the_bot = add_reaction.message_author_id == Some(framework.bot_id);
-            let is_reaction_emoji_delete = add_reaction.emoji.unicode_eq(DELETE_MESSAGE_EMOJI);
-
-            if is_reactor_not_the_bot && is_message_from_the_bot && is_reaction_emoji_delete {
-                delete_message_with_audit_log_reason(
-                    ctx.http.clone(),
-                    add_reaction.channel_id,
-                    add_reaction.message_id,
-                    &add_reaction
-                        .message_author_id
-                        .map_or("Unknown".to_string(), |id| id.to_string()),
-                )
-                .await?;
+        CliCommand::CheckConfig => {
+            if let Err(err) = config.require_discord_token() {
+                exit_with_config_error(err);
             }
+            println!("The configuration is valid");
+            Ok(())
         }
-        serenity::FullEvent::InteractionCreate { interaction } => {
-            println!("Received interaction: {:?}", interaction);
-            if let Some(component_interaction) = interaction.as_message_component() {
-                match InteractionCustomId::try_from(&component_interaction.data.custom_id[..]) {
-                    Ok(InteractionCustomId::DeleteMessage) => {
-                        delete_message_with_audit_log_reason(
-                            ctx.http.clone(),
-                            component_interaction.channel_id,
-                            component_interaction.message.id,
-                            &component_interaction.user.name,
-                        )
-                        .await?;
-                    }
-                    Ok(InteractionCustomId::SetReminder(bookmark_id)) => {
-                        let db_connection = data.db_connection.clone();
-                        let bookmark =
-                            database::bookmark::get_bookmark_by_id(&db_connection, bookmark_id)
-                                .await?
-                                .unwrap_or_else(|| {
-                                    panic!(
-                                        "Expected bookmark {} to be found in database",
-                                        bookmark_id
-                                    )
-                                });
-                        match &component_interaction.data.kind {
-                            ComponentInteractionDataKind::StringSelect { values } => {
-                                let selected_value = values
-                                                .first()
-                                                .unwrap_or_else(|| panic!("Expected at least one value to be selected, received: {:?}", values));
-                                let reminder_wait_duration = chrono::Duration::from(
-                                    ReminderSelectMenuValue::from_str(selected_value.as_str())
-                                        .unwrap_or_else(|_| {
-                                            panic!(
-                                                "Failed to parse selected value: {}",
-                                                selected_value
-                                            )
-                                        }),
-                                );
-                                assert!(
-                                    component_interaction.user.id == bookmark.user_id(),
-                                    "Expected user ID to match bookmark user ID"
-                                );
-                                let remind_at = chrono::Utc::now() + reminder_wait_duration;
-                                let reminder = models::reminder::Reminder::new(
-                                    bookmark.user_id(),
-                                    bookmark.message().clone(),
-                                    remind_at,
-                                );
-                                let persisted_reminder =
-                                    database::reminder::insert_reminder(&db_connection, reminder)
-                                        .await?;
-                                data.tx.send(persisted_reminder).await?;
-                                component_interaction
-                                    .create_response(
-                                        &ctx.http,
-                                        serenity::CreateInteractionResponse::Message(
-                                            CreateInteractionResponseMessage::new()
-                                                .content(format!(
-                                                    "Reminder set for {}",
-                                                    get_discord_relative_timestamp_string(
-                                                        &remind_at
-                                                    )
-                                                ))
-                                                .ephemeral(true),
-                                        ),
-                                    )
-                                    .await?;
-                            }
-                            _ => {
-                                panic!("Received unexpected interaction data kind for custom id {}: {:?}", component_interaction.data.custom_id, component_interaction.data.kind);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("{}", e);
-                    }
-                }
+        command => {
+            let db_connection = if command.is_read_only() {
+                Connection::open_with_flags(
+                    &config.database.path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                )
+                .await
+                .map_err(|err| {
+                    format!(
+                        "Couldn't open the database at {}: {}",
+                        config.database.path.display(),
+                        err
+                    )
+                })?
+            } else {
+                Connection::open(&config.database.path).await?
+            };
+            let db_connection = Mutex::new(db_connection);
+            if let Some(keyring) = message_keyring {
+                attach_message_keyring(&db_connection, keyring).await?;
             }
+            cli::run_database_command(command, &db_connection).await
         }
-        _ => {}
     }
-
-    Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Error> {
-    let should_reregister_commands_globally = env::args().any(|arg| arg == "--reregister");
-    let _ = dotenvy::dotenv(); // Am discarding the result because I don't actually care if there isn't a literal .env file as long as the environment variable is set
-
-    let (serenity_commands, all_commands) = {
-        let commands = vec![commands::help()];
-        let commands_available_in_dms = vec![
-            commands::get_reminders(),
-            commands::remind_me_in_10_seconds(),
-            commands::bookmark(),
-            commands::get_bookmarks(),
-        ];
-
-        let serenity_commands = [
-            create_application_commands(&commands),
-            create_application_commands(&commands_available_in_dms)
-                .into_iter()
-                .map(|command| {
-                    command
-                        .add_context(serenity::InteractionContext::PrivateChannel)
-                        .add_context(serenity::InteractionContext::BotDm)
-                })
-                .collect(),
-        ]
-        .concat();
-
-        (
-            serenity_commands,
-            commands
-                .into_iter()
-                .chain(commands_available_in_dms)
-                .collect(),
-        )
-    };
-
-    let options = poise::FrameworkOptions {
-        commands: all_commands,
-        on_error: |error| Box::pin(on_error(error)),
-        pre_command: |ctx| {
-            Box::pin(async move {
-                println!("Executing command {}...", ctx.command().qualified_name);
-            })
-        },
-        post_command: |ctx| {
-            Box::pin(async move {
-                println!("Executed command {}!", ctx.command().qualified_name);
-            })
-        },
-        skip_checks_for_owners: false,
-        event_handler: |ctx, event, framework, data| {
-            Box::pin(on_event(ctx, event, framework, data))
-        },
-        ..Default::default()
-    };
+fn exit_with_config_error(err: ConfigError) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
 
-    let db_connection = Arc::new(Mutex::new(Connection::open("./data.db").await.unwrap()));
-    let _ = tokio::try_join!(
-        create_reminders_table_if_nonexistent(&db_connection),
-        create_bookmarks_table_if_nonexistent(&db_connection),
-    )?;
-    let reminders_from_database = get_all_reminders(&db_connection)
-        .await?
-        .into_iter()
-        .map(Reverse)
-        .collect::<Vec<_>>();
+async fn run(config: Config) -> Result<(), Error> {
+    let (serenity_commands, all_commands) = get_commands();
 
-    let reminders_heap = BinaryHeap::from(reminders_from_database);
+    let options = get_framework_options(all_commands);
 
-    let (tx, rx) = tokio::sync::mpsc::channel(32);
+    let discord_token = config.discord_token.clone();
+    let (data, background_tasks) = background_tasks::init(config.clone()).await?;
+    let shutdown = background_tasks.shutdown_token();
+    // The tasks are started once the bot is connected, and are handed back here to be shut down when it stops.
+    // If starting fails instead, the error is handed back so that it's what `run` returns.
+    let (started_tx, mut started_rx) = tokio::sync::oneshot::channel();
 
-    let db_connection_clone = db_connection.clone();
     let framework = poise::Framework::builder()
-        .setup(move |ctx, ready, _framework| {
+        .setup(move |ctx, ready, framework| {
             Box::pin(async move {
-                if should_reregister_commands_globally {
-                    println!("Setting global commands...");
-                    serenity::Command::set_global_commands(ctx.http.clone(), serenity_commands.clone())
-                        .await?;
+                let test_guild_ids = config
+                    .commands
+                    .test_guild_ids
+                    .iter()
+                    .map(|guild_id| serenity::GuildId::new(*guild_id))
+                    .collect::<Vec<_>>();
+                let registration = command_registration::register_commands(
+                    &ctx.http,
+                    &serenity_commands,
+                    config.commands.global_registration == GlobalCommandRegistration::OnStartup,
+                    &test_guild_ids,
+                )
+                .await;
+                if let Err(err) = registration {
+                    // Poise gets a copy to log before shutting down the shards
+                    let message = err.to_string();
+                    let _ = started_tx.send(Err(err));
+                    return Err(message.into());
                 }
 
-                const TEST_GUILD_ID_ENV_VAR_NAME: &str = "TEST_GUILD_ID";
+                let _ = started_tx.send(Ok(
+                    background_tasks.start(ctx, framework.shard_manager().clone())
+                ));
 
-                match env::var(TEST_GUILD_ID_ENV_VAR_NAME) {
-                    Ok(guild_id) => {
-                        let guild_id = serenity::GuildId::new(
-                            guild_id
-                                .parse::<u64>()
-                                .unwrap_or_else(|_| panic!("Environment variable `{}` must be a valid u64", TEST_GUILD_ID_ENV_VAR_NAME))
-                        );
-                        // This will make each command appear twice in the command list specifically only for the test guild
-                        guild_id
-                            .set_commands(ctx.http.clone(), serenity_commands)
-                            .await?;
-                    }
-                    Err(env::VarError::NotPresent) => {
-                        println!(
-                            "Environment variable `{}` not set, skipping guild command registration",
-                            TEST_GUILD_ID_ENV_VAR_NAME
-                        );
-                    }
-                    Err(env::VarError::NotUnicode(_)) => {
-                        panic!("Environment variable `{}` must be a valid UTF-8 string", TEST_GUILD_ID_ENV_VAR_NAME);
-                    }
-                }
-
-                println!("Logged in as {}", ready.user.name);
-                Ok(Data {
-                    db_connection: db_connection_clone,
-                    tx,
-                    uuid_context: Arc::new(std::sync::Mutex::new(uuid::ContextV7::new())),
-                })
+                tracing::info!(user = %ready.user.name, "Logged in");
+                Ok(data)
             })
         })
         .options(options)
         .build();
 
-    const DISCORD_TOKEN_ENV_VAR_NAME: &str = "DISCORD_TOKEN";
-    let token = env::var(DISCORD_TOKEN_ENV_VAR_NAME).unwrap_or_else(|_| {
-        panic!(
-            "Environment variable `{}` must be set",
-            DISCORD_TOKEN_ENV_VAR_NAME
-        )
-    });
     let intents = serenity::GatewayIntents::non_privileged();
 
-    let mut client = serenity::ClientBuilder::new(token, intents)
-        .framework(framework)
-        .await
-        .unwrap();
+    let mut client = serenity::ClientBuilder::new(&discord_token, intents)
+        .framework(logging::InstrumentedFramework(framework))
+        .await?;
 
-    tokio::spawn(send_reminders(
-        client.http.clone(),
-        reminders_heap,
-        db_connection,
-        rx,
+    tokio::spawn(shutdown::shut_down_on_signal(
+        shutdown.clone(),
+        client.shard_manager.clone(),
     ));
 
-    client.start().await.unwrap();
-
-    Ok(())
-}
-
-async fn send_reminders(
-    http: Arc<serenity::Http>,
-    mut reminders: BinaryHeap<Reverse<models::reminder::PersistedReminder>>,
-    db_connection: Arc<Mutex<Connection>>,
-    mut rx: tokio::sync::mpsc::Receiver<models::reminder::PersistedReminder>,
-) -> Result<(), Error> {
-    loop {
-        println!("{} reminders in the heap.", reminders.len());
-        let next_reminder = reminders.pop();
-
-        let sleep_time = next_reminder
-            .as_ref()
-            .map(|r| &r.0)
-            .map(models::reminder::PersistedReminder::get_sleep_time_until_reminder_should_trigger)
-            .unwrap_or(std::time::Duration::from_secs(u64::MAX));
-
-        tokio::select! {
-            _ = tokio::time::sleep(sleep_time) => {
-                if let Some(Reverse(reminder)) = next_reminder {
-                    let channel_name = reminder.message().channel_id.name(&http).await.unwrap_or_else(|err| {
-                        eprintln!("Failed to get channel name for channel ID {}: {}", reminder.message().channel_id, err);
-                        "the past!".to_string()
-                    });
-
-                    let message = get_reminder_message(
-                        &reminder,
-                        &channel_name,
-                        DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
-                        DELETE_MESSAGE_EMOJI
-                    );
-
-                    let user_id = serenity::UserId::new(reminder.user_id());
+    let result = client.start().await;
 
-                    user_id
-                        .create_dm_channel(&http)
-                        .await?
-                        .send_message(&http, message)
-                        .await?;
-
-                    delete_reminder_by_id(&db_connection, reminder.pk())
-                        .await?;
-                }
-            }
-            Some(reminder) = rx.recv() => {
-                println!("Received reminder: {:?}", reminder);
-                if let Some(next_reminder) = next_reminder {
-                    reminders.push(next_reminder);
-                }
-                reminders.push(Reverse(reminder));
-            }
+    match started_rx.try_recv() {
+        Ok(Ok(running_tasks)) => running_tasks.shut_down().await,
+        Ok(Err(err)) => {
+            shutdown.cancel();
+            return Err(err);
         }
+        // Stopped before getting as far as starting them
+        Err(_) => shutdown.cancel(),
     }
-}
-
-async fn delete_message_with_audit_log_reason(
-    http: Arc<serenity::Http>,
-    channel_id: serenity::ChannelId,
-    message_id: serenity::MessageId,
-    responsible_user_name: &str,
-) -> Result<(), Error> {
-    http.delete_message(
-        channel_id,
-        message_id,
-        Some(&get_delete_message_audit_log_reason(responsible_user_name)),
-    )
-    .await?;
 
-    Ok(())
-}
-
-fn get_delete_message_audit_log_reason(responsible_user_name: &str) -> String {
-    format!("Deletion requested by: {}", responsible_user_name)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_delete_message_audit_log_reason() {
-        let responsible_user_name = "TestUser";
-        assert_eq!(
-            get_delete_message_audit_log_reason(responsible_user_name),
-            "Deletion requested by: TestUser"
-        );
-    }
+    Ok(result?)
 }
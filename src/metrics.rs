@@ -0,0 +1,118 @@
+#[cfg(feature = "metrics")]
+mod recorder;
+#[cfg(feature = "metrics")]
+pub mod server;
+
+use poise::serenity_prelude as serenity;
+
+use crate::{models::reminder::PersistedReminder, Error};
+#[cfg(not(feature = "metrics"))]
+pub use disabled::Metrics;
+#[cfg(feature = "metrics")]
+pub use recorder::Metrics;
+
+/// Without the `metrics` feature there's nothing to read metrics, so recording them compiles to nothing
+#[cfg(not(feature = "metrics"))]
+mod disabled {
+    use crate::{models::reminder::PersistedReminder, Error};
+
+    #[derive(Clone, Default)]
+    pub struct Metrics;
+
+    impl Metrics {
+        pub fn new() -> Self {
+            Self
+        }
+
+        pub fn set_scheduler_heap_size(&self, _size: usize) {}
+
+        pub fn record_reminder_queued_for_delivery(&self) {}
+
+        pub fn record_reminder_taken_for_delivery(&self) {}
+
+        pub fn record_reminder_delivered(
+            &self,
+            _reminder: &PersistedReminder,
+            _delivered_at: &chrono::DateTime<chrono::Utc>,
+        ) {
+        }
+
+        pub fn record_delivery_failure(&self, _err: &Error) {}
+
+        pub fn record_bookmark_created(&self) {}
+
+        pub fn record_command_invocation(&self, _command: &str) {}
+
+        pub fn record_error<U, E>(&self, _error: &poise::FrameworkError<'_, U, E>) {}
+    }
+}
+
+/// Coarse enough to keep the number of label values small. Discord refusing a request is split out,
+/// since that's what happens when a user doesn't accept DMs from the bot.
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+fn get_delivery_failure_reason(err: &Error) -> &'static str {
+    match err {
+        Error::Discord(err) => match err.as_ref() {
+            serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response))
+                if response.status_code == serenity::StatusCode::FORBIDDEN =>
+            {
+                "discord_forbidden"
+            }
+            _ => "discord",
+        },
+        Error::Storage(_) => "database",
+        Error::Internal(err)
+            if err
+                .downcast_ref::<tokio::sync::mpsc::error::SendError<PersistedReminder>>()
+                .is_some() =>
+        {
+            "scheduler"
+        }
+        Error::User(_) | Error::Internal(_) => "other",
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+fn get_framework_error_kind<U, E>(error: &poise::FrameworkError<'_, U, E>) -> &'static str {
+    match error {
+        poise::FrameworkError::Setup { .. } => "setup",
+        poise::FrameworkError::EventHandler { .. } => "event_handler",
+        poise::FrameworkError::Command { .. } => "command",
+        poise::FrameworkError::CommandPanic { .. } => "command_panic",
+        poise::FrameworkError::ArgumentParse { .. } => "argument_parse",
+        poise::FrameworkError::CooldownHit { .. } => "cooldown_hit",
+        poise::FrameworkError::MissingBotPermissions { .. }
+        | poise::FrameworkError::MissingUserPermissions { .. } => "missing_permissions",
+        poise::FrameworkError::CommandCheckFailed { .. } => "command_check_failed",
+        poise::FrameworkError::UnknownCommand { .. }
+        | poise::FrameworkError::UnknownInteraction { .. } => "unknown_command",
+        _ => "other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::reminder::Reminder;
+
+    #[test]
+    fn test_get_delivery_failure_reason() {
+        let database_error = Error::from(tokio_rusqlite::Error::ConnectionClosed);
+        assert_eq!(get_delivery_failure_reason(&database_error), "database");
+
+        let discord_error = Error::from(serenity::Error::Other("test"));
+        assert_eq!(get_delivery_failure_reason(&discord_error), "discord");
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<PersistedReminder>();
+        drop(rx);
+        let reminder = PersistedReminder::from_reminder(
+            Reminder::new(1, serenity::Message::default(), chrono::Utc::now()),
+            1,
+        );
+        let scheduler_error = Error::from(tx.send(reminder).unwrap_err());
+        assert_eq!(get_delivery_failure_reason(&scheduler_error), "scheduler");
+
+        let other_error: Error = "test".into();
+        assert_eq!(get_delivery_failure_reason(&other_error), "other");
+    }
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family, gauge::Gauge, histogram::Histogram},
+    registry::Registry,
+};
+
+use super::{get_delivery_failure_reason, get_framework_error_kind};
+use crate::{models::reminder::PersistedReminder, Error};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct CommandLabels {
+    command: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct DeliveryFailureLabels {
+    reason: &'static str,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ErrorLabels {
+    kind: &'static str,
+}
+
+/// The bot's metrics, registered with their own [`Registry`]. Cheap to clone, since every clone records into the same metrics
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Arc<Registry>,
+    scheduler_heap_size: Gauge,
+    reminders_queued_for_delivery: Gauge,
+    delivery_latency_seconds: Histogram,
+    delivery_failures: Family<DeliveryFailureLabels, Counter>,
+    bookmarks_created: Counter,
+    command_invocations: Family<CommandLabels, Counter>,
+    errors: Family<ErrorLabels, Counter>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let mut registry = Registry::with_prefix("attendant");
+
+        let scheduler_heap_size = Gauge::default();
+        registry.register(
+            "scheduler_heap_size",
+            "Reminders waiting in the scheduler for their time to come",
+            scheduler_heap_size.clone(),
+        );
+        let reminders_queued_for_delivery = Gauge::default();
+        registry.register(
+            "reminders_queued_for_delivery",
+            "Reminders that came due and are waiting for a delivery worker",
+            reminders_queued_for_delivery.clone(),
+        );
+        let delivery_latency_seconds =
+            Histogram::new([0.1, 0.5, 1.0, 5.0, 15.0, 60.0, 300.0, 900.0, 3600.0]);
+        registry.register(
+            "delivery_latency_seconds",
+            "How long after they were due reminders were delivered",
+            delivery_latency_seconds.clone(),
+        );
+        let delivery_failures = Family::default();
+        registry.register(
+            "delivery_failures",
+            "Reminders that failed to be delivered",
+            delivery_failures.clone(),
+        );
+        let bookmarks_created = Counter::default();
+        registry.register(
+            "bookmarks_created",
+            "Bookmarks created",
+            bookmarks_created.clone(),
+        );
+        let command_invocations = Family::default();
+        registry.register(
+            "command_invocations",
+            "Commands run",
+            command_invocations.clone(),
+        );
+        let errors = Family::default();
+        registry.register(
+            "errors",
+            "Errors handled by the framework's error handler",
+            errors.clone(),
+        );
+
+        Self {
+            registry: Arc::new(registry),
+            scheduler_heap_size,
+            reminders_queued_for_delivery,
+            delivery_latency_seconds,
+            delivery_failures,
+            bookmarks_created,
+            command_invocations,
+            errors,
+        }
+    }
+
+    pub fn set_scheduler_heap_size(&self, size: usize) {
+        self.scheduler_heap_size.set(size as i64);
+    }
+
+    pub fn record_reminder_queued_for_delivery(&self) {
+        self.reminders_queued_for_delivery.inc();
+    }
+
+    pub fn record_reminder_taken_for_delivery(&self) {
+        self.reminders_queued_for_delivery.dec();
+    }
+
+    pub fn record_reminder_delivered(
+        &self,
+        reminder: &PersistedReminder,
+        delivered_at: &chrono::DateTime<chrono::Utc>,
+    ) {
+        let latency = delivered_at.signed_duration_since(*reminder.remind_at());
+        self.delivery_latency_seconds
+            .observe(latency.num_milliseconds().max(0) as f64 / 1000.0);
+    }
+
+    pub fn record_delivery_failure(&self, err: &Error) {
+        self.delivery_failures
+            .get_or_create(&DeliveryFailureLabels {
+                reason: get_delivery_failure_reason(err),
+            })
+            .inc();
+    }
+
+    pub fn record_bookmark_created(&self) {
+        self.bookmarks_created.inc();
+    }
+
+    pub fn record_command_invocation(&self, command: &str) {
+        self.command_invocations
+            .get_or_create(&CommandLabels {
+                command: command.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn record_error<U, E>(&self, error: &poise::FrameworkError<'_, U, E>) {
+        self.errors
+            .get_or_create(&ErrorLabels {
+                kind: get_framework_error_kind(error),
+            })
+            .inc();
+    }
+
+    /// In the Prometheus text format
+    pub fn encode(&self) -> Result<String, std::fmt::Error> {
+        let mut encoded = String::new();
+        prometheus_client::encoding::text::encode(&mut encoded, &self.registry)?;
+
+        Ok(encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_metrics() {
+        let metrics = Metrics::new();
+        metrics.record_command_invocation("bookmark");
+
+        let encoded = metrics.encode().unwrap();
+
+        assert!(encoded.contains("attendant_command_invocations_total{command=\"bookmark\"}"));
+        assert!(encoded.contains("# TYPE attendant_delivery_latency_seconds histogram"));
+        assert!(encoded.ends_with("# EOF\n"));
+    }
+}
@@ -0,0 +1,181 @@
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use poise::serenity_prelude as serenity;
+use tokio::sync::{mpsc, Mutex};
+use tokio_rusqlite::Connection;
+use tokio_util::sync::CancellationToken;
+
+use crate::{metrics::Metrics, models::reminder::PersistedReminder, Error};
+
+/// How long `/healthz` waits for the database before considering it unreachable,
+/// since the connection is shared with everything else the bot is doing
+const DATABASE_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What `/healthz` checks
+pub struct HealthChecks {
+    pub shard_manager: Arc<serenity::ShardManager>,
+    pub db_connection: Arc<Mutex<Connection>>,
+    /// Only used to tell whether the scheduler is still receiving reminders
+    pub scheduler_tx: mpsc::Sender<PersistedReminder>,
+    /// Served on `/metrics`
+    pub metrics: Metrics,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HealthReport {
+    gateway_connected: bool,
+    scheduler_alive: bool,
+    database_reachable: bool,
+}
+
+impl HealthReport {
+    fn is_healthy(&self) -> bool {
+        self.gateway_connected && self.scheduler_alive && self.database_reachable
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "healthy": self.is_healthy(),
+            "gateway_connected": self.gateway_connected,
+            "scheduler_alive": self.scheduler_alive,
+            "database_reachable": self.database_reachable,
+        })
+    }
+}
+
+impl HealthChecks {
+    async fn run(&self) -> HealthReport {
+        HealthReport {
+            gateway_connected: self.is_gateway_connected().await,
+            scheduler_alive: !self.scheduler_tx.is_closed(),
+            database_reachable: self.is_database_reachable().await,
+        }
+    }
+
+    async fn is_gateway_connected(&self) -> bool {
+        let runners = self.shard_manager.runners.lock().await;
+
+        !runners.is_empty()
+            && runners
+                .values()
+                .all(|runner| runner.stage == serenity::ConnectionStage::Connected)
+    }
+
+    async fn is_database_reachable(&self) -> bool {
+        let query = async {
+            self.db_connection
+                .lock()
+                .await
+                .call(|conn| Ok(conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))?))
+                .await
+        };
+
+        matches!(
+            tokio::time::timeout(DATABASE_HEALTH_CHECK_TIMEOUT, query).await,
+            Ok(Ok(1))
+        )
+    }
+}
+
+/// Serves `/healthz` and `/metrics` until `shutdown` is cancelled. Meant to be bound to a local address,
+/// since neither endpoint is authenticated.
+pub async fn serve(
+    address: SocketAddr,
+    health_checks: HealthChecks,
+    shutdown: CancellationToken,
+) -> Result<(), Error> {
+    let health_checks = Arc::new(health_checks);
+    let make_service = make_service_fn(move |_| {
+        let health_checks = health_checks.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let health_checks = health_checks.clone();
+                async move { Ok::<_, Infallible>(handle_request(request, &health_checks).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&address)?
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown.cancelled_owned());
+    tracing::info!(%address, "Serving health checks and metrics");
+    server.await?;
+
+    Ok(())
+}
+
+async fn handle_request(request: Request<Body>, health_checks: &HealthChecks) -> Response<Body> {
+    if request.method() != Method::GET {
+        return get_response(StatusCode::METHOD_NOT_ALLOWED, "text/plain", "");
+    }
+
+    match request.uri().path() {
+        "/healthz" => {
+            let report = health_checks.run().await;
+            let status = if report.is_healthy() {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            get_response(status, "application/json", report.to_json().to_string())
+        }
+        "/metrics" => match health_checks.metrics.encode() {
+            Ok(metrics) => get_response(
+                StatusCode::OK,
+                "application/openmetrics-text; version=1.0.0; charset=utf-8",
+                metrics,
+            ),
+            Err(err) => {
+                tracing::error!(error = %err, "Failed to encode metrics");
+                get_response(StatusCode::INTERNAL_SERVER_ERROR, "text/plain", "")
+            }
+        },
+        _ => get_response(StatusCode::NOT_FOUND, "text/plain", ""),
+    }
+}
+
+fn get_response(status: StatusCode, content_type: &str, body: impl Into<Body>) -> Response<Body> {
+    let mut response = Response::new(body.into());
+    *response.status_mut() = status;
+    if let Ok(content_type) = content_type.parse() {
+        response
+            .headers_mut()
+            .insert(hyper::header::CONTENT_TYPE, content_type);
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_report() {
+        let healthy_report = HealthReport {
+            gateway_connected: true,
+            scheduler_alive: true,
+            database_reachable: true,
+        };
+        assert!(healthy_report.is_healthy());
+
+        let unhealthy_report = HealthReport {
+            gateway_connected: false,
+            ..healthy_report
+        };
+        assert!(!unhealthy_report.is_healthy());
+        assert_eq!(
+            unhealthy_report.to_json(),
+            serde_json::json!({
+                "healthy": false,
+                "gateway_connected": false,
+                "scheduler_alive": true,
+                "database_reachable": true,
+            })
+        );
+    }
+}
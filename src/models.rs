@@ -1,2 +1,79 @@
+//! The bot's data, as stored in the database.
+//!
+//! Each stored model's `from_row` takes one argument per column, in the table's order, so it can be checked against
+//! the table at a glance, even where that means expecting `clippy::too_many_arguments`.
+
 pub mod bookmark;
+pub mod bookmark_review;
+pub mod countdown;
+pub mod guild_settings;
+pub mod message_link;
+pub mod reaction_shortcut;
 pub mod reminder;
+pub mod user_settings;
+
+use poise::serenity_prelude as serenity;
+
+/// Declares the error for a model's columns failing to parse, with one variant per column holding the underlying error.
+/// Each variant is given as `Variant(Error) => "column_name", "what the column holds"`.
+macro_rules! parse_row_error {
+    ($name:ident { $($variant:ident($error:ty) => $column_name:literal, $description:literal),+ $(,)? }) => {
+        #[derive(Debug)]
+        pub enum $name {
+            $($variant($error)),+
+        }
+
+        impl $name {
+            /// The column holding the value that failed to parse
+            pub fn column_name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant(_) => $column_name),+
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $(Self::$variant(err) => write!(f, concat!("invalid ", $description, ": {}"), err)),+
+                }
+            }
+        }
+
+        impl std::error::Error for $name {}
+    };
+}
+pub(crate) use parse_row_error;
+
+/// Keeps only what's needed to link to `message` and fetch it again later.
+/// Used when privacy mode means the message's content mustn't be stored.
+pub fn get_message_without_content(message: &serenity::Message) -> serenity::Message {
+    let mut message_without_content = serenity::Message::default();
+    message_without_content.id = message.id;
+    message_without_content.channel_id = message.channel_id;
+    message_without_content.guild_id = message.guild_id;
+    message_without_content.timestamp = message.timestamp;
+    message_without_content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_message_without_content() {
+        let mut message = serenity::Message::default();
+        message.id = 3.into();
+        message.channel_id = 2.into();
+        message.guild_id = Some(1.into());
+        message.content = "Something sensitive".to_string();
+        message.author.name = "someone_else".to_string();
+
+        let message_without_content = get_message_without_content(&message);
+
+        assert_eq!(message_without_content.link(), message.link());
+        assert_eq!(message_without_content.timestamp, message.timestamp);
+        assert_eq!(message_without_content.content, "");
+        assert_eq!(message_without_content.author.name, "");
+    }
+}
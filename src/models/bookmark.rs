@@ -2,11 +2,18 @@ use poise::serenity_prelude as serenity;
 use rusqlite::Row;
 use uuid::Uuid;
 
+use crate::{
+    database::message_encryption::get_message_column,
+    models::{bookmark_review::ReviewStep, get_message_without_content, parse_row_error},
+};
+
 #[derive(Debug)]
 pub struct BookmarkedMessage {
     bookmark_id: Uuid,
     user_id: u64,
     message: serenity::Message,
+    /// Whether `message` was stripped down to its IDs because of privacy mode
+    content_omitted: bool,
 }
 
 #[derive(Debug)]
@@ -17,24 +24,58 @@ pub struct PersistedBookmarkedMessage {
     bookmark_id: Uuid,
     user_id: u64,
     message: serenity::Message,
+    /// Whether `message` was stripped down to its IDs because of privacy mode
+    content_omitted: bool,
+    /// Whether `message` was stripped down to its IDs by the retention policy
+    content_purged: bool,
+    /// Starred bookmarks never expire, regardless of the retention policy
+    starred: bool,
+    /// Whether the user is done with it, for those who use their bookmarks as a reading list
+    read: bool,
+    /// Where the bookmark is in its spaced repetition schedule, or [`None`] if it's never been resurfaced
+    review_step: Option<ReviewStep>,
 }
 
-#[derive(Debug)]
-pub enum ParseBookmarkedMessageError {
-    #[expect(dead_code)]
-    BookmarkId(uuid::Error),
-    #[expect(dead_code)]
-    UserId(std::num::ParseIntError),
-    #[expect(dead_code)]
-    Message(serde_json::Error),
+/// How many bookmarks a user has, and how many of them they haven't read yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BookmarkCounts {
+    total: u64,
+    unread: u64,
 }
 
+/// What the retention policy needs to know about a bookmark, which can be read without its (possibly encrypted) message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookmarkRetentionInfo {
+    pk: i64,
+    bookmark_id: Uuid,
+    user_id: u64,
+    starred: bool,
+    /// Whether the stored message still has its content, i.e. it was neither omitted nor purged
+    has_message_content: bool,
+}
+
+parse_row_error!(ParseBookmarkedMessageError {
+    BookmarkId(uuid::Error) => "bookmark_id", "bookmark ID",
+    UserId(std::num::ParseIntError) => "user_id", "user ID",
+    Message(serde_json::Error) => "message", "message",
+});
+
 impl BookmarkedMessage {
     pub fn new(bookmark_id: Uuid, user_id: u64, message: serenity::Message) -> Self {
         Self {
             bookmark_id,
             user_id,
             message,
+            content_omitted: false,
+        }
+    }
+
+    /// Strips the message down to what's needed to fetch it again whenever the bookmark is shown
+    pub fn without_message_content(self) -> Self {
+        Self {
+            message: get_message_without_content(&self.message),
+            content_omitted: true,
+            ..self
         }
     }
 
@@ -49,6 +90,10 @@ impl BookmarkedMessage {
     pub fn message(&self) -> &serenity::Message {
         &self.message
     }
+
+    pub fn is_content_omitted(&self) -> bool {
+        self.content_omitted
+    }
 }
 
 impl PersistedBookmarkedMessage {
@@ -58,14 +103,25 @@ impl PersistedBookmarkedMessage {
             bookmark_id: bookmark.bookmark_id,
             user_id: bookmark.user_id,
             message: bookmark.message,
+            content_omitted: bookmark.content_omitted,
+            content_purged: false,
+            starred: false,
+            read: false,
+            review_step: None,
         }
     }
 
+    #[expect(clippy::too_many_arguments)]
     pub fn from_row(
         pk: i64,
         bookmark_id: String, // ideally, a uuid string
         user_id: String,     // Sqlite integers are signed
         message: String,     // ideally, a json string
+        content_omitted: bool,
+        content_purged: bool,
+        starred: bool,
+        read: bool,
+        review_step: Option<u32>,
     ) -> Result<Self, ParseBookmarkedMessageError> {
         let bookmark_id =
             Uuid::parse_str(&bookmark_id).map_err(ParseBookmarkedMessageError::BookmarkId)?;
@@ -82,10 +138,14 @@ impl PersistedBookmarkedMessage {
             bookmark_id,
             user_id,
             message,
+            content_omitted,
+            content_purged,
+            starred,
+            read,
+            review_step: review_step.map(|step| ReviewStep::from_index(step as usize)),
         })
     }
 
-    #[expect(dead_code)]
     pub fn pk(&self) -> i64 {
         self.pk
     }
@@ -101,6 +161,93 @@ impl PersistedBookmarkedMessage {
     pub fn message(&self) -> &serenity::Message {
         &self.message
     }
+
+    /// Whether the stored message has no content, either because of privacy mode or because it was purged
+    pub fn is_content_omitted(&self) -> bool {
+        self.content_omitted || self.content_purged
+    }
+
+    pub fn is_starred(&self) -> bool {
+        self.starred
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read
+    }
+
+    pub fn review_step(&self) -> Option<ReviewStep> {
+        self.review_step
+    }
+
+    /// When the bookmark was created, as recorded in its UUIDv7 [`Self::bookmark_id`]
+    pub fn created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        get_bookmark_created_at(self.bookmark_id)
+    }
+
+    /// Replaces the stored message, e.g. with a freshly fetched copy of a message whose content wasn't stored
+    pub fn with_message(self, message: serenity::Message) -> Self {
+        Self { message, ..self }
+    }
+}
+
+impl BookmarkRetentionInfo {
+    pub fn new(
+        pk: i64,
+        bookmark_id: Uuid,
+        user_id: u64,
+        starred: bool,
+        has_message_content: bool,
+    ) -> Self {
+        Self {
+            pk,
+            bookmark_id,
+            user_id,
+            starred,
+            has_message_content,
+        }
+    }
+
+    pub fn pk(&self) -> i64 {
+        self.pk
+    }
+
+    pub fn user_id(&self) -> u64 {
+        self.user_id
+    }
+
+    pub fn is_starred(&self) -> bool {
+        self.starred
+    }
+
+    pub fn has_message_content(&self) -> bool {
+        self.has_message_content
+    }
+
+    /// See [`PersistedBookmarkedMessage::created_at`]
+    pub fn created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        get_bookmark_created_at(self.bookmark_id)
+    }
+}
+
+fn get_bookmark_created_at(bookmark_id: Uuid) -> Option<chrono::DateTime<chrono::Utc>> {
+    bookmark_id.get_timestamp().and_then(|timestamp| {
+        let (seconds, nanoseconds) = timestamp.to_unix();
+        chrono::DateTime::from_timestamp(seconds as i64, nanoseconds)
+    })
+}
+
+impl BookmarkCounts {
+    pub fn new(total: u64, unread: u64) -> Self {
+        Self { total, unread }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn unread(&self) -> u64 {
+        self.unread
+    }
 }
 
 #[derive(Debug)]
@@ -125,11 +272,52 @@ impl TryFrom<&Row<'_>> for PersistedBookmarkedMessage {
     type Error = PersistedBookmarkedMessageFromRowError;
 
     fn try_from(row: &Row) -> Result<Self, Self::Error> {
-        let pk: i64 = row.get(0)?;
-        let bookmark_id: String = row.get(1)?;
-        let user_id: String = row.get(2)?;
-        let message: String = row.get(3)?;
+        let pk: i64 = row.get("pk")?;
+        let bookmark_id: String = row.get("bookmark_id")?;
+        let user_id: String = row.get("user_id")?;
+        let message: String = get_message_column(row, "message")?;
+        let content_omitted: bool = row.get("content_omitted")?;
+        let content_purged: bool = row.get("content_purged")?;
+        let starred: bool = row.get("starred")?;
+        let read: bool = row.get("read")?;
+        let review_step: Option<u32> = row.get("review_step")?;
+
+        Ok(Self::from_row(
+            pk,
+            bookmark_id,
+            user_id,
+            message,
+            content_omitted,
+            content_purged,
+            starred,
+            read,
+            review_step,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_created_at_is_read_from_bookmark_id() {
+        let bookmark = PersistedBookmarkedMessage::from_bookmarked_message(
+            BookmarkedMessage::new(
+                Uuid::new_v7(uuid::Timestamp::from_unix(
+                    uuid::NoContext,
+                    1_700_000_000,
+                    0,
+                )),
+                123456789,
+                serenity::Message::default(),
+            ),
+            1,
+        );
 
-        Ok(Self::from_row(pk, bookmark_id, user_id, message)?)
+        assert_eq!(
+            bookmark.created_at(),
+            chrono::DateTime::from_timestamp(1_700_000_000, 0)
+        );
     }
 }
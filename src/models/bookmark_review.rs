@@ -0,0 +1,74 @@
+/// How long until a bookmark comes up for review again, by how many times in a row it's been remembered.
+/// Each interval is roughly three times the last, and the last one repeats once it's reached.
+const REVIEW_INTERVAL_DAYS: [i64; 6] = [1, 3, 7, 21, 60, 180];
+
+/// Where a bookmark is in its spaced repetition schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReviewStep(usize);
+
+/// The buttons on a bookmark review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewAnswer {
+    GotIt,
+    ShowSooner,
+}
+
+impl ReviewStep {
+    /// Where newly bookmarked messages start
+    pub const FIRST: Self = Self(0);
+
+    /// The inverse of [`Self::index`]. Steps past the end of the schedule are treated as its last step.
+    pub fn from_index(index: usize) -> Self {
+        Self(index.min(REVIEW_INTERVAL_DAYS.len() - 1))
+    }
+
+    /// How this is stored
+    pub fn index(self) -> usize {
+        self.0
+    }
+
+    /// How long to wait before the next review
+    pub fn interval(self) -> chrono::Duration {
+        chrono::Duration::days(REVIEW_INTERVAL_DAYS[self.0])
+    }
+
+    /// Remembering a bookmark moves it on to a longer interval, while asking to see it sooner moves it back
+    pub fn after(self, answer: ReviewAnswer) -> Self {
+        match answer {
+            ReviewAnswer::GotIt => Self::from_index(self.0 + 1),
+            ReviewAnswer::ShowSooner => Self(self.0.saturating_sub(1)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_intervals_grow_with_each_step() {
+        let intervals = (0..REVIEW_INTERVAL_DAYS.len())
+            .map(|index| ReviewStep::from_index(index).interval().num_days())
+            .collect::<Vec<_>>();
+
+        assert_eq!(intervals, vec![1, 3, 7, 21, 60, 180]);
+        assert_eq!(ReviewStep::from_index(100).interval().num_days(), 180);
+    }
+
+    #[test]
+    fn test_review_step_after_answer() {
+        let step = ReviewStep::FIRST;
+        assert_eq!(step.after(ReviewAnswer::ShowSooner), ReviewStep::FIRST);
+        assert_eq!(step.after(ReviewAnswer::GotIt).index(), 1);
+        assert_eq!(
+            step.after(ReviewAnswer::GotIt)
+                .after(ReviewAnswer::GotIt)
+                .after(ReviewAnswer::ShowSooner)
+                .index(),
+            1
+        );
+
+        let last_step = ReviewStep::from_index(REVIEW_INTERVAL_DAYS.len() - 1);
+        assert_eq!(last_step.after(ReviewAnswer::GotIt), last_step);
+    }
+}
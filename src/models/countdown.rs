@@ -0,0 +1,269 @@
+use chrono::TimeZone;
+use chrono_tz::Tz;
+use poise::serenity_prelude as serenity;
+use uuid::Uuid;
+
+use crate::models::reminder::{CountdownLink, Reminder};
+
+/// A deadline plus how long before it the user wants to be alerted.
+/// Each offset becomes its own [`Reminder`], linked to the others by [`CountdownLink`].
+#[derive(Debug)]
+pub struct Countdown {
+    countdown_id: Uuid,
+    deadline: chrono::DateTime<chrono::Utc>,
+    offsets: Vec<chrono::Duration>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseOffsetsError {
+    Empty,
+    InvalidOffset(String),
+    TooMany,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseDeadlineError {
+    InvalidFormat(String),
+    /// The local time doesn't exist in the user's timezone (e.g. it's skipped by a DST change)
+    NonexistentLocalTime(String),
+}
+
+/// Alerts beyond this many would be more spam than help
+pub const MAX_COUNTDOWN_OFFSETS: usize = 10;
+/// Format for deadlines entered in the user's local time
+pub const DEADLINE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+impl Countdown {
+    pub fn new(
+        countdown_id: Uuid,
+        deadline: chrono::DateTime<chrono::Utc>,
+        offsets: Vec<chrono::Duration>,
+    ) -> Self {
+        Self {
+            countdown_id,
+            deadline,
+            offsets,
+        }
+    }
+
+    pub fn countdown_id(&self) -> Uuid {
+        self.countdown_id
+    }
+
+    pub fn deadline(&self) -> &chrono::DateTime<chrono::Utc> {
+        &self.deadline
+    }
+
+    /// Expands the countdown into one reminder per offset, earliest first.
+    /// Alerts that would already be in the past at `now` are skipped, as are duplicates.
+    pub fn get_alerts(
+        &self,
+        user_id: u64,
+        message: &serenity::Message,
+        now: &chrono::DateTime<chrono::Utc>,
+    ) -> Vec<Reminder> {
+        let mut alert_times = self
+            .offsets
+            .iter()
+            .map(|offset| self.deadline - *offset)
+            .filter(|alert_time| alert_time > now)
+            .collect::<Vec<_>>();
+        alert_times.sort();
+        alert_times.dedup();
+
+        let link = CountdownLink::new(self.countdown_id, self.deadline);
+        alert_times
+            .into_iter()
+            .map(|alert_time| {
+                Reminder::new_countdown_alert(user_id, message.clone(), alert_time, link)
+            })
+            .collect()
+    }
+}
+
+/// Parses a list of offsets like `1d, 1h, 10m`. Each offset may combine units (e.g. `1h30m`),
+/// using `w` (weeks), `d` (days), `h` (hours), `m` (minutes) or `s` (seconds).
+pub fn parse_offsets(input: &str) -> Result<Vec<chrono::Duration>, ParseOffsetsError> {
+    let offsets = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|offset| !offset.is_empty())
+        .map(|offset| {
+            parse_offset(offset).ok_or_else(|| ParseOffsetsError::InvalidOffset(offset.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if offsets.is_empty() {
+        return Err(ParseOffsetsError::Empty);
+    }
+    if offsets.len() > MAX_COUNTDOWN_OFFSETS {
+        return Err(ParseOffsetsError::TooMany);
+    }
+
+    Ok(offsets)
+}
+
+/// Parses a single offset like `1h30m`, see [`parse_offsets`]
+pub fn parse_offset(offset: &str) -> Option<chrono::Duration> {
+    let mut total = chrono::Duration::zero();
+    let mut digits = String::new();
+
+    for c in offset.to_lowercase().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let amount = digits.parse::<i64>().ok()?;
+        digits.clear();
+        let duration = match c {
+            'w' => chrono::Duration::try_weeks(amount),
+            'd' => chrono::Duration::try_days(amount),
+            'h' => chrono::Duration::try_hours(amount),
+            'm' => chrono::Duration::try_minutes(amount),
+            's' => chrono::Duration::try_seconds(amount),
+            _ => None,
+        }?;
+        total = total.checked_add(&duration)?;
+    }
+
+    // Trailing digits without a unit are ambiguous, so they're rejected
+    digits.is_empty().then_some(total)
+}
+
+/// Parses either a Discord timestamp (e.g. `<t:1735689600:R>`, as produced by the "Copy timestamp" feature)
+/// or a local date and time in [`DEADLINE_FORMAT`] interpreted in `timezone`.
+pub fn parse_deadline(
+    input: &str,
+    timezone: Tz,
+) -> Result<chrono::DateTime<chrono::Utc>, ParseDeadlineError> {
+    let input = input.trim();
+
+    if let Some(timestamp) = input
+        .strip_prefix("<t:")
+        .and_then(|rest| rest.strip_suffix('>'))
+    {
+        let seconds = timestamp.split(':').next().unwrap_or_default();
+        return seconds
+            .parse::<i64>()
+            .ok()
+            .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+            .ok_or_else(|| ParseDeadlineError::InvalidFormat(input.to_string()));
+    }
+
+    let local_deadline = chrono::NaiveDateTime::parse_from_str(input, DEADLINE_FORMAT)
+        .map_err(|_| ParseDeadlineError::InvalidFormat(input.to_string()))?;
+
+    timezone
+        .from_local_datetime(&local_deadline)
+        .earliest()
+        .map(|deadline| deadline.with_timezone(&chrono::Utc))
+        .ok_or_else(|| ParseDeadlineError::NonexistentLocalTime(input.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+    ) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_offsets() {
+        assert_eq!(
+            parse_offsets("1d, 1h, 10m").unwrap(),
+            vec![
+                chrono::Duration::days(1),
+                chrono::Duration::hours(1),
+                chrono::Duration::minutes(10)
+            ]
+        );
+        assert_eq!(
+            parse_offsets("1H30m 2w").unwrap(),
+            vec![chrono::Duration::minutes(90), chrono::Duration::weeks(2)]
+        );
+        assert_eq!(parse_offsets(" , "), Err(ParseOffsetsError::Empty));
+        assert_eq!(
+            parse_offsets("1d, 10"),
+            Err(ParseOffsetsError::InvalidOffset("10".to_string()))
+        );
+        assert_eq!(
+            parse_offsets("1y"),
+            Err(ParseOffsetsError::InvalidOffset("1y".to_string()))
+        );
+        assert_eq!(
+            parse_offsets("1m 2m 3m 4m 5m 6m 7m 8m 9m 10m 11m"),
+            Err(ParseOffsetsError::TooMany)
+        );
+    }
+
+    #[test]
+    fn test_parse_deadline() {
+        assert_eq!(
+            parse_deadline("<t:1735689600:R>", Tz::UTC).unwrap(),
+            utc(2025, 1, 1, 0, 0)
+        );
+        assert_eq!(
+            parse_deadline("<t:1735689600>", Tz::UTC).unwrap(),
+            utc(2025, 1, 1, 0, 0)
+        );
+        assert_eq!(
+            parse_deadline("2025-01-01 09:30", chrono_tz::Europe::Berlin).unwrap(),
+            utc(2025, 1, 1, 8, 30)
+        );
+        assert_eq!(
+            parse_deadline("tomorrow", Tz::UTC),
+            Err(ParseDeadlineError::InvalidFormat("tomorrow".to_string()))
+        );
+        // Clocks in Berlin skipped from 02:00 to 03:00 on this date
+        assert_eq!(
+            parse_deadline("2025-03-30 02:30", chrono_tz::Europe::Berlin),
+            Err(ParseDeadlineError::NonexistentLocalTime(
+                "2025-03-30 02:30".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_countdown_get_alerts() {
+        let deadline = utc(2025, 1, 2, 12, 0);
+        let countdown = Countdown::new(
+            Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            deadline,
+            vec![
+                chrono::Duration::minutes(10),
+                chrono::Duration::days(1),
+                chrono::Duration::hours(1),
+                chrono::Duration::minutes(60),
+                // Already in the past at `now`
+                chrono::Duration::days(7),
+            ],
+        );
+        let now = utc(2025, 1, 1, 0, 0);
+
+        let alerts = countdown.get_alerts(123456789, &serenity::Message::default(), &now);
+
+        let alert_times = alerts
+            .iter()
+            .map(|alert| *alert.remind_at())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            alert_times,
+            vec![
+                utc(2025, 1, 1, 12, 0),
+                utc(2025, 1, 2, 11, 0),
+                utc(2025, 1, 2, 11, 50)
+            ]
+        );
+        assert!(alerts.iter().all(|alert| alert.countdown()
+            == Some(&CountdownLink::new(countdown.countdown_id(), deadline))));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::models::{
+    parse_row_error,
+    reaction_shortcut::{InvalidReactionShortcutEmojisError, ReactionShortcutEmojis},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildSettings {
+    guild_id: u64,
+    /// When enabled, only the IDs of messages from this guild are stored when its members
+    /// bookmark them or set reminders on them, regardless of the members' own settings
+    privacy_mode: bool,
+    /// Whether members can bookmark messages and set reminders on them by reacting with [`Self::reaction_shortcut_emojis`]
+    reaction_shortcuts_enabled: bool,
+    reaction_shortcut_emojis: ReactionShortcutEmojis,
+}
+
+parse_row_error!(ParseGuildSettingsError {
+    GuildId(std::num::ParseIntError) => "guild_id", "guild ID",
+    // Any of the emoji columns could be at fault, e.g. for duplicates, so this is just the first one
+    ReactionShortcutEmojis(InvalidReactionShortcutEmojisError) => "bookmark_reaction_emoji", "reaction shortcut emojis",
+});
+
+impl GuildSettings {
+    /// The settings a guild has before its moderators have changed anything
+    pub fn new(guild_id: u64) -> Self {
+        Self {
+            guild_id,
+            privacy_mode: false,
+            reaction_shortcuts_enabled: true,
+            reaction_shortcut_emojis: ReactionShortcutEmojis::default(),
+        }
+    }
+
+    /// Missing emojis are the defaults
+    pub fn from_row(
+        guild_id: String, // Sqlite integers are signed
+        privacy_mode: bool,
+        reaction_shortcuts_enabled: bool,
+        bookmark_reaction_emoji: Option<String>,
+        remind_reaction_emoji: Option<String>,
+        remind_tomorrow_reaction_emoji: Option<String>,
+    ) -> Result<Self, ParseGuildSettingsError> {
+        let guild_id = guild_id
+            .parse::<u64>()
+            .map_err(ParseGuildSettingsError::GuildId)?;
+
+        let defaults = ReactionShortcutEmojis::default();
+        let reaction_shortcut_emojis = ReactionShortcutEmojis::new(
+            bookmark_reaction_emoji
+                .as_deref()
+                .unwrap_or(defaults.bookmark()),
+            remind_reaction_emoji
+                .as_deref()
+                .unwrap_or(defaults.remind()),
+            remind_tomorrow_reaction_emoji
+                .as_deref()
+                .unwrap_or(defaults.remind_tomorrow()),
+        )
+        .map_err(ParseGuildSettingsError::ReactionShortcutEmojis)?;
+
+        Ok(Self {
+            guild_id,
+            privacy_mode,
+            reaction_shortcuts_enabled,
+            reaction_shortcut_emojis,
+        })
+    }
+
+    pub fn guild_id(&self) -> u64 {
+        self.guild_id
+    }
+
+    pub fn privacy_mode(&self) -> bool {
+        self.privacy_mode
+    }
+
+    pub fn set_privacy_mode(&mut self, privacy_mode: bool) {
+        self.privacy_mode = privacy_mode;
+    }
+
+    pub fn reaction_shortcuts_enabled(&self) -> bool {
+        self.reaction_shortcuts_enabled
+    }
+
+    pub fn set_reaction_shortcuts_enabled(&mut self, reaction_shortcuts_enabled: bool) {
+        self.reaction_shortcuts_enabled = reaction_shortcuts_enabled;
+    }
+
+    pub fn reaction_shortcut_emojis(&self) -> &ReactionShortcutEmojis {
+        &self.reaction_shortcut_emojis
+    }
+
+    pub fn set_reaction_shortcut_emojis(
+        &mut self,
+        reaction_shortcut_emojis: ReactionShortcutEmojis,
+    ) {
+        self.reaction_shortcut_emojis = reaction_shortcut_emojis;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guild_settings_from_row() {
+        let settings = GuildSettings::from_row(
+            "123456789".to_string(),
+            true,
+            false,
+            Some("⭐".to_string()),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(settings.guild_id(), 123456789);
+        assert!(settings.privacy_mode());
+        assert!(!settings.reaction_shortcuts_enabled());
+        assert_eq!(
+            settings.reaction_shortcut_emojis(),
+            &ReactionShortcutEmojis::new("⭐", "⏰", "📌").unwrap()
+        );
+
+        assert!(
+            GuildSettings::from_row("not_a_number".to_string(), true, true, None, None, None)
+                .is_err()
+        );
+        assert!(GuildSettings::from_row(
+            "123456789".to_string(),
+            true,
+            true,
+            Some("⏰".to_string()),
+            None,
+            None
+        )
+        .is_err());
+    }
+}
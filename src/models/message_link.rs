@@ -0,0 +1,160 @@
+use poise::serenity_prelude as serenity;
+
+/// Hosts Discord serves message links from, e.g. when using "Copy Message Link" on the PTB or Canary clients
+const MESSAGE_LINK_HOSTS: [&str; 4] = [
+    "discord.com",
+    "ptb.discord.com",
+    "canary.discord.com",
+    "discordapp.com",
+];
+
+/// Where a message link (as produced by [`serenity::Message::link`]) points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageLink {
+    /// [`None`] for messages outside of guilds, whose links have `@me` in its place
+    guild_id: Option<serenity::GuildId>,
+    channel_id: serenity::ChannelId,
+    message_id: serenity::MessageId,
+}
+
+impl MessageLink {
+    pub fn guild_id(&self) -> Option<serenity::GuildId> {
+        self.guild_id
+    }
+
+    pub fn channel_id(&self) -> serenity::ChannelId {
+        self.channel_id
+    }
+
+    pub fn message_id(&self) -> serenity::MessageId {
+        self.message_id
+    }
+}
+
+/// Parses links like `https://discord.com/channels/<guild ID or @me>/<channel ID>/<message ID>`.
+/// The scheme may be left out and the link may be wrapped in `<>` (which Discord uses to suppress embeds).
+pub fn parse_message_link(input: &str) -> Option<MessageLink> {
+    let input = input.trim();
+    let link = input
+        .strip_prefix('<')
+        .and_then(|link| link.strip_suffix('>'))
+        .unwrap_or(input);
+    let link = link
+        .strip_prefix("https://")
+        .or_else(|| link.strip_prefix("http://"))
+        .unwrap_or(link);
+
+    let (host, path) = link.split_once('/')?;
+    if !MESSAGE_LINK_HOSTS.contains(&host.to_lowercase().as_str()) {
+        return None;
+    }
+
+    let path = path.split(['?', '#']).next()?.trim_end_matches('/');
+    let [guild_id, channel_id, message_id] = path
+        .strip_prefix("channels/")?
+        .split('/')
+        .collect::<Vec<_>>()
+        .try_into()
+        .ok()?;
+
+    let guild_id = match guild_id {
+        "@me" => None,
+        guild_id => Some(parse_id(guild_id)?.into()),
+    };
+
+    Some(MessageLink {
+        guild_id,
+        channel_id: parse_id(channel_id)?.into(),
+        message_id: parse_id(message_id)?.into(),
+    })
+}
+
+/// Discord IDs are never 0, which serenity's ID types panic on
+fn parse_id(id: &str) -> Option<u64> {
+    id.parse::<u64>().ok().filter(|id| *id != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message_link() {
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/1/2/3"),
+            Some(MessageLink {
+                guild_id: Some(1.into()),
+                channel_id: 2.into(),
+                message_id: 3.into(),
+            })
+        );
+        assert_eq!(
+            parse_message_link(" <https://canary.discord.com/channels/1/2/3/> "),
+            Some(MessageLink {
+                guild_id: Some(1.into()),
+                channel_id: 2.into(),
+                message_id: 3.into(),
+            })
+        );
+        assert_eq!(
+            parse_message_link("discordapp.com/channels/1/2/3?foo=bar"),
+            Some(MessageLink {
+                guild_id: Some(1.into()),
+                channel_id: 2.into(),
+                message_id: 3.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_message_link_without_guild() {
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/@me/2/3"),
+            Some(MessageLink {
+                guild_id: None,
+                channel_id: 2.into(),
+                message_id: 3.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_message_link_round_trips_message_links() {
+        let mut message = serenity::Message::default();
+        message.id = 3.into();
+        message.channel_id = 2.into();
+
+        let link = parse_message_link(&message.link()).unwrap();
+        assert_eq!(link.guild_id(), None);
+        assert_eq!(link.channel_id(), message.channel_id);
+        assert_eq!(link.message_id(), message.id);
+
+        message.guild_id = Some(1.into());
+        let link = parse_message_link(&message.link()).unwrap();
+        assert_eq!(link.guild_id(), message.guild_id);
+    }
+
+    #[test]
+    fn test_parse_message_link_rejects_other_links() {
+        assert_eq!(parse_message_link(""), None);
+        assert_eq!(parse_message_link("hello"), None);
+        assert_eq!(
+            parse_message_link("https://example.com/channels/1/2/3"),
+            None
+        );
+        assert_eq!(parse_message_link("https://discord.com/channels/1/2"), None);
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/1/2/3/4"),
+            None
+        );
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/1/2/x"),
+            None
+        );
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/1/0/3"),
+            None
+        );
+        assert_eq!(parse_message_link("https://discord.com/invite/1/2/3"), None);
+    }
+}
@@ -0,0 +1,232 @@
+use poise::serenity_prelude as serenity;
+
+pub const DEFAULT_BOOKMARK_EMOJI: &str = "🔖";
+pub const DEFAULT_REMIND_EMOJI: &str = "⏰";
+pub const DEFAULT_REMIND_TOMORROW_EMOJI: &str = "📌";
+
+/// The longest emoji accepted, which fits any custom emoji (`<a:name:id>`) and sequences like flags
+const MAX_EMOJI_LENGTH: usize = 64;
+
+/// What reacting to a message with one of a guild's [`ReactionShortcutEmojis`] does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionShortcut {
+    /// Like the "Bookmark" context menu command
+    Bookmark,
+    /// Sends the reminder time picker for the message, without bookmarking it
+    Remind,
+    /// Sets a reminder for the same time tomorrow
+    RemindTomorrow,
+}
+
+/// Each is either a unicode emoji or a custom emoji in Discord's `<:name:id>` format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReactionShortcutEmojis {
+    bookmark: String,
+    remind: String,
+    remind_tomorrow: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidReactionShortcutEmojisError {
+    /// Holds the offending emoji
+    NotAnEmoji(String),
+    /// Holds the emoji used for more than one shortcut
+    Duplicate(String),
+}
+
+impl std::fmt::Display for InvalidReactionShortcutEmojisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAnEmoji(emoji) => write!(f, "not an emoji: {:?}", emoji),
+            Self::Duplicate(emoji) => write!(f, "used for more than one shortcut: {}", emoji),
+        }
+    }
+}
+
+impl std::error::Error for InvalidReactionShortcutEmojisError {}
+
+impl Default for ReactionShortcutEmojis {
+    fn default() -> Self {
+        Self {
+            bookmark: DEFAULT_BOOKMARK_EMOJI.to_string(),
+            remind: DEFAULT_REMIND_EMOJI.to_string(),
+            remind_tomorrow: DEFAULT_REMIND_TOMORROW_EMOJI.to_string(),
+        }
+    }
+}
+
+impl ReactionShortcutEmojis {
+    pub fn new(
+        bookmark: &str,
+        remind: &str,
+        remind_tomorrow: &str,
+    ) -> Result<Self, InvalidReactionShortcutEmojisError> {
+        let emojis = [bookmark, remind, remind_tomorrow].map(str::trim);
+        for emoji in emojis {
+            if !is_emoji(emoji) {
+                return Err(InvalidReactionShortcutEmojisError::NotAnEmoji(
+                    emoji.to_string(),
+                ));
+            }
+        }
+        for (index, emoji) in emojis.iter().enumerate() {
+            if emojis[..index]
+                .iter()
+                .any(|other| is_same_emoji(&get_reaction_type(other), &get_reaction_type(emoji)))
+            {
+                return Err(InvalidReactionShortcutEmojisError::Duplicate(
+                    emoji.to_string(),
+                ));
+            }
+        }
+
+        let [bookmark, remind, remind_tomorrow] = emojis.map(str::to_string);
+        Ok(Self {
+            bookmark,
+            remind,
+            remind_tomorrow,
+        })
+    }
+
+    pub fn bookmark(&self) -> &str {
+        &self.bookmark
+    }
+
+    pub fn remind(&self) -> &str {
+        &self.remind
+    }
+
+    pub fn remind_tomorrow(&self) -> &str {
+        &self.remind_tomorrow
+    }
+
+    pub fn get_shortcut(&self, emoji: &serenity::ReactionType) -> Option<ReactionShortcut> {
+        [
+            (&self.bookmark, ReactionShortcut::Bookmark),
+            (&self.remind, ReactionShortcut::Remind),
+            (&self.remind_tomorrow, ReactionShortcut::RemindTomorrow),
+        ]
+        .into_iter()
+        .find(|(shortcut_emoji, _)| is_same_emoji(&get_reaction_type(shortcut_emoji), emoji))
+        .map(|(_, shortcut)| shortcut)
+    }
+}
+
+/// Serenity parses any text that isn't a custom emoji as a unicode one, so this also rules out
+/// anything with letters (or no emoji-like characters at all) in it. Keycaps like 1️⃣ are still fine.
+fn is_emoji(emoji: &str) -> bool {
+    if emoji.is_empty() || emoji.len() > MAX_EMOJI_LENGTH || emoji.contains(char::is_whitespace) {
+        return false;
+    }
+    match serenity::ReactionType::try_from(emoji) {
+        Ok(serenity::ReactionType::Custom { .. }) => true,
+        Ok(_) => !emoji.is_ascii() && !emoji.contains(|c: char| c.is_ascii_alphabetic()),
+        Err(_) => false,
+    }
+}
+
+fn get_reaction_type(emoji: &str) -> serenity::ReactionType {
+    serenity::ReactionType::try_from(emoji)
+        .unwrap_or_else(|_| serenity::ReactionType::Unicode(emoji.to_string()))
+}
+
+/// Custom emojis are compared by ID, since they can be renamed. Unicode emojis are compared without
+/// variation selectors, which Discord leaves off reactions but keyboards often add (e.g. ⏰ vs ⏰️).
+fn is_same_emoji(a: &serenity::ReactionType, b: &serenity::ReactionType) -> bool {
+    match (a, b) {
+        (
+            serenity::ReactionType::Custom { id: a, .. },
+            serenity::ReactionType::Custom { id: b, .. },
+        ) => a == b,
+        (serenity::ReactionType::Unicode(a), serenity::ReactionType::Unicode(b)) => {
+            a.replace('\u{FE0F}', "") == b.replace('\u{FE0F}', "")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_shortcut() {
+        let emojis = ReactionShortcutEmojis::default();
+
+        assert_eq!(
+            emojis.get_shortcut(&serenity::ReactionType::Unicode("🔖".to_string())),
+            Some(ReactionShortcut::Bookmark)
+        );
+        assert_eq!(
+            emojis.get_shortcut(&serenity::ReactionType::Unicode("⏰".to_string())),
+            Some(ReactionShortcut::Remind)
+        );
+        assert_eq!(
+            emojis.get_shortcut(&serenity::ReactionType::Unicode("📌".to_string())),
+            Some(ReactionShortcut::RemindTomorrow)
+        );
+        assert_eq!(
+            emojis.get_shortcut(&serenity::ReactionType::Unicode("👍".to_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_shortcut_ignores_variation_selectors() {
+        let emojis = ReactionShortcutEmojis::new("🔖", "⏰\u{FE0F}", "📌").unwrap();
+
+        assert_eq!(
+            emojis.get_shortcut(&serenity::ReactionType::Unicode("⏰".to_string())),
+            Some(ReactionShortcut::Remind)
+        );
+    }
+
+    #[test]
+    fn test_get_shortcut_matches_custom_emojis_by_id() {
+        let emojis = ReactionShortcutEmojis::new("<:save:123456789>", "⏰", "📌").unwrap();
+
+        assert_eq!(
+            emojis.get_shortcut(&serenity::ReactionType::Custom {
+                animated: false,
+                id: serenity::EmojiId::new(123456789),
+                name: Some("renamed".to_string()),
+            }),
+            Some(ReactionShortcut::Bookmark)
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_emojis() {
+        assert_eq!(
+            ReactionShortcutEmojis::new("", "⏰", "📌"),
+            Err(InvalidReactionShortcutEmojisError::NotAnEmoji(
+                "".to_string()
+            ))
+        );
+        assert_eq!(
+            ReactionShortcutEmojis::new("not an emoji", "⏰", "📌"),
+            Err(InvalidReactionShortcutEmojisError::NotAnEmoji(
+                "not an emoji".to_string()
+            ))
+        );
+        assert_eq!(
+            ReactionShortcutEmojis::new("🔖", "⏰", "abc"),
+            Err(InvalidReactionShortcutEmojisError::NotAnEmoji(
+                "abc".to_string()
+            ))
+        );
+        assert_eq!(
+            ReactionShortcutEmojis::new("🔖", "⏰", "<:broken:>"),
+            Err(InvalidReactionShortcutEmojisError::NotAnEmoji(
+                "<:broken:>".to_string()
+            ))
+        );
+        assert!(ReactionShortcutEmojis::new("🔖", "⏰", "1\u{FE0F}\u{20E3}").is_ok());
+        assert_eq!(
+            ReactionShortcutEmojis::new("🔖", "⏰", "⏰\u{FE0F}"),
+            Err(InvalidReactionShortcutEmojisError::Duplicate(
+                "⏰\u{FE0F}".to_string()
+            ))
+        );
+    }
+}
@@ -1,10 +1,26 @@
 use poise::serenity_prelude as serenity;
+use uuid::Uuid;
+
+use crate::models::{get_message_without_content, parse_row_error};
 
 #[derive(Debug)]
 pub struct Reminder {
     user_id: u64,
     message: serenity::Message,
     remind_at: chrono::DateTime<chrono::Utc>,
+    countdown: Option<CountdownLink>,
+    /// Whether `message` was stripped down to its IDs because of privacy mode
+    content_omitted: bool,
+    /// Set when this resurfaces a bookmark for spaced repetition, see [`crate::spaced_repetition`]
+    review_bookmark_id: Option<Uuid>,
+}
+
+/// Links the alerts created from one [`crate::models::countdown::Countdown`]
+/// so they can be shown and cancelled together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountdownLink {
+    countdown_id: Uuid,
+    deadline: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug)]
@@ -14,17 +30,23 @@ pub struct PersistedReminder {
     user_id: u64,
     message: serenity::Message,
     remind_at: chrono::DateTime<chrono::Utc>,
+    /// Urgent reminders are delivered even during the user's quiet hours
+    urgent: bool,
+    countdown: Option<CountdownLink>,
+    /// Whether `message` was stripped down to its IDs because of privacy mode
+    content_omitted: bool,
+    /// Set when this resurfaces a bookmark for spaced repetition, see [`crate::spaced_repetition`]
+    review_bookmark_id: Option<Uuid>,
 }
 
-#[derive(Debug)]
-pub enum ParseReminderError {
-    #[expect(dead_code)]
-    UserId(std::num::ParseIntError),
-    #[expect(dead_code)]
-    Message(serde_json::Error),
-    #[expect(dead_code)]
-    RemindAt(chrono::ParseError),
-}
+parse_row_error!(ParseReminderError {
+    UserId(std::num::ParseIntError) => "user_id", "user ID",
+    Message(serde_json::Error) => "message", "message",
+    RemindAt(chrono::ParseError) => "remind_at", "reminder time",
+    CountdownId(uuid::Error) => "countdown_id", "countdown ID",
+    Deadline(chrono::ParseError) => "deadline", "countdown deadline",
+    ReviewBookmarkId(uuid::Error) => "review_bookmark_id", "review bookmark ID",
+});
 
 impl Reminder {
     pub fn new(
@@ -36,6 +58,51 @@ impl Reminder {
             user_id,
             message,
             remind_at,
+            countdown: None,
+            content_omitted: false,
+            review_bookmark_id: None,
+        }
+    }
+
+    pub fn new_countdown_alert(
+        user_id: u64,
+        message: serenity::Message,
+        remind_at: chrono::DateTime<chrono::Utc>,
+        countdown: CountdownLink,
+    ) -> Self {
+        Self {
+            user_id,
+            message,
+            remind_at,
+            countdown: Some(countdown),
+            content_omitted: false,
+            review_bookmark_id: None,
+        }
+    }
+
+    /// Resurfaces the bookmark with the given ID, whose message is `message`
+    pub fn new_bookmark_review(
+        user_id: u64,
+        message: serenity::Message,
+        remind_at: chrono::DateTime<chrono::Utc>,
+        bookmark_id: Uuid,
+    ) -> Self {
+        Self {
+            user_id,
+            message,
+            remind_at,
+            countdown: None,
+            content_omitted: false,
+            review_bookmark_id: Some(bookmark_id),
+        }
+    }
+
+    /// Strips the message down to what's needed to fetch it again when the reminder is delivered
+    pub fn without_message_content(self) -> Self {
+        Self {
+            message: get_message_without_content(&self.message),
+            content_omitted: true,
+            ..self
         }
     }
 
@@ -50,6 +117,35 @@ impl Reminder {
     pub fn remind_at(&self) -> &chrono::DateTime<chrono::Utc> {
         &self.remind_at
     }
+
+    pub fn countdown(&self) -> Option<&CountdownLink> {
+        self.countdown.as_ref()
+    }
+
+    pub fn is_content_omitted(&self) -> bool {
+        self.content_omitted
+    }
+
+    pub fn review_bookmark_id(&self) -> Option<Uuid> {
+        self.review_bookmark_id
+    }
+}
+
+impl CountdownLink {
+    pub fn new(countdown_id: Uuid, deadline: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            countdown_id,
+            deadline,
+        }
+    }
+
+    pub fn countdown_id(&self) -> Uuid {
+        self.countdown_id
+    }
+
+    pub fn deadline(&self) -> &chrono::DateTime<chrono::Utc> {
+        &self.deadline
+    }
 }
 
 impl PersistedReminder {
@@ -59,6 +155,10 @@ impl PersistedReminder {
             user_id: reminder.user_id,
             message: reminder.message,
             remind_at: reminder.remind_at,
+            urgent: false,
+            countdown: reminder.countdown,
+            content_omitted: reminder.content_omitted,
+            review_bookmark_id: reminder.review_bookmark_id,
         }
     }
 
@@ -69,11 +169,17 @@ impl PersistedReminder {
     /// I just take owned Strings.
     /// This theoretically can be changed later to accomodate string slices if there is a desire for more flexibility
     /// in this function, but I think I'd rather just create a separate function.
+    #[expect(clippy::too_many_arguments)]
     pub fn from_row(
         pk: i64,
         user_id: String,   // Sqlite integers are signed
         message: String,   // ideally, a json string
         remind_at: String, // ideally, a iso 8601 string
+        urgent: bool,
+        countdown_id: Option<String>, // ideally, a uuid string
+        deadline: Option<String>,     // ideally, a iso 8601 string
+        content_omitted: bool,
+        review_bookmark_id: Option<String>, // ideally, a uuid string
     ) -> Result<Self, ParseReminderError> {
         let user_id = user_id.parse::<u64>().map_err(ParseReminderError::UserId)?;
 
@@ -84,11 +190,30 @@ impl PersistedReminder {
             .map_err(ParseReminderError::RemindAt)?
             .with_timezone(&chrono::Utc);
 
+        let countdown = match (countdown_id, deadline) {
+            (Some(countdown_id), Some(deadline)) => Some(CountdownLink::new(
+                Uuid::parse_str(&countdown_id).map_err(ParseReminderError::CountdownId)?,
+                chrono::DateTime::parse_from_rfc3339(&deadline)
+                    .map_err(ParseReminderError::Deadline)?
+                    .with_timezone(&chrono::Utc),
+            )),
+            _ => None,
+        };
+
+        let review_bookmark_id = review_bookmark_id
+            .map(|bookmark_id| Uuid::parse_str(&bookmark_id))
+            .transpose()
+            .map_err(ParseReminderError::ReviewBookmarkId)?;
+
         Ok(Self {
             pk,
             user_id,
             message,
             remind_at,
+            urgent,
+            countdown,
+            content_omitted,
+            review_bookmark_id,
         })
     }
 
@@ -116,6 +241,32 @@ impl PersistedReminder {
     pub fn remind_at(&self) -> &chrono::DateTime<chrono::Utc> {
         &self.remind_at
     }
+
+    pub fn is_urgent(&self) -> bool {
+        self.urgent
+    }
+
+    pub fn countdown(&self) -> Option<&CountdownLink> {
+        self.countdown.as_ref()
+    }
+
+    pub fn is_content_omitted(&self) -> bool {
+        self.content_omitted
+    }
+
+    pub fn review_bookmark_id(&self) -> Option<Uuid> {
+        self.review_bookmark_id
+    }
+
+    /// Replaces the stored message, e.g. with a freshly fetched copy of a message whose content wasn't stored
+    pub fn with_message(self, message: serenity::Message) -> Self {
+        Self { message, ..self }
+    }
+
+    /// Used when a reminder is pushed back (e.g. because it came due during quiet hours)
+    pub fn rescheduled_to(self, remind_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self { remind_at, ..self }
+    }
 }
 
 impl Ord for PersistedReminder {
@@ -152,10 +303,30 @@ mod tests {
         let message = serde_json::to_string(&serenity::Message::default()).unwrap();
         let some_time = chrono::Utc::now();
         let remind_at = some_time.to_rfc3339();
-        let reminder = PersistedReminder::from_row(pk, user_id, message, remind_at).unwrap();
+        let countdown_id = Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext));
+        let deadline = some_time + chrono::Duration::hours(1);
+        let reminder = PersistedReminder::from_row(
+            pk,
+            user_id,
+            message,
+            remind_at,
+            true,
+            Some(countdown_id.to_string()),
+            Some(deadline.to_rfc3339()),
+            true,
+            Some(countdown_id.to_string()),
+        )
+        .unwrap();
         assert_eq!(reminder.pk, pk);
         assert_eq!(reminder.user_id, 123456789);
         assert_eq!(reminder.message.id, serenity::Message::default().id);
         assert_eq!(reminder.remind_at, some_time);
+        assert!(reminder.urgent);
+        assert_eq!(
+            reminder.countdown,
+            Some(CountdownLink::new(countdown_id, deadline))
+        );
+        assert!(reminder.content_omitted);
+        assert_eq!(reminder.review_bookmark_id, Some(countdown_id));
     }
 }
@@ -0,0 +1,549 @@
+use chrono::{NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+
+use crate::models::parse_row_error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSettings {
+    user_id: u64,
+    /// Quiet hours are interpreted in this timezone
+    timezone: Tz,
+    quiet_hours: Option<QuietHours>,
+    /// When set, non-urgent reminders are collected into one daily message sent at this local time
+    digest_time: Option<NaiveTime>,
+    /// The local date on which the last digest was sent, so that a digest is sent at most once a day
+    last_digest_sent_on: Option<NaiveDate>,
+    /// When enabled, only the IDs of bookmarked and reminded messages are stored,
+    /// and their content is fetched from Discord whenever it's shown
+    privacy_mode: bool,
+    /// Replaces the server's retention policy for this user's bookmarks when set
+    bookmark_retention: Option<RetentionOverride>,
+    /// Replaces the server's retention policy for the content of this user's bookmarked messages when set
+    message_content_retention: Option<RetentionOverride>,
+    /// When enabled, new bookmarks are resurfaced at growing intervals, see [`crate::spaced_repetition`]
+    spaced_repetition: bool,
+}
+
+/// A user's own choice of how long to keep something, in place of the server's retention policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionOverride {
+    Forever,
+    Days(u32),
+}
+
+/// A daily window (in the user's local time) during which non-urgent reminders are held back.
+/// The window may wrap past midnight (e.g. 22:00 to 07:00).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+parse_row_error!(ParseUserSettingsError {
+    UserId(std::num::ParseIntError) => "user_id", "user ID",
+    Timezone(chrono_tz::ParseError) => "timezone", "timezone",
+    QuietHours(chrono::ParseError) => "quiet_hours_start", "quiet hours",
+    DigestTime(chrono::ParseError) => "digest_time", "digest time",
+    LastDigestSentOn(chrono::ParseError) => "last_digest_sent_on", "last digest date",
+});
+
+/// Format used to store and display times of day, e.g. [`QuietHours`] bounds and the digest time
+pub const TIME_OF_DAY_FORMAT: &str = "%H:%M";
+/// Format used to store [`UserSettings::last_digest_sent_on`]
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+impl UserSettings {
+    /// The settings a user has before they've changed anything
+    pub fn new(user_id: u64) -> Self {
+        Self {
+            user_id,
+            timezone: Tz::UTC,
+            quiet_hours: None,
+            digest_time: None,
+            last_digest_sent_on: None,
+            privacy_mode: false,
+            bookmark_retention: None,
+            message_content_retention: None,
+            spaced_repetition: false,
+        }
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    pub fn from_row(
+        user_id: String,                     // Sqlite integers are signed
+        timezone: String,                    // ideally, an IANA timezone name
+        quiet_hours_start: Option<String>,   // ideally, a "%H:%M" string
+        quiet_hours_end: Option<String>,     // ideally, a "%H:%M" string
+        digest_time: Option<String>,         // ideally, a "%H:%M" string
+        last_digest_sent_on: Option<String>, // ideally, a "%Y-%m-%d" string
+        privacy_mode: bool,
+        bookmark_retention_days: Option<u32>, // 0 means forever
+        message_content_retention_days: Option<u32>, // 0 means forever
+        spaced_repetition: bool,
+    ) -> Result<Self, ParseUserSettingsError> {
+        let user_id = user_id
+            .parse::<u64>()
+            .map_err(ParseUserSettingsError::UserId)?;
+
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(ParseUserSettingsError::Timezone)?;
+
+        let quiet_hours = match (quiet_hours_start, quiet_hours_end) {
+            (Some(start), Some(end)) => Some(QuietHours::new(
+                NaiveTime::parse_from_str(&start, TIME_OF_DAY_FORMAT)
+                    .map_err(ParseUserSettingsError::QuietHours)?,
+                NaiveTime::parse_from_str(&end, TIME_OF_DAY_FORMAT)
+                    .map_err(ParseUserSettingsError::QuietHours)?,
+            )),
+            _ => None,
+        };
+
+        let digest_time = digest_time
+            .map(|digest_time| NaiveTime::parse_from_str(&digest_time, TIME_OF_DAY_FORMAT))
+            .transpose()
+            .map_err(ParseUserSettingsError::DigestTime)?;
+
+        let last_digest_sent_on = last_digest_sent_on
+            .map(|date| NaiveDate::parse_from_str(&date, DATE_FORMAT))
+            .transpose()
+            .map_err(ParseUserSettingsError::LastDigestSentOn)?;
+
+        Ok(Self {
+            user_id,
+            timezone,
+            quiet_hours,
+            digest_time,
+            last_digest_sent_on,
+            privacy_mode,
+            bookmark_retention: bookmark_retention_days.map(RetentionOverride::from_days),
+            message_content_retention: message_content_retention_days
+                .map(RetentionOverride::from_days),
+            spaced_repetition,
+        })
+    }
+
+    pub fn user_id(&self) -> u64 {
+        self.user_id
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    pub fn set_timezone(&mut self, timezone: Tz) {
+        self.timezone = timezone;
+    }
+
+    pub fn quiet_hours(&self) -> Option<&QuietHours> {
+        self.quiet_hours.as_ref()
+    }
+
+    pub fn set_quiet_hours(&mut self, quiet_hours: Option<QuietHours>) {
+        self.quiet_hours = quiet_hours;
+    }
+
+    pub fn digest_time(&self) -> Option<NaiveTime> {
+        self.digest_time
+    }
+
+    pub fn set_digest_time(&mut self, digest_time: Option<NaiveTime>) {
+        self.digest_time = digest_time;
+    }
+
+    pub fn privacy_mode(&self) -> bool {
+        self.privacy_mode
+    }
+
+    pub fn set_privacy_mode(&mut self, privacy_mode: bool) {
+        self.privacy_mode = privacy_mode;
+    }
+
+    pub fn bookmark_retention(&self) -> Option<RetentionOverride> {
+        self.bookmark_retention
+    }
+
+    pub fn set_bookmark_retention(&mut self, bookmark_retention: Option<RetentionOverride>) {
+        self.bookmark_retention = bookmark_retention;
+    }
+
+    pub fn message_content_retention(&self) -> Option<RetentionOverride> {
+        self.message_content_retention
+    }
+
+    pub fn set_message_content_retention(
+        &mut self,
+        message_content_retention: Option<RetentionOverride>,
+    ) {
+        self.message_content_retention = message_content_retention;
+    }
+
+    pub fn spaced_repetition(&self) -> bool {
+        self.spaced_repetition
+    }
+
+    pub fn set_spaced_repetition(&mut self, spaced_repetition: bool) {
+        self.spaced_repetition = spaced_repetition;
+    }
+
+    pub fn last_digest_sent_on(&self) -> Option<NaiveDate> {
+        self.last_digest_sent_on
+    }
+
+    /// The user's local date at `instant`
+    pub fn get_local_date(&self, instant: &chrono::DateTime<chrono::Utc>) -> NaiveDate {
+        instant.with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Whether today's digest (in the user's timezone) is due at `instant` and hasn't been sent yet
+    pub fn is_digest_due(&self, instant: &chrono::DateTime<chrono::Utc>) -> bool {
+        let Some(digest_time) = self.digest_time else {
+            return false;
+        };
+
+        let local_instant = instant.with_timezone(&self.timezone);
+        let already_sent_today = self.last_digest_sent_on == Some(local_instant.date_naive());
+
+        !already_sent_today && local_instant.time() >= digest_time
+    }
+
+    /// The instant today's digest (in the user's timezone) is sent, if it hasn't been sent yet.
+    /// Reminders due before this are held back for it
+    pub fn get_pending_digest_time(
+        &self,
+        instant: &chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        let digest_time = self.digest_time?;
+        let local_date = self.get_local_date(instant);
+        if self.last_digest_sent_on == Some(local_date) {
+            return None;
+        }
+
+        get_first_valid_instant(self.timezone, local_date.and_time(digest_time))
+    }
+
+    /// If `instant` falls inside this user's quiet hours, returns when those quiet hours end.
+    pub fn get_end_of_quiet_hours_containing(
+        &self,
+        instant: &chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.quiet_hours.as_ref().and_then(|quiet_hours| {
+            quiet_hours.get_end_of_window_containing(instant, self.timezone)
+        })
+    }
+}
+
+impl RetentionOverride {
+    /// The inverse of [`Self::to_days`]
+    pub fn from_days(days: u32) -> Self {
+        match days {
+            0 => Self::Forever,
+            days => Self::Days(days),
+        }
+    }
+
+    /// How this is stored, with 0 standing in for [`Self::Forever`]
+    pub fn to_days(self) -> u32 {
+        match self {
+            Self::Forever => 0,
+            Self::Days(days) => days,
+        }
+    }
+
+    /// How long to keep something for, or [`None`] to keep it forever
+    pub fn to_duration(self) -> Option<chrono::Duration> {
+        match self {
+            Self::Forever => None,
+            Self::Days(days) => Some(chrono::Duration::days(days.into())),
+        }
+    }
+}
+
+impl std::fmt::Display for RetentionOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Forever => write!(f, "Forever"),
+            Self::Days(1) => write!(f, "1 day"),
+            Self::Days(days) => write!(f, "{} days", days),
+        }
+    }
+}
+
+impl QuietHours {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> NaiveTime {
+        self.start
+    }
+
+    pub fn end(&self) -> NaiveTime {
+        self.end
+    }
+
+    /// If `instant` (interpreted in `timezone`) falls inside the window, returns the instant the window ends.
+    /// A window whose start and end are equal is treated as empty rather than as lasting all day.
+    pub fn get_end_of_window_containing(
+        &self,
+        instant: &chrono::DateTime<chrono::Utc>,
+        timezone: Tz,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        let local_instant = instant.with_timezone(&timezone);
+        let local_time = local_instant.time();
+        let local_date = local_instant.date_naive();
+
+        let end_date = if self.start < self.end {
+            // e.g. 13:00 to 15:00
+            (self.start <= local_time && local_time < self.end).then_some(local_date)
+        } else if self.start > self.end {
+            // e.g. 22:00 to 07:00
+            if local_time >= self.start {
+                local_date.succ_opt()
+            } else if local_time < self.end {
+                Some(local_date)
+            } else {
+                None
+            }
+        } else {
+            None
+        }?;
+
+        get_first_valid_instant(timezone, end_date.and_time(self.end))
+    }
+}
+
+/// If `local_datetime` falls into a DST gap, the local time doesn't exist on that day,
+/// so this falls back to the first valid time after the gap.
+fn get_first_valid_instant(
+    timezone: Tz,
+    local_datetime: chrono::NaiveDateTime,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    timezone
+        .from_local_datetime(&local_datetime)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(local_datetime + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map(|instant| instant.with_timezone(&chrono::Utc))
+}
+
+impl std::fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} to {}",
+            self.start.format(TIME_OF_DAY_FORMAT),
+            self.end.format(TIME_OF_DAY_FORMAT)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn utc(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+    ) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_user_settings_from_row() {
+        let settings = UserSettings::from_row(
+            "123456789".to_string(),
+            "Europe/Berlin".to_string(),
+            Some("22:00".to_string()),
+            Some("07:30".to_string()),
+            Some("08:15".to_string()),
+            Some("2025-01-01".to_string()),
+            true,
+            Some(0),
+            Some(30),
+            true,
+        )
+        .unwrap();
+        assert_eq!(settings.user_id(), 123456789);
+        assert_eq!(settings.timezone(), chrono_tz::Europe::Berlin);
+        assert_eq!(
+            settings.quiet_hours(),
+            Some(&QuietHours::new(time(22, 0), time(7, 30)))
+        );
+        assert_eq!(settings.digest_time(), Some(time(8, 15)));
+        assert_eq!(
+            settings.last_digest_sent_on(),
+            NaiveDate::from_ymd_opt(2025, 1, 1)
+        );
+        assert!(settings.privacy_mode());
+        assert_eq!(
+            settings.bookmark_retention(),
+            Some(RetentionOverride::Forever)
+        );
+        assert_eq!(
+            settings.message_content_retention(),
+            Some(RetentionOverride::Days(30))
+        );
+        assert!(settings.spaced_repetition());
+
+        let settings = UserSettings::from_row(
+            "1".to_string(),
+            "UTC".to_string(),
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(settings, UserSettings::new(1));
+    }
+
+    #[test]
+    fn test_retention_override() {
+        assert_eq!(RetentionOverride::from_days(0), RetentionOverride::Forever);
+        assert_eq!(RetentionOverride::from_days(7), RetentionOverride::Days(7));
+        assert_eq!(RetentionOverride::Forever.to_days(), 0);
+        assert_eq!(RetentionOverride::Days(7).to_days(), 7);
+
+        assert_eq!(RetentionOverride::Forever.to_duration(), None);
+        assert_eq!(
+            RetentionOverride::Days(7).to_duration(),
+            Some(chrono::Duration::days(7))
+        );
+
+        assert_eq!(RetentionOverride::Forever.to_string(), "Forever");
+        assert_eq!(RetentionOverride::Days(1).to_string(), "1 day");
+        assert_eq!(RetentionOverride::Days(30).to_string(), "30 days");
+    }
+
+    #[test]
+    fn test_quiet_hours_within_a_single_day() {
+        let quiet_hours = QuietHours::new(time(13, 0), time(15, 0));
+
+        assert_eq!(
+            quiet_hours.get_end_of_window_containing(&utc(2025, 1, 1, 14, 0), Tz::UTC),
+            Some(utc(2025, 1, 1, 15, 0))
+        );
+        assert_eq!(
+            quiet_hours.get_end_of_window_containing(&utc(2025, 1, 1, 15, 0), Tz::UTC),
+            None
+        );
+        assert_eq!(
+            quiet_hours.get_end_of_window_containing(&utc(2025, 1, 1, 12, 59), Tz::UTC),
+            None
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours_wrapping_past_midnight() {
+        let quiet_hours = QuietHours::new(time(22, 0), time(7, 0));
+
+        assert_eq!(
+            quiet_hours.get_end_of_window_containing(&utc(2025, 1, 1, 23, 0), Tz::UTC),
+            Some(utc(2025, 1, 2, 7, 0))
+        );
+        assert_eq!(
+            quiet_hours.get_end_of_window_containing(&utc(2025, 1, 2, 3, 0), Tz::UTC),
+            Some(utc(2025, 1, 2, 7, 0))
+        );
+        assert_eq!(
+            quiet_hours.get_end_of_window_containing(&utc(2025, 1, 2, 12, 0), Tz::UTC),
+            None
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours_are_interpreted_in_the_users_timezone() {
+        let mut settings = UserSettings::new(1);
+        settings.set_timezone(chrono_tz::America::New_York);
+        settings.set_quiet_hours(Some(QuietHours::new(time(22, 0), time(7, 0))));
+
+        // 08:00 UTC is 03:00 in New York (EST, UTC-5)
+        assert_eq!(
+            settings.get_end_of_quiet_hours_containing(&utc(2025, 1, 1, 8, 0)),
+            Some(utc(2025, 1, 1, 12, 0))
+        );
+        // 03:00 UTC is 22:00 the previous day in New York
+        assert_eq!(
+            settings.get_end_of_quiet_hours_containing(&utc(2025, 1, 1, 3, 0)),
+            Some(utc(2025, 1, 1, 12, 0))
+        );
+        assert_eq!(
+            settings.get_end_of_quiet_hours_containing(&utc(2025, 1, 1, 18, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_empty_quiet_hours() {
+        let quiet_hours = QuietHours::new(time(7, 0), time(7, 0));
+        assert_eq!(
+            quiet_hours.get_end_of_window_containing(&utc(2025, 1, 1, 7, 0), Tz::UTC),
+            None
+        );
+        assert_eq!(
+            UserSettings::new(1).get_end_of_quiet_hours_containing(&utc(2025, 1, 1, 7, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_digest_due() {
+        let mut settings = UserSettings::new(1);
+        settings.set_timezone(chrono_tz::America::New_York);
+        assert!(!settings.is_digest_due(&utc(2025, 1, 1, 13, 0)));
+
+        settings.set_digest_time(Some(time(8, 0)));
+        // 12:59 UTC is 07:59 in New York
+        assert!(!settings.is_digest_due(&utc(2025, 1, 1, 12, 59)));
+        assert!(settings.is_digest_due(&utc(2025, 1, 1, 13, 0)));
+
+        settings.last_digest_sent_on = NaiveDate::from_ymd_opt(2025, 1, 1);
+        assert!(!settings.is_digest_due(&utc(2025, 1, 1, 20, 0)));
+        // 13:00 UTC on the 2nd is 08:00 on the 2nd in New York
+        assert!(settings.is_digest_due(&utc(2025, 1, 2, 13, 0)));
+    }
+
+    #[test]
+    fn test_get_pending_digest_time() {
+        let mut settings = UserSettings::new(1);
+        assert_eq!(
+            settings.get_pending_digest_time(&utc(2025, 1, 1, 13, 0)),
+            None
+        );
+
+        settings.set_digest_time(Some(time(8, 0)));
+        settings.set_timezone(chrono_tz::America::New_York);
+        // 03:00 UTC on the 2nd is still the 1st in New York
+        assert_eq!(
+            settings.get_pending_digest_time(&utc(2025, 1, 2, 3, 0)),
+            Some(utc(2025, 1, 1, 13, 0))
+        );
+
+        settings.last_digest_sent_on = Some(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        assert_eq!(
+            settings.get_pending_digest_time(&utc(2025, 1, 2, 3, 0)),
+            None
+        );
+        assert_eq!(
+            settings.get_pending_digest_time(&utc(2025, 1, 2, 6, 0)),
+            Some(utc(2025, 1, 2, 13, 0))
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use poise::serenity_prelude as serenity;
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+use crate::{
+    database::{guild_settings::get_guild_settings, user_settings::get_user_settings},
+    linked_message::fetch_message,
+    models::{bookmark::PersistedBookmarkedMessage, reminder::PersistedReminder},
+};
+
+/// Shown in place of a message's content when privacy mode kept it from being stored and it can't be fetched anymore
+pub const MESSAGE_NO_LONGER_AVAILABLE: &str = "*This message is no longer available.*";
+
+/// Privacy mode applies if either the user or the guild the message is from has enabled it.
+pub async fn is_privacy_mode_enabled(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+    guild_id: Option<serenity::GuildId>,
+) -> Result<bool, tokio_rusqlite::Error> {
+    let user_privacy_mode = get_user_settings(db_connection, user_id)
+        .await?
+        .is_some_and(|settings| settings.privacy_mode());
+    if user_privacy_mode {
+        return Ok(true);
+    }
+
+    let Some(guild_id) = guild_id else {
+        return Ok(false);
+    };
+    Ok(get_guild_settings(db_connection, guild_id.get())
+        .await?
+        .is_some_and(|settings| settings.privacy_mode()))
+}
+
+/// Fetches the current version of a message whose content wasn't stored,
+/// falling back to [`get_unavailable_message`] if it was deleted or can't be seen by the bot anymore.
+pub async fn get_live_message(
+    cache_http: impl serenity::CacheHttp,
+    stored_message: &serenity::Message,
+) -> serenity::Message {
+    match fetch_message(
+        cache_http,
+        stored_message.guild_id,
+        stored_message.channel_id,
+        stored_message.id,
+    )
+    .await
+    {
+        Ok(live_message) => live_message,
+        Err(err) => {
+            tracing::warn!(
+                message_id = %stored_message.id,
+                channel_id = %stored_message.channel_id,
+                error = %err,
+                "Failed to fetch message"
+            );
+            get_unavailable_message(stored_message)
+        }
+    }
+}
+
+fn get_unavailable_message(stored_message: &serenity::Message) -> serenity::Message {
+    let mut unavailable_message = stored_message.clone();
+    unavailable_message.content = MESSAGE_NO_LONGER_AVAILABLE.to_string();
+    unavailable_message
+}
+
+/// Leaves reminders whose message content was stored untouched
+pub async fn with_live_reminder_message(
+    cache_http: impl serenity::CacheHttp,
+    reminder: PersistedReminder,
+) -> PersistedReminder {
+    if !reminder.is_content_omitted() {
+        return reminder;
+    }
+
+    let live_message = get_live_message(cache_http, reminder.message()).await;
+    reminder.with_message(live_message)
+}
+
+/// Leaves bookmarks whose message content was stored untouched
+pub async fn with_live_bookmark_message(
+    cache_http: impl serenity::CacheHttp,
+    bookmark: PersistedBookmarkedMessage,
+) -> PersistedBookmarkedMessage {
+    if !bookmark.is_content_omitted() {
+        return bookmark;
+    }
+
+    let live_message = get_live_message(cache_http, bookmark.message()).await;
+    bookmark.with_message(live_message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_unavailable_message() {
+        let mut stored_message = serenity::Message::default();
+        stored_message.id = 3.into();
+        stored_message.channel_id = 2.into();
+        stored_message.guild_id = Some(1.into());
+
+        let unavailable_message = get_unavailable_message(&stored_message);
+
+        assert_eq!(unavailable_message.content, MESSAGE_NO_LONGER_AVAILABLE);
+        assert_eq!(unavailable_message.link(), stored_message.link());
+    }
+}
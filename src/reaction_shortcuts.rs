@@ -0,0 +1,202 @@
+//! Bookmarking messages and setting reminders on them by reacting with a guild's shortcut emojis,
+//! which is quicker than digging through the Apps context menu, especially on mobile.
+
+use std::{collections::HashMap, sync::Arc};
+
+use poise::serenity_prelude as serenity;
+use tracing::{field::Empty, Instrument};
+
+use crate::{
+    components::{
+        bookmark::bookmark_message::get_bookmark_message,
+        reminder::{
+            reminder_created_message::get_reminder_created_message,
+            reminder_picker_message::get_reminder_picker_message,
+            reminder_time_select_menu::menu_value::ReminderSelectMenuValue,
+        },
+        DELETE_MESSAGE_EMOJI,
+    },
+    database::{
+        bookmark::{insert_bookmark, InsertBookmarkError},
+        guild_settings::get_guild_settings,
+        reminder::insert_reminder,
+    },
+    linked_message::fetch_message,
+    models::{
+        bookmark::{BookmarkedMessage, PersistedBookmarkedMessage},
+        guild_settings::GuildSettings,
+        reaction_shortcut::{ReactionShortcut, ReactionShortcutEmojis},
+        reminder::Reminder,
+    },
+    privacy::is_privacy_mode_enabled,
+    spaced_repetition::start_reviews_if_enabled,
+    Data, Error,
+};
+
+/// Each guild's shortcut emojis, or [`None`] if it has turned them off, so that reactions
+/// which aren't shortcuts (i.e. nearly all of them) don't each need a database query
+#[derive(Clone, Default)]
+pub struct ReactionShortcutEmojisCache(
+    Arc<std::sync::Mutex<HashMap<u64, Option<ReactionShortcutEmojis>>>>,
+);
+
+impl ReactionShortcutEmojisCache {
+    /// Called whenever a guild's reaction shortcut settings change
+    pub fn update(&self, settings: &GuildSettings) {
+        self.lock()
+            .insert(settings.guild_id(), get_enabled_emojis(settings));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Option<ReactionShortcutEmojis>>> {
+        self.0
+            .lock()
+            .expect("Reaction shortcut emoji cache lock shouldn't be poisoned")
+    }
+}
+
+/// Does nothing if the emoji isn't one of the guild's shortcuts or the guild has turned them off.
+/// Messages outside of guilds use the default shortcuts.
+pub async fn handle_reaction_shortcut(
+    ctx: &serenity::Context,
+    reaction: &serenity::Reaction,
+    data: &Data,
+) -> Result<(), Error> {
+    let Some(user_id) = reaction.user_id else {
+        return Ok(());
+    };
+
+    // Reaction events only get the generic event span, which has no fields for what the shortcut creates
+    let span = tracing::info_span!(
+        "reaction_shortcut",
+        user_id = %user_id,
+        guild_id = reaction.guild_id.map(|guild_id| guild_id.get()),
+        message_id = %reaction.message_id,
+        bookmark_id = Empty,
+        reminder_pk = Empty,
+    );
+    run_reaction_shortcut(ctx, reaction, data, user_id)
+        .instrument(span)
+        .await
+}
+
+async fn run_reaction_shortcut(
+    ctx: &serenity::Context,
+    reaction: &serenity::Reaction,
+    data: &Data,
+    user_id: serenity::UserId,
+) -> Result<(), Error> {
+    let Some(emojis) = get_reaction_shortcut_emojis(data, reaction.guild_id).await? else {
+        return Ok(());
+    };
+    let Some(shortcut) = emojis.get_shortcut(&reaction.emoji) else {
+        return Ok(());
+    };
+    tracing::debug!(?shortcut, "Received reaction shortcut");
+
+    let message = fetch_message(
+        ctx,
+        reaction.guild_id,
+        reaction.channel_id,
+        reaction.message_id,
+    )
+    .await?;
+    let omit_message_content =
+        is_privacy_mode_enabled(&data.db_connection, user_id.get(), reaction.guild_id).await?;
+
+    let dm_message = match shortcut {
+        ReactionShortcut::Bookmark => {
+            let (bookmark, is_new) =
+                get_or_create_bookmark(data, user_id, &message, omit_message_content).await?;
+            if !is_new {
+                // Reacting again (or after bookmarking some other way) shouldn't send the same DM twice
+                tracing::debug!("Message was already bookmarked");
+                return Ok(());
+            }
+            let bookmark = bookmark.with_message(message);
+            let channel_name = bookmark.message().channel_id.name(ctx).await?;
+            get_bookmark_message(&bookmark, &channel_name, DELETE_MESSAGE_EMOJI)
+        }
+        // Not bookmarked, since the reminder is all that was asked for
+        ReactionShortcut::Remind => get_reminder_picker_message(&message),
+        ReactionShortcut::RemindTomorrow => {
+            let remind_at = chrono::Utc::now()
+                + chrono::Duration::from(ReminderSelectMenuValue::TwentyFourHours);
+            let mut reminder = Reminder::new(user_id.get(), message, remind_at);
+            if omit_message_content {
+                reminder = reminder.without_message_content();
+            }
+            let reminder = insert_reminder(&data.db_connection, reminder).await?;
+            tracing::Span::current().record("reminder_pk", reminder.pk());
+            let dm_message = get_reminder_created_message(&reminder);
+            data.tx.send(reminder).await?;
+            dm_message
+        }
+    };
+
+    user_id
+        .create_dm_channel(ctx)
+        .await?
+        .send_message(ctx, dm_message)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns [`None`] if the guild has turned reaction shortcuts off
+async fn get_reaction_shortcut_emojis(
+    data: &Data,
+    guild_id: Option<serenity::GuildId>,
+) -> Result<Option<ReactionShortcutEmojis>, Error> {
+    let Some(guild_id) = guild_id else {
+        return Ok(Some(ReactionShortcutEmojis::default()));
+    };
+    if let Some(emojis) = data.reaction_shortcut_emojis.lock().get(&guild_id.get()) {
+        return Ok(emojis.clone());
+    }
+
+    let emojis = match get_guild_settings(&data.db_connection, guild_id.get()).await? {
+        Some(settings) => get_enabled_emojis(&settings),
+        None => Some(ReactionShortcutEmojis::default()),
+    };
+    // Settings changed while they were being read have already been cached, and are newer
+    Ok(data
+        .reaction_shortcut_emojis
+        .lock()
+        .entry(guild_id.get())
+        .or_insert(emojis)
+        .clone())
+}
+
+fn get_enabled_emojis(settings: &GuildSettings) -> Option<ReactionShortcutEmojis> {
+    settings
+        .reaction_shortcuts_enabled()
+        .then(|| settings.reaction_shortcut_emojis().clone())
+}
+
+/// Also returns whether the bookmark is new, i.e. the user hadn't bookmarked the message yet
+async fn get_or_create_bookmark(
+    data: &Data,
+    user_id: serenity::UserId,
+    message: &serenity::Message,
+    omit_message_content: bool,
+) -> Result<(PersistedBookmarkedMessage, bool), Error> {
+    let mut bookmark = BookmarkedMessage::new(
+        uuid::Uuid::new_v7(uuid::Timestamp::now(data.uuid_context.as_ref())),
+        user_id.get(),
+        message.clone(),
+    );
+    if omit_message_content {
+        bookmark = bookmark.without_message_content();
+    }
+
+    match insert_bookmark(&data.db_connection, bookmark).await {
+        Ok(bookmark) => {
+            tracing::Span::current().record("bookmark_id", bookmark.bookmark_id().to_string());
+            data.metrics.record_bookmark_created();
+            start_reviews_if_enabled(&data.db_connection, &data.tx, &bookmark).await?;
+            Ok((bookmark, true))
+        }
+        Err(InsertBookmarkError::BookmarkAlreadyExists(bookmark)) => Ok((bookmark, false)),
+        Err(InsertBookmarkError::TokioRusqliteError(err)) => Err(err.into()),
+    }
+}
@@ -0,0 +1,418 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    config::RetentionConfig,
+    database::{
+        bookmark::{
+            delete_expired_bookmarks_by_pk, get_bookmark_retention_infos,
+            purge_bookmark_message_contents,
+        },
+        reminder_delivery::{
+            count_reminder_deliveries_delivered_before, delete_reminder_deliveries_delivered_before,
+        },
+        user_settings::get_all_user_settings,
+    },
+    models::{
+        bookmark::BookmarkRetentionInfo,
+        user_settings::{RetentionOverride, UserSettings},
+    },
+    Error,
+};
+
+/// How often to apply the retention policy
+const RETENTION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// How long stored data is kept for. Each period is [`None`] when that data is kept forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Bookmarks older than this are deleted unless they're starred
+    bookmark_retention: Option<chrono::Duration>,
+    /// Bookmarks older than this keep only a link to their message
+    message_content_retention: Option<chrono::Duration>,
+    /// Records of delivered reminders older than this are deleted
+    reminder_history_retention: Option<chrono::Duration>,
+    /// When set, what would be removed is only logged
+    dry_run: bool,
+}
+
+/// What one pass of the retention policy removed (or would have removed, in a dry run)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionReport {
+    pub expired_bookmark_count: usize,
+    pub purged_message_content_count: usize,
+    pub pruned_reminder_delivery_count: usize,
+    pub dry_run: bool,
+}
+
+/// The bookmarks a pass of the retention policy should act on
+#[derive(Debug, Default)]
+pub struct BookmarkRetentionPlan {
+    expired: Vec<BookmarkRetentionInfo>,
+    /// Never includes expired bookmarks, since they'll be deleted anyway
+    content_to_purge: Vec<BookmarkRetentionInfo>,
+}
+
+impl RetentionPolicy {
+    pub fn new(
+        bookmark_retention: Option<chrono::Duration>,
+        message_content_retention: Option<chrono::Duration>,
+        reminder_history_retention: Option<chrono::Duration>,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            bookmark_retention,
+            message_content_retention,
+            reminder_history_retention,
+            dry_run,
+        }
+    }
+}
+
+impl From<&RetentionConfig> for RetentionPolicy {
+    fn from(config: &RetentionConfig) -> Self {
+        let days_to_duration =
+            |days: Option<u32>| days.map(|days| chrono::Duration::days(days.into()));
+
+        Self::new(
+            days_to_duration(config.bookmark_days),
+            days_to_duration(config.message_content_days),
+            days_to_duration(config.reminder_history_days),
+            config.dry_run,
+        )
+    }
+}
+
+/// A user's override replaces the server's policy entirely, including to keep something forever
+fn get_retention_period(
+    policy_retention: Option<chrono::Duration>,
+    user_override: Option<RetentionOverride>,
+) -> Option<chrono::Duration> {
+    match user_override {
+        Some(user_override) => user_override.to_duration(),
+        None => policy_retention,
+    }
+}
+
+/// Decides which bookmarks have outlived the retention policy (or their owner's override of it) at `now`.
+/// Bookmarks whose creation time can't be determined are left alone.
+pub fn get_bookmark_retention_plan(
+    bookmarks: Vec<BookmarkRetentionInfo>,
+    settings_by_user: &HashMap<u64, UserSettings>,
+    policy: &RetentionPolicy,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> BookmarkRetentionPlan {
+    let mut plan = BookmarkRetentionPlan::default();
+
+    for bookmark in bookmarks {
+        let Some(created_at) = bookmark.created_at() else {
+            continue;
+        };
+        let age = *now - created_at;
+        let user_settings = settings_by_user.get(&bookmark.user_id());
+
+        let bookmark_retention = get_retention_period(
+            policy.bookmark_retention,
+            user_settings.and_then(UserSettings::bookmark_retention),
+        );
+        let message_content_retention = get_retention_period(
+            policy.message_content_retention,
+            user_settings.and_then(UserSettings::message_content_retention),
+        );
+
+        if !bookmark.is_starred() && bookmark_retention.is_some_and(|retention| age > retention) {
+            plan.expired.push(bookmark);
+        } else if bookmark.has_message_content()
+            && message_content_retention.is_some_and(|retention| age > retention)
+        {
+            plan.content_to_purge.push(bookmark);
+        }
+    }
+
+    plan
+}
+
+/// Applies one pass of the retention policy as of `now`
+pub async fn apply_retention_policy(
+    db_connection: &Mutex<Connection>,
+    policy: &RetentionPolicy,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> Result<RetentionReport, Error> {
+    let settings_by_user = get_all_user_settings(db_connection)
+        .await?
+        .into_iter()
+        .map(|settings| (settings.user_id(), settings))
+        .collect::<HashMap<_, _>>();
+    let plan = get_bookmark_retention_plan(
+        get_bookmark_retention_infos(db_connection).await?,
+        &settings_by_user,
+        policy,
+        now,
+    );
+    let reminder_history_cutoff = policy
+        .reminder_history_retention
+        .map(|retention| *now - retention);
+
+    if policy.dry_run {
+        let pruned_reminder_delivery_count = match reminder_history_cutoff {
+            Some(cutoff) => {
+                count_reminder_deliveries_delivered_before(db_connection, cutoff).await?
+            }
+            None => 0,
+        };
+        return Ok(RetentionReport {
+            expired_bookmark_count: plan.expired.len(),
+            purged_message_content_count: plan.content_to_purge.len(),
+            pruned_reminder_delivery_count,
+            dry_run: true,
+        });
+    }
+
+    let expired_bookmark_count =
+        delete_expired_bookmarks_by_pk(db_connection, get_pks(&plan.expired)).await?;
+    let purged_message_content_count =
+        purge_bookmark_message_contents(db_connection, get_pks(&plan.content_to_purge)).await?;
+    let pruned_reminder_delivery_count = match reminder_history_cutoff {
+        Some(cutoff) => delete_reminder_deliveries_delivered_before(db_connection, cutoff).await?,
+        None => 0,
+    };
+
+    Ok(RetentionReport {
+        expired_bookmark_count,
+        purged_message_content_count,
+        pruned_reminder_delivery_count,
+        dry_run: false,
+    })
+}
+
+fn get_pks(bookmarks: &[BookmarkRetentionInfo]) -> Vec<i64> {
+    bookmarks.iter().map(BookmarkRetentionInfo::pk).collect()
+}
+
+/// Periodically applies the retention policy, logging what it removed, until `shutdown` is cancelled
+pub async fn run_retention_maintenance(
+    db_connection: Arc<Mutex<Connection>>,
+    policy: RetentionPolicy,
+    shutdown: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(RETENTION_CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        match apply_retention_policy(&db_connection, &policy, &chrono::Utc::now()).await {
+            Ok(report) => tracing::info!(
+                dry_run = report.dry_run,
+                expired_bookmark_count = report.expired_bookmark_count,
+                purged_message_content_count = report.purged_message_content_count,
+                pruned_reminder_delivery_count = report.pruned_reminder_delivery_count,
+                "Applied retention policy"
+            ),
+            Err(err) => tracing::error!(error = %err, "Failed to apply retention policy"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude as serenity;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        database::{
+            bookmark::{get_all_bookmarks, insert_bookmark, toggle_bookmark_star},
+            user_settings::upsert_user_settings,
+        },
+        models::bookmark::{BookmarkedMessage, PersistedBookmarkedMessage},
+        test_fixtures::get_test_db_connection,
+    };
+
+    const USER_ID: u64 = 123456789;
+
+    fn days(days: i64) -> chrono::Duration {
+        chrono::Duration::days(days)
+    }
+
+    fn get_bookmark_created_at(
+        created_at: chrono::DateTime<chrono::Utc>,
+        message_id: u64,
+    ) -> BookmarkedMessage {
+        let mut message = serenity::Message::default();
+        message.id = serenity::MessageId::new(message_id);
+        message.content = "Some content".to_string();
+
+        BookmarkedMessage::new(
+            Uuid::new_v7(uuid::Timestamp::from_unix(
+                uuid::NoContext,
+                created_at.timestamp() as u64,
+                0,
+            )),
+            USER_ID,
+            message,
+        )
+    }
+
+    fn get_retention_info_created_at(
+        created_at: chrono::DateTime<chrono::Utc>,
+        pk: i64,
+        has_message_content: bool,
+    ) -> BookmarkRetentionInfo {
+        BookmarkRetentionInfo::new(
+            pk,
+            get_bookmark_created_at(created_at, pk as u64).bookmark_id(),
+            USER_ID,
+            false,
+            has_message_content,
+        )
+    }
+
+    #[test]
+    fn test_bookmark_retention_plan_without_policy_keeps_everything() {
+        let now = chrono::Utc::now();
+        let policy = RetentionPolicy::new(None, None, None, false);
+
+        let plan = get_bookmark_retention_plan(
+            vec![get_retention_info_created_at(now - days(365), 1, true)],
+            &HashMap::new(),
+            &policy,
+            &now,
+        );
+
+        assert!(plan.expired.is_empty());
+        assert!(plan.content_to_purge.is_empty());
+    }
+
+    #[test]
+    fn test_bookmark_retention_plan() {
+        let now = chrono::Utc::now();
+        let policy = RetentionPolicy::new(Some(days(30)), Some(days(7)), None, false);
+
+        let plan = get_bookmark_retention_plan(
+            vec![
+                get_retention_info_created_at(now - days(1), 1, true),
+                get_retention_info_created_at(now - days(10), 2, true),
+                get_retention_info_created_at(now - days(40), 3, true),
+                get_retention_info_created_at(now - days(10), 4, false),
+            ],
+            &HashMap::new(),
+            &policy,
+            &now,
+        );
+
+        assert_eq!(get_pks(&plan.expired), vec![3]);
+        assert_eq!(get_pks(&plan.content_to_purge), vec![2]);
+    }
+
+    #[test]
+    fn test_bookmark_retention_plan_respects_user_overrides() {
+        let now = chrono::Utc::now();
+        let policy = RetentionPolicy::new(Some(days(30)), None, None, false);
+        let mut settings = UserSettings::new(USER_ID);
+        settings.set_bookmark_retention(Some(RetentionOverride::Forever));
+        settings.set_message_content_retention(Some(RetentionOverride::Days(7)));
+        let settings_by_user = HashMap::from([(USER_ID, settings)]);
+
+        let plan = get_bookmark_retention_plan(
+            vec![get_retention_info_created_at(now - days(40), 1, true)],
+            &settings_by_user,
+            &policy,
+            &now,
+        );
+
+        assert!(plan.expired.is_empty());
+        assert_eq!(get_pks(&plan.content_to_purge), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_apply_retention_policy() {
+        let db_connection = get_test_db_connection().await;
+        let now = chrono::Utc::now();
+        let mut bookmarks = Vec::new();
+        for (message_id, age) in [(1, days(40)), (2, days(40)), (3, days(10))] {
+            let bookmark = get_bookmark_created_at(now - age, message_id);
+            bookmarks.push(insert_bookmark(&db_connection, bookmark).await.unwrap());
+        }
+        toggle_bookmark_star(&db_connection, bookmarks[1].bookmark_id(), USER_ID)
+            .await
+            .unwrap();
+        upsert_user_settings(&db_connection, UserSettings::new(USER_ID))
+            .await
+            .unwrap();
+
+        let dry_run_policy = RetentionPolicy::new(Some(days(30)), Some(days(7)), None, true);
+        let expected_report = RetentionReport {
+            expired_bookmark_count: 1,
+            purged_message_content_count: 2,
+            pruned_reminder_delivery_count: 0,
+            dry_run: true,
+        };
+        assert_eq!(
+            apply_retention_policy(&db_connection, &dry_run_policy, &now)
+                .await
+                .unwrap(),
+            expected_report
+        );
+        assert_eq!(get_all_bookmarks(&db_connection).await.unwrap().len(), 3);
+
+        let policy = RetentionPolicy::new(Some(days(30)), Some(days(7)), None, false);
+        assert_eq!(
+            apply_retention_policy(&db_connection, &policy, &now)
+                .await
+                .unwrap(),
+            RetentionReport {
+                dry_run: false,
+                ..expected_report
+            }
+        );
+        let remaining_bookmarks = get_all_bookmarks(&db_connection).await.unwrap();
+        assert_eq!(
+            remaining_bookmarks
+                .iter()
+                .map(PersistedBookmarkedMessage::pk)
+                .collect::<Vec<_>>(),
+            bookmarks[1..]
+                .iter()
+                .map(PersistedBookmarkedMessage::pk)
+                .collect::<Vec<_>>()
+        );
+        assert!(remaining_bookmarks.iter().all(|bookmark| {
+            bookmark.is_content_omitted() && bookmark.message().content.is_empty()
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_bookmarks_starred_after_planning_are_not_deleted() {
+        let db_connection = get_test_db_connection().await;
+        let now = chrono::Utc::now();
+        let bookmark = insert_bookmark(&db_connection, get_bookmark_created_at(now - days(40), 1))
+            .await
+            .unwrap();
+        let policy = RetentionPolicy::new(Some(days(30)), None, None, false);
+        let plan = get_bookmark_retention_plan(
+            get_bookmark_retention_infos(&db_connection).await.unwrap(),
+            &HashMap::new(),
+            &policy,
+            &now,
+        );
+        assert_eq!(get_pks(&plan.expired), vec![bookmark.pk()]);
+
+        toggle_bookmark_star(&db_connection, bookmark.bookmark_id(), USER_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            delete_expired_bookmarks_by_pk(&db_connection, get_pks(&plan.expired))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(get_all_bookmarks(&db_connection).await.unwrap().len(), 1);
+    }
+}
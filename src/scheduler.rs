@@ -0,0 +1,583 @@
+use std::{cmp::Reverse, collections::BinaryHeap, collections::HashMap, sync::Arc};
+
+use poise::serenity_prelude as serenity;
+use tokio::{
+    sync::{mpsc, Mutex},
+    task::JoinHandle,
+};
+use tokio_rusqlite::Connection;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+use crate::{
+    components::{
+        bookmark::bookmark_review_message::get_bookmark_review_message,
+        interaction_custom_id::DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+        reminder::reminder_message::get_reminder_message, DELETE_MESSAGE_EMOJI,
+    },
+    database::{
+        reminder::{delete_reminder_by_id, get_reminder_by_id, update_reminder_remind_at},
+        reminder_delivery::mark_reminder_delivered,
+        user_settings::get_user_settings,
+    },
+    logging::get_loggable_message_content,
+    metrics::Metrics,
+    models::{
+        bookmark_review::ReviewStep, reminder::PersistedReminder, user_settings::UserSettings,
+    },
+    privacy::with_live_reminder_message,
+    spaced_repetition::{get_bookmark_to_review, schedule_review_after_delivery},
+    Error,
+};
+
+/// A fixed pool of tasks that deliver due reminders concurrently.
+///
+/// Each user is always assigned to the same worker, so one user's reminders are delivered in the order they came due
+/// while different users' reminders don't have to wait on each other.
+/// Discord's per-route rate limits are respected by [`serenity::Http`]'s ratelimiter, which all workers share.
+pub struct DeliveryWorkers {
+    senders: Vec<mpsc::UnboundedSender<PersistedReminder>>,
+    handles: Vec<JoinHandle<()>>,
+    http: Arc<serenity::Http>,
+    db_connection: Arc<Mutex<Connection>>,
+    reschedule_tx: mpsc::Sender<PersistedReminder>,
+    metrics: Metrics,
+    shutdown: CancellationToken,
+}
+
+impl DeliveryWorkers {
+    /// `reschedule_tx` is used to hand reminders back to the scheduler when their delivery is deferred (e.g. by quiet hours).
+    ///
+    /// Once `shutdown` is cancelled, each worker finishes the delivery it's in the middle of and stops.
+    pub fn spawn(
+        worker_count: usize,
+        http: Arc<serenity::Http>,
+        db_connection: Arc<Mutex<Connection>>,
+        reschedule_tx: mpsc::Sender<PersistedReminder>,
+        metrics: Metrics,
+        shutdown: CancellationToken,
+    ) -> Self {
+        let mut delivery_workers = Self {
+            senders: Vec::with_capacity(worker_count),
+            handles: Vec::with_capacity(worker_count),
+            http,
+            db_connection,
+            reschedule_tx,
+            metrics,
+            shutdown,
+        };
+        for _ in 0..worker_count {
+            let (sender, handle) = delivery_workers.spawn_worker();
+            delivery_workers.senders.push(sender);
+            delivery_workers.handles.push(handle);
+        }
+
+        delivery_workers
+    }
+
+    fn spawn_worker(&self) -> (mpsc::UnboundedSender<PersistedReminder>, JoinHandle<()>) {
+        // Unbounded so the scheduler never blocks on a busy worker (which could in turn be blocked on rescheduling)
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(run_delivery_worker(
+            rx,
+            self.http.clone(),
+            self.db_connection.clone(),
+            self.reschedule_tx.clone(),
+            self.metrics.clone(),
+            self.shutdown.clone(),
+        ));
+        (tx, handle)
+    }
+
+    /// Waits for every worker to stop, which they only do once shutdown has been requested
+    async fn join(self) {
+        drop(self.senders);
+        for handle in self.handles {
+            if let Err(err) = handle.await {
+                tracing::error!(error = %err, "Delivery worker panicked");
+            }
+        }
+    }
+
+    /// A worker only stops early if it panicked, in which case it's replaced so the rest of its users' reminders
+    /// still get delivered. Reminders that can't be handed to a worker stay in the database for the next start-up.
+    fn dispatch(&mut self, reminder: PersistedReminder) {
+        let worker_index = get_worker_index(reminder.user_id(), self.senders.len());
+        let Err(mpsc::error::SendError(reminder)) = self.senders[worker_index].send(reminder)
+        else {
+            self.metrics.record_reminder_queued_for_delivery();
+            return;
+        };
+
+        if self.shutdown.is_cancelled() {
+            tracing::info!(
+                reminder_pk = reminder.pk(),
+                "Shutting down, leaving reminder for the next start-up"
+            );
+            return;
+        }
+
+        tracing::error!(
+            worker_index,
+            "Delivery worker stopped unexpectedly, restarting it"
+        );
+        let (sender, handle) = self.spawn_worker();
+        self.senders[worker_index] = sender;
+        self.handles[worker_index] = handle;
+        match self.senders[worker_index].send(reminder) {
+            Ok(()) => self.metrics.record_reminder_queued_for_delivery(),
+            Err(mpsc::error::SendError(reminder)) => tracing::error!(
+                reminder_pk = reminder.pk(),
+                "Failed to hand reminder to the restarted delivery worker, leaving it for the next start-up"
+            ),
+        }
+    }
+}
+
+fn get_worker_index(user_id: u64, worker_count: usize) -> usize {
+    (user_id % worker_count as u64) as usize
+}
+
+/// Waits for each queued reminder to come due and hands it off to the [`DeliveryWorkers`].
+///
+/// Receiving a user ID on `cancel_rx` drops all of that user's queued reminders.
+/// `include_message_content` is [`crate::config::LoggingConfig::include_message_content`], for logging queued reminders.
+///
+/// Returns once `shutdown` is cancelled and the [`DeliveryWorkers`] have finished their current deliveries.
+/// Reminders still queued at that point are left in the database for the next start-up to pick up.
+pub async fn send_reminders(
+    mut reminders: BinaryHeap<Reverse<PersistedReminder>>,
+    mut rx: mpsc::Receiver<PersistedReminder>,
+    mut cancel_rx: mpsc::Receiver<u64>,
+    mut delivery_workers: DeliveryWorkers,
+    include_message_content: bool,
+    shutdown: CancellationToken,
+) -> Result<(), Error> {
+    loop {
+        tracing::debug!(
+            queued_reminder_count = reminders.len(),
+            "Waiting for the next reminder"
+        );
+        delivery_workers
+            .metrics
+            .set_scheduler_heap_size(reminders.len());
+        let next_reminder = reminders.pop();
+
+        let sleep_time = next_reminder
+            .as_ref()
+            .map(|r| &r.0)
+            .map(PersistedReminder::get_sleep_time_until_reminder_should_trigger)
+            .unwrap_or(std::time::Duration::from_secs(u64::MAX));
+
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                if let Some(next_reminder) = next_reminder {
+                    reminders.push(next_reminder);
+                }
+                break;
+            }
+            _ = tokio::time::sleep(sleep_time) => {
+                if let Some(Reverse(reminder)) = next_reminder {
+                    delivery_workers.dispatch(reminder);
+                }
+            }
+            Some(reminder) = rx.recv() => {
+                tracing::debug!(
+                    reminder_pk = reminder.pk(),
+                    user_id = reminder.user_id(),
+                    remind_at = %reminder.remind_at(),
+                    message_content = get_loggable_message_content(
+                        &reminder.message().content,
+                        include_message_content,
+                    ),
+                    "Queued reminder"
+                );
+                if let Some(next_reminder) = next_reminder {
+                    reminders.push(next_reminder);
+                }
+                reminders.push(Reverse(reminder));
+            }
+            Some(user_id) = cancel_rx.recv() => {
+                tracing::info!(user_id, "Cancelling all queued reminders for user");
+                if let Some(next_reminder) = next_reminder {
+                    reminders.push(next_reminder);
+                }
+                reminders.retain(|reminder| reminder.0.user_id() != user_id);
+            }
+        }
+    }
+
+    // Anything still in the channel was already saved to the database, so it only needs to be counted
+    rx.close();
+    let mut unscheduled_reminder_count = 0;
+    while rx.try_recv().is_ok() {
+        unscheduled_reminder_count += 1;
+    }
+    tracing::info!(
+        queued_reminder_count = reminders.len(),
+        unscheduled_reminder_count,
+        "Stopped scheduling reminders, waiting for in-flight deliveries"
+    );
+
+    delivery_workers.join().await;
+    tracing::info!("Delivery workers stopped");
+
+    Ok(())
+}
+
+async fn run_delivery_worker(
+    mut rx: mpsc::UnboundedReceiver<PersistedReminder>,
+    http: Arc<serenity::Http>,
+    db_connection: Arc<Mutex<Connection>>,
+    reschedule_tx: mpsc::Sender<PersistedReminder>,
+    metrics: Metrics,
+    shutdown: CancellationToken,
+) {
+    // Creating a DM channel is its own rate-limited request, and every user is always handled by the same worker,
+    // so each worker remembers the DM channels of the users it has delivered to.
+    let mut dm_channels = HashMap::<serenity::UserId, serenity::ChannelId>::new();
+
+    // Only checked between deliveries, so a delivery is never interrupted between sending the DM and recording it
+    while let Some(reminder) = tokio::select! {
+        biased;
+        _ = shutdown.cancelled() => None,
+        reminder = rx.recv() => reminder,
+    } {
+        metrics.record_reminder_taken_for_delivery();
+        let span = tracing::info_span!(
+            "reminder_delivery",
+            reminder_pk = reminder.pk(),
+            user_id = reminder.user_id(),
+        );
+        if let Err(err) = process_due_reminder(
+            reminder,
+            &http,
+            &db_connection,
+            &reschedule_tx,
+            &metrics,
+            &mut dm_channels,
+        )
+        .instrument(span.clone())
+        .await
+        {
+            metrics.record_delivery_failure(&err);
+            span.in_scope(|| tracing::error!(error = %err, "Failed to deliver reminder"));
+        }
+    }
+}
+
+async fn process_due_reminder(
+    reminder: PersistedReminder,
+    http: &serenity::Http,
+    db_connection: &Mutex<Connection>,
+    reschedule_tx: &mpsc::Sender<PersistedReminder>,
+    metrics: &Metrics,
+    dm_channels: &mut HashMap<serenity::UserId, serenity::ChannelId>,
+) -> Result<(), Error> {
+    // Re-read the reminder in case it was changed (e.g. marked as urgent) after it was queued
+    let Some(reminder) = get_reminder_by_id(db_connection, reminder.pk()).await? else {
+        tracing::info!("Reminder no longer exists, skipping it");
+        return Ok(());
+    };
+
+    if !reminder.is_urgent() {
+        let settings = get_user_settings(db_connection, reminder.user_id()).await?;
+
+        match get_delivery_hold(&reminder, settings.as_ref(), &chrono::Utc::now()) {
+            Some(DeliveryHold::Digest) => {
+                tracing::info!("Leaving reminder to be delivered in the user's next digest");
+                return Ok(());
+            }
+            Some(DeliveryHold::QuietHours(end_of_quiet_hours)) => {
+                tracing::info!(%end_of_quiet_hours, "Deferring reminder until the end of quiet hours");
+                update_reminder_remind_at(db_connection, reminder.pk(), end_of_quiet_hours).await?;
+                reschedule_tx
+                    .send(reminder.rescheduled_to(end_of_quiet_hours))
+                    .await?;
+                return Ok(());
+            }
+            None => {}
+        }
+    }
+
+    let bookmark_to_review = match reminder.review_bookmark_id() {
+        Some(bookmark_id) => {
+            let bookmark =
+                get_bookmark_to_review(db_connection, bookmark_id, reminder.user_id()).await?;
+            if bookmark.is_none() {
+                tracing::info!(%bookmark_id, "Bookmark is no longer being reviewed, dropping its review");
+                delete_reminder_by_id(db_connection, reminder.pk()).await?;
+                return Ok(());
+            }
+            bookmark
+        }
+        None => None,
+    };
+
+    let reminder = with_live_reminder_message(http, reminder).await;
+
+    let channel_name = reminder
+        .message()
+        .channel_id
+        .name(http)
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!(
+                channel_id = %reminder.message().channel_id,
+                error = %err,
+                "Failed to get channel name"
+            );
+            "the past!".to_string()
+        });
+
+    let message = match bookmark_to_review {
+        Some(bookmark) => {
+            let review_step = bookmark.review_step().unwrap_or(ReviewStep::FIRST);
+            // The review's message was fetched again above if its content wasn't stored
+            let bookmark = bookmark.with_message(reminder.message().clone());
+            get_bookmark_review_message(&bookmark, &channel_name, review_step, DELETE_MESSAGE_EMOJI)
+        }
+        None => get_reminder_message(
+            &reminder,
+            &channel_name,
+            DELETE_MESSAGE_INTERACTION_CUSTOM_ID,
+            DELETE_MESSAGE_EMOJI,
+        ),
+    };
+
+    let user_id = serenity::UserId::new(reminder.user_id());
+    let dm_channel_id = match dm_channels.get(&user_id) {
+        Some(dm_channel_id) => *dm_channel_id,
+        None => {
+            let dm_channel_id = user_id.create_dm_channel(http).await?.id;
+            dm_channels.insert(user_id, dm_channel_id);
+            dm_channel_id
+        }
+    };
+
+    if let Err(err) = dm_channel_id.send_message(http, message).await {
+        // The cached channel could be what's wrong, so it's looked up again for the next delivery
+        dm_channels.remove(&user_id);
+        return Err(err.into());
+    }
+
+    let delivered_at = chrono::Utc::now();
+    mark_reminder_delivered(db_connection, reminder.pk(), delivered_at).await?;
+    metrics.record_reminder_delivered(&reminder, &delivered_at);
+
+    if let Some(bookmark_id) = reminder.review_bookmark_id() {
+        schedule_review_after_delivery(db_connection, reschedule_tx, bookmark_id).await?;
+    }
+
+    tracing::info!(
+        delay_ms = delivered_at
+            .signed_duration_since(*reminder.remind_at())
+            .num_milliseconds(),
+        "Delivered reminder"
+    );
+
+    Ok(())
+}
+
+/// Why a due, non-urgent reminder isn't delivered right away
+#[derive(Debug, PartialEq, Eq)]
+enum DeliveryHold {
+    /// The reminder is left for the user's digest, which hasn't been sent yet today
+    Digest,
+    /// The reminder is deferred until the end of the user's quiet hours
+    QuietHours(chrono::DateTime<chrono::Utc>),
+}
+
+fn get_delivery_hold(
+    reminder: &PersistedReminder,
+    settings: Option<&UserSettings>,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> Option<DeliveryHold> {
+    // Countdown alerts lose their meaning if they arrive late, so they're never held back
+    if reminder.countdown().is_some() {
+        return None;
+    }
+    let settings = settings?;
+
+    // Bookmark reviews need their buttons, so they're always delivered on their own
+    let is_left_for_digest = reminder.review_bookmark_id().is_none()
+        && settings
+            .get_pending_digest_time(now)
+            .is_some_and(|digest_time| *reminder.remind_at() < digest_time);
+    if is_left_for_digest {
+        return Some(DeliveryHold::Digest);
+    }
+
+    settings
+        .get_end_of_quiet_hours_containing(now)
+        .map(DeliveryHold::QuietHours)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        reminder::{CountdownLink, Reminder},
+        user_settings::QuietHours,
+    };
+
+    #[test]
+    fn test_get_worker_index_is_stable_per_user() {
+        let worker_count = 4;
+        for user_id in [0, 1, 123456789, u64::MAX] {
+            let worker_index = get_worker_index(user_id, worker_count);
+            assert!(worker_index < worker_count);
+            assert_eq!(worker_index, get_worker_index(user_id, worker_count));
+        }
+        assert_eq!(get_worker_index(123456789, 1), 0);
+    }
+
+    fn utc(day: u32, hour: u32, minute: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2025, 1, day, hour, minute, 0).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> chrono::NaiveTime {
+        chrono::NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn reminder_at(remind_at: chrono::DateTime<chrono::Utc>) -> PersistedReminder {
+        PersistedReminder::from_reminder(
+            Reminder::new(1, serenity::Message::default(), remind_at),
+            1,
+        )
+    }
+
+    #[test]
+    fn test_only_reminders_due_before_the_digest_are_left_for_it() {
+        let mut settings = UserSettings::new(1);
+        settings.set_digest_time(Some(time(8, 0)));
+
+        assert_eq!(
+            get_delivery_hold(&reminder_at(utc(1, 7, 0)), Some(&settings), &utc(1, 7, 0)),
+            Some(DeliveryHold::Digest)
+        );
+        // Today's digest is due but hasn't gone out yet, so it picks up the earlier reminder
+        assert_eq!(
+            get_delivery_hold(&reminder_at(utc(1, 7, 0)), Some(&settings), &utc(1, 15, 0)),
+            Some(DeliveryHold::Digest)
+        );
+        assert_eq!(
+            get_delivery_hold(&reminder_at(utc(1, 15, 0)), Some(&settings), &utc(1, 15, 0)),
+            None
+        );
+
+        let review = PersistedReminder::from_reminder(
+            Reminder::new_bookmark_review(
+                1,
+                serenity::Message::default(),
+                utc(1, 7, 0),
+                uuid::Uuid::nil(),
+            ),
+            2,
+        );
+        assert_eq!(
+            get_delivery_hold(&review, Some(&settings), &utc(1, 7, 0)),
+            None
+        );
+        assert_eq!(
+            get_delivery_hold(&reminder_at(utc(1, 7, 0)), None, &utc(1, 7, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_countdown_alerts_are_never_held() {
+        let mut settings = UserSettings::new(1);
+        settings.set_digest_time(Some(time(8, 0)));
+        settings.set_quiet_hours(Some(QuietHours::new(time(6, 0), time(7, 30))));
+
+        let alert = PersistedReminder::from_reminder(
+            Reminder::new_countdown_alert(
+                1,
+                serenity::Message::default(),
+                utc(1, 7, 0),
+                CountdownLink::new(uuid::Uuid::nil(), utc(1, 8, 0)),
+            ),
+            1,
+        );
+        assert_eq!(
+            get_delivery_hold(&alert, Some(&settings), &utc(1, 7, 0)),
+            None
+        );
+        assert_eq!(
+            get_delivery_hold(&reminder_at(utc(1, 7, 0)), Some(&settings), &utc(1, 7, 0)),
+            Some(DeliveryHold::Digest)
+        );
+    }
+
+    #[test]
+    fn test_reminders_are_deferred_until_the_end_of_quiet_hours() {
+        let mut settings = UserSettings::new(1);
+        settings.set_quiet_hours(Some(QuietHours::new(time(22, 0), time(7, 0))));
+
+        assert_eq!(
+            get_delivery_hold(&reminder_at(utc(1, 23, 0)), Some(&settings), &utc(1, 23, 0)),
+            Some(DeliveryHold::QuietHours(utc(2, 7, 0)))
+        );
+        assert_eq!(
+            get_delivery_hold(&reminder_at(utc(1, 12, 0)), Some(&settings), &utc(1, 12, 0)),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_reminders_stops_on_shutdown() {
+        let db_connection = Arc::new(Mutex::new(Connection::open_in_memory().await.unwrap()));
+        let (tx, rx) = mpsc::channel(1);
+        let (_cancel_tx, cancel_rx) = mpsc::channel(1);
+        let shutdown = CancellationToken::new();
+        let delivery_workers = DeliveryWorkers::spawn(
+            2,
+            Arc::new(serenity::Http::new("")),
+            db_connection,
+            tx,
+            Metrics::new(),
+            shutdown.clone(),
+        );
+
+        shutdown.cancel();
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            send_reminders(
+                BinaryHeap::new(),
+                rx,
+                cancel_rx,
+                delivery_workers,
+                false,
+                shutdown,
+            ),
+        )
+        .await
+        .expect("The scheduler should stop once shutdown is requested");
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_stopped_delivery_worker_is_restarted() {
+        let db_connection = Arc::new(Mutex::new(Connection::open_in_memory().await.unwrap()));
+        let (tx, _rx) = mpsc::channel(1);
+        let mut delivery_workers = DeliveryWorkers::spawn(
+            1,
+            Arc::new(serenity::Http::new("")),
+            db_connection,
+            tx,
+            Metrics::new(),
+            CancellationToken::new(),
+        );
+        delivery_workers.handles[0].abort();
+        while !delivery_workers.handles[0].is_finished() {
+            tokio::task::yield_now().await;
+        }
+
+        delivery_workers.dispatch(reminder_at(chrono::Utc::now()));
+
+        assert!(!delivery_workers.senders[0].is_closed());
+        assert!(!delivery_workers.handles[0].is_finished());
+    }
+}
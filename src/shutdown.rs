@@ -0,0 +1,158 @@
+use std::{sync::Arc, time::Duration};
+
+use poise::serenity_prelude as serenity;
+use tokio::{sync::Mutex, task::JoinHandle};
+use tokio_rusqlite::Connection;
+use tokio_util::sync::CancellationToken;
+
+use crate::{database::close_database, Error};
+
+/// Waits for Ctrl-C (or SIGTERM on Unix), then cancels `shutdown` and disconnects every shard so no new
+/// interactions come in. [`serenity::Client::start`] returns once the shards are gone.
+pub async fn shut_down_on_signal(
+    shutdown: CancellationToken,
+    shard_manager: Arc<serenity::ShardManager>,
+) {
+    tokio::select! {
+        _ = wait_for_shutdown_signal() => tracing::info!("Received shutdown signal"),
+        // Shutting down for another reason (e.g. failing to start), which is already being taken care of
+        _ = shutdown.cancelled() => return,
+    }
+
+    shutdown.cancel();
+    shard_manager.shutdown_all().await;
+}
+
+async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %err, "Failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Once the client has stopped: lets the scheduler finish its in-flight deliveries and the other `tasks` (which should
+/// stop once `shutdown` is cancelled) finish up, then closes the database.
+/// Gives up on whatever is left after `timeout`, since the process is about to exit either way.
+pub async fn finish_shutdown(
+    shutdown: CancellationToken,
+    scheduler: JoinHandle<Result<(), Error>>,
+    tasks: Vec<JoinHandle<()>>,
+    db_connection: &Mutex<Connection>,
+    timeout: Duration,
+) {
+    shutdown.cancel();
+
+    let drain = async {
+        match scheduler.await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => tracing::error!(error = %err, "Scheduler stopped with an error"),
+            Err(err) => tracing::error!(error = %err, "Scheduler panicked"),
+        }
+        for task in tasks {
+            if let Err(err) = task.await {
+                tracing::error!(error = %err, "Background task panicked");
+            }
+        }
+
+        if let Err(err) = close_database(db_connection).await {
+            tracing::error!(error = %err, "Failed to close database");
+        }
+    };
+
+    match tokio::time::timeout(timeout, drain).await {
+        Ok(()) => tracing::info!("Shut down cleanly"),
+        Err(_) => tracing::warn!(
+            timeout_seconds = timeout.as_secs(),
+            "Timed out waiting for in-flight work to finish, exiting anyway"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_finish_shutdown_waits_for_tasks_and_closes_database() {
+        let db_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+        let shutdown = CancellationToken::new();
+        let scheduler = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move {
+                shutdown.cancelled().await;
+                Ok(())
+            }
+        });
+        let task_finished = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let task = tokio::spawn({
+            let shutdown = shutdown.clone();
+            let task_finished = task_finished.clone();
+            async move {
+                shutdown.cancelled().await;
+                task_finished.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        });
+
+        finish_shutdown(
+            shutdown,
+            scheduler,
+            vec![task],
+            &db_connection,
+            Duration::from_secs(5),
+        )
+        .await;
+
+        assert!(task_finished.load(std::sync::atomic::Ordering::SeqCst));
+
+        let result = db_connection
+            .lock()
+            .await
+            .call(|conn| Ok(conn.execute_batch("SELECT 1")?))
+            .await;
+        assert!(matches!(
+            result,
+            Err(tokio_rusqlite::Error::ConnectionClosed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_finish_shutdown_gives_up_after_timeout() {
+        let db_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+        let scheduler = tokio::spawn(std::future::pending());
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            finish_shutdown(
+                CancellationToken::new(),
+                scheduler,
+                Vec::new(),
+                &db_connection,
+                Duration::from_millis(10),
+            ),
+        )
+        .await
+        .expect("Shutdown should give up after its own timeout");
+    }
+}
@@ -0,0 +1,389 @@
+//! Resurfacing bookmarks at growing intervals (1 day, 3 days, 7 days, 21 days and so on), for users who've turned it on.
+//!
+//! Each review is a reminder linked to its bookmark, so it's delivered by the [`crate::scheduler`] like any other,
+//! while the bookmark remembers where it is in the schedule. Once a review is delivered, the next one is scheduled
+//! at the same interval, which the review's "Got it" and "Show again sooner" buttons then lengthen or shorten.
+
+use tokio::sync::{mpsc, Mutex};
+use tokio_rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        bookmark::{get_bookmark_by_id, get_bookmarks_without_pending_review_for_user},
+        reminder::{delete_bookmark_reviews_for_user, replace_bookmark_reviews},
+        user_settings::get_user_settings,
+    },
+    models::{
+        bookmark::PersistedBookmarkedMessage,
+        bookmark_review::{ReviewAnswer, ReviewStep},
+        reminder::{PersistedReminder, Reminder},
+    },
+    Error,
+};
+
+/// How far apart the first reviews of bookmarks that existed before spaced repetition was turned on are,
+/// so turning it on with lots of bookmarks doesn't send them all at once
+const EXISTING_BOOKMARK_REVIEW_SPACING_HOURS: i64 = 4;
+
+/// Schedules the first review of a newly created bookmark if its owner has spaced repetition turned on
+pub async fn start_reviews_if_enabled(
+    db_connection: &Mutex<Connection>,
+    tx: &mpsc::Sender<PersistedReminder>,
+    bookmark: &PersistedBookmarkedMessage,
+) -> Result<(), Error> {
+    if is_spaced_repetition_enabled(db_connection, bookmark.user_id()).await? {
+        schedule_review(
+            db_connection,
+            tx,
+            bookmark,
+            ReviewStep::FIRST,
+            chrono::Utc::now(),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Schedules reviews for the user's bookmarks that don't have one pending, for when they turn spaced repetition on.
+/// Bookmarks that were reviewed before the setting was last turned off pick up where they left off.
+/// Their first reviews are spread out by `EXISTING_BOOKMARK_REVIEW_SPACING_HOURS`, oldest bookmark first.
+/// Returns how many reviews were scheduled
+pub async fn start_reviews_for_existing_bookmarks(
+    db_connection: &Mutex<Connection>,
+    tx: &mpsc::Sender<PersistedReminder>,
+    user_id: u64,
+) -> Result<usize, Error> {
+    let bookmarks = get_bookmarks_without_pending_review_for_user(db_connection, user_id).await?;
+    let now = chrono::Utc::now();
+    let reviews = bookmarks
+        .iter()
+        .zip(0..)
+        .map(|(bookmark, index)| {
+            let review_step = bookmark.review_step().unwrap_or(ReviewStep::FIRST);
+            let remind_at = now
+                + review_step.interval()
+                + chrono::Duration::hours(EXISTING_BOOKMARK_REVIEW_SPACING_HOURS * index);
+            (review_step, get_review(bookmark, remind_at))
+        })
+        .collect();
+
+    let reviews = replace_bookmark_reviews(db_connection, reviews).await?;
+    let scheduled_count = reviews.len();
+    for review in reviews {
+        tx.send(review).await?;
+    }
+
+    Ok(scheduled_count)
+}
+
+/// Drops the user's pending reviews when they turn spaced repetition off, returning how many were dropped.
+/// Their bookmarks keep their place in the schedule for if it's turned back on
+pub async fn stop_reviews(db_connection: &Mutex<Connection>, user_id: u64) -> Result<usize, Error> {
+    Ok(delete_bookmark_reviews_for_user(db_connection, user_id).await?)
+}
+
+/// Moves the bookmark along its schedule according to `answer`, returning when it'll be reviewed next
+pub async fn answer_review(
+    db_connection: &Mutex<Connection>,
+    tx: &mpsc::Sender<PersistedReminder>,
+    bookmark: &PersistedBookmarkedMessage,
+    answer: ReviewAnswer,
+) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+    let review_step = bookmark
+        .review_step()
+        .unwrap_or(ReviewStep::FIRST)
+        .after(answer);
+
+    schedule_review(db_connection, tx, bookmark, review_step, chrono::Utc::now()).await
+}
+
+/// The bookmark a due review is for, or [`None`] if the review should be dropped
+/// (because the bookmark was deleted or its owner turned spaced repetition off)
+pub async fn get_bookmark_to_review(
+    db_connection: &Mutex<Connection>,
+    bookmark_id: Uuid,
+    user_id: u64,
+) -> Result<Option<PersistedBookmarkedMessage>, Error> {
+    if !is_spaced_repetition_enabled(db_connection, user_id).await? {
+        return Ok(None);
+    }
+
+    Ok(get_bookmark_by_id(db_connection, bookmark_id)
+        .await?
+        .filter(|bookmark| bookmark.user_id() == user_id))
+}
+
+/// Keeps resurfacing the bookmark at its current interval after a review was delivered, in case neither button is pressed
+pub async fn schedule_review_after_delivery(
+    db_connection: &Mutex<Connection>,
+    tx: &mpsc::Sender<PersistedReminder>,
+    bookmark_id: Uuid,
+) -> Result<(), Error> {
+    let Some(bookmark) = get_bookmark_by_id(db_connection, bookmark_id).await? else {
+        return Ok(());
+    };
+    let review_step = bookmark.review_step().unwrap_or(ReviewStep::FIRST);
+
+    schedule_review(
+        db_connection,
+        tx,
+        &bookmark,
+        review_step,
+        chrono::Utc::now(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Records `review_step` on the bookmark and replaces its pending review (if any) with one due after that step's
+/// interval, so answering a review more than once can't leave the bookmark with several reviews queued
+async fn schedule_review(
+    db_connection: &Mutex<Connection>,
+    tx: &mpsc::Sender<PersistedReminder>,
+    bookmark: &PersistedBookmarkedMessage,
+    review_step: ReviewStep,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+    let remind_at = now + review_step.interval();
+    let review = get_review(bookmark, remind_at);
+
+    for review in replace_bookmark_reviews(db_connection, vec![(review_step, review)]).await? {
+        tracing::info!(
+            bookmark_id = %bookmark.bookmark_id(),
+            reminder_pk = review.pk(),
+            %remind_at,
+            "Scheduled bookmark review"
+        );
+        tx.send(review).await?;
+    }
+
+    Ok(remind_at)
+}
+
+fn get_review(
+    bookmark: &PersistedBookmarkedMessage,
+    remind_at: chrono::DateTime<chrono::Utc>,
+) -> Reminder {
+    let review = Reminder::new_bookmark_review(
+        bookmark.user_id(),
+        bookmark.message().clone(),
+        remind_at,
+        bookmark.bookmark_id(),
+    );
+    if bookmark.is_content_omitted() {
+        review.without_message_content()
+    } else {
+        review
+    }
+}
+
+async fn is_spaced_repetition_enabled(
+    db_connection: &Mutex<Connection>,
+    user_id: u64,
+) -> Result<bool, Error> {
+    Ok(get_user_settings(db_connection, user_id)
+        .await?
+        .is_some_and(|settings| settings.spaced_repetition()))
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude as serenity;
+
+    use super::*;
+    use crate::{
+        database::{
+            bookmark::insert_bookmark,
+            reminder::{get_all_reminders, get_reminders_for_user, insert_reminder},
+            user_settings::upsert_user_settings,
+        },
+        models::{bookmark::BookmarkedMessage, user_settings::UserSettings},
+        test_fixtures::get_test_db_connection,
+    };
+
+    async fn insert_test_bookmark(
+        db_connection: &Mutex<Connection>,
+        user_id: u64,
+        message_id: u64,
+    ) -> PersistedBookmarkedMessage {
+        let mut message = serenity::Message::default();
+        message.id = message_id.into();
+        insert_bookmark(
+            db_connection,
+            BookmarkedMessage::new(
+                // Ordered by message ID, since bookmarks are listed oldest first
+                Uuid::new_v7(uuid::Timestamp::from_unix(
+                    uuid::NoContext,
+                    1_700_000_000 + message_id,
+                    0,
+                )),
+                user_id,
+                message,
+            ),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reviews_only_start_when_enabled() {
+        let db_connection = get_test_db_connection().await;
+        let (tx, mut rx) = mpsc::channel(10);
+        let bookmark = insert_test_bookmark(&db_connection, 1, 1).await;
+
+        start_reviews_if_enabled(&db_connection, &tx, &bookmark)
+            .await
+            .unwrap();
+        assert!(rx.try_recv().is_err());
+
+        let mut settings = UserSettings::new(1);
+        settings.set_spaced_repetition(true);
+        upsert_user_settings(&db_connection, settings)
+            .await
+            .unwrap();
+        start_reviews_if_enabled(&db_connection, &tx, &bookmark)
+            .await
+            .unwrap();
+
+        let review = rx.try_recv().unwrap();
+        assert_eq!(review.review_bookmark_id(), Some(bookmark.bookmark_id()));
+        let hours_until_review = review
+            .remind_at()
+            .signed_duration_since(chrono::Utc::now())
+            .num_hours();
+        assert!((23..=24).contains(&hours_until_review));
+        assert_eq!(
+            get_bookmark_to_review(&db_connection, bookmark.bookmark_id(), 1)
+                .await
+                .unwrap()
+                .and_then(|bookmark| bookmark.review_step()),
+            Some(ReviewStep::FIRST)
+        );
+        assert!(
+            get_bookmark_to_review(&db_connection, bookmark.bookmark_id(), 2)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_answering_a_review_replaces_the_pending_one() {
+        let db_connection = get_test_db_connection().await;
+        let (tx, _rx) = mpsc::channel(10);
+        let bookmark = insert_test_bookmark(&db_connection, 1, 2).await;
+
+        let next_review_at = answer_review(&db_connection, &tx, &bookmark, ReviewAnswer::GotIt)
+            .await
+            .unwrap();
+        let bookmark = get_bookmark_by_id(&db_connection, bookmark.bookmark_id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bookmark.review_step(), Some(ReviewStep::from_index(1)));
+        assert_eq!(
+            next_review_at
+                .signed_duration_since(chrono::Utc::now())
+                .num_hours(),
+            3 * 24 - 1
+        );
+
+        answer_review(&db_connection, &tx, &bookmark, ReviewAnswer::ShowSooner)
+            .await
+            .unwrap();
+        schedule_review_after_delivery(&db_connection, &tx, bookmark.bookmark_id())
+            .await
+            .unwrap();
+
+        let reviews = get_all_reminders(&db_connection).await.unwrap();
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(
+            get_bookmark_by_id(&db_connection, bookmark.bookmark_id())
+                .await
+                .unwrap()
+                .unwrap()
+                .review_step(),
+            Some(ReviewStep::FIRST)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_turning_reviews_on_covers_existing_bookmarks() {
+        let db_connection = get_test_db_connection().await;
+        let (tx, mut rx) = mpsc::channel(10);
+        let reviewed_bookmark = insert_test_bookmark(&db_connection, 1, 3).await;
+        answer_review(&db_connection, &tx, &reviewed_bookmark, ReviewAnswer::GotIt)
+            .await
+            .unwrap();
+        rx.try_recv().unwrap();
+        // Turning the setting off drops the pending review but leaves the bookmark's step behind
+        stop_reviews(&db_connection, 1).await.unwrap();
+        let new_bookmark = insert_test_bookmark(&db_connection, 1, 4).await;
+        let pending_bookmark = insert_test_bookmark(&db_connection, 1, 5).await;
+        schedule_review_after_delivery(&db_connection, &tx, pending_bookmark.bookmark_id())
+            .await
+            .unwrap();
+        rx.try_recv().unwrap();
+        insert_test_bookmark(&db_connection, 2, 6).await;
+
+        let scheduled_count = start_reviews_for_existing_bookmarks(&db_connection, &tx, 1)
+            .await
+            .unwrap();
+        assert_eq!(scheduled_count, 2);
+
+        let mut hours_until_review_by_bookmark = std::collections::HashMap::new();
+        while let Ok(review) = rx.try_recv() {
+            let hours_until_review = review
+                .remind_at()
+                .signed_duration_since(chrono::Utc::now())
+                .num_hours();
+            hours_until_review_by_bookmark
+                .insert(review.review_bookmark_id().unwrap(), hours_until_review);
+        }
+        assert_eq!(
+            hours_until_review_by_bookmark,
+            std::collections::HashMap::from([
+                (reviewed_bookmark.bookmark_id(), 3 * 24 - 1),
+                (
+                    new_bookmark.bookmark_id(),
+                    24 + EXISTING_BOOKMARK_REVIEW_SPACING_HOURS - 1
+                ),
+            ])
+        );
+        assert_eq!(get_all_reminders(&db_connection).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_reviews_are_kept_apart_from_reminders() {
+        let db_connection = get_test_db_connection().await;
+        let (tx, _rx) = mpsc::channel(10);
+        let bookmark = insert_test_bookmark(&db_connection, 1, 1).await;
+        answer_review(&db_connection, &tx, &bookmark, ReviewAnswer::GotIt)
+            .await
+            .unwrap();
+        let reminder = insert_reminder(
+            &db_connection,
+            Reminder::new(1, serenity::Message::default(), chrono::Utc::now()),
+        )
+        .await
+        .unwrap();
+
+        let listed_reminders = get_reminders_for_user(&db_connection, 1, 10).await.unwrap();
+        assert_eq!(listed_reminders.len(), 1);
+        assert_eq!(listed_reminders[0].pk(), reminder.pk());
+
+        assert_eq!(stop_reviews(&db_connection, 1).await.unwrap(), 1);
+        assert_eq!(get_all_reminders(&db_connection).await.unwrap().len(), 1);
+        assert_eq!(
+            get_bookmark_by_id(&db_connection, bookmark.bookmark_id())
+                .await
+                .unwrap()
+                .unwrap()
+                .review_step(),
+            Some(ReviewStep::from_index(1))
+        );
+    }
+}
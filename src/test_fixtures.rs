@@ -0,0 +1,31 @@
+//! Setup shared by the unit tests
+
+use poise::serenity_prelude as serenity;
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+use crate::{
+    database::create_all_tables_if_nonexistent,
+    models::bookmark::{BookmarkedMessage, PersistedBookmarkedMessage},
+};
+
+/// An in-memory database with every table created
+pub async fn get_test_db_connection() -> Mutex<Connection> {
+    let db_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+    create_all_tables_if_nonexistent(&db_connection)
+        .await
+        .unwrap();
+    db_connection
+}
+
+/// A bookmark of `message` by user 123456789, as if it had been stored with the given primary key
+pub fn get_test_bookmark(pk: i64, message: serenity::Message) -> PersistedBookmarkedMessage {
+    PersistedBookmarkedMessage::from_bookmarked_message(
+        BookmarkedMessage::new(
+            uuid::Uuid::new_v7(uuid::Timestamp::now(uuid::NoContext)),
+            123456789,
+            message,
+        ),
+        pk,
+    )
+}
@@ -0,0 +1,81 @@
+use discord_attendant::{
+    database::{
+        bookmark::{get_bookmark_by_id, insert_bookmark},
+        create_all_tables_if_nonexistent,
+    },
+    framework::{get_commands, get_embeddable_commands, get_framework_options, on_event},
+    models::bookmark::BookmarkedMessage,
+    AttendantState, Data, Error,
+};
+use poise::serenity_prelude as serenity;
+use tokio::sync::Mutex;
+use tokio_rusqlite::Connection;
+
+#[test]
+fn test_commands_can_be_run_by_another_framework() {
+    let (serenity_commands, commands) = get_commands();
+    assert_eq!(serenity_commands.len(), commands.len());
+
+    let options = get_framework_options(commands);
+    for name in ["bookmark", "get_bookmarks", "get_reminders", "remind"] {
+        assert!(
+            options.commands.iter().any(|command| command.name == name),
+            "Missing /{}",
+            name
+        );
+    }
+}
+
+/// A bot with its own data, running the commands alongside its own
+struct HostData {
+    attendant: Data,
+}
+
+impl AttendantState for HostData {
+    fn attendant(&self) -> &Data {
+        &self.attendant
+    }
+}
+
+#[test]
+fn test_commands_can_be_embedded_in_a_bot_with_its_own_data() {
+    let (serenity_commands, commands) = get_embeddable_commands::<HostData>();
+    assert_eq!(serenity_commands.len(), commands.len());
+    assert!(commands.iter().all(|command| command.name != "help"));
+
+    let options = poise::FrameworkOptions::<HostData, Error> {
+        commands,
+        event_handler: |ctx, event, framework, data| {
+            Box::pin(async move {
+                // The host's own event handling would go here
+                on_event(ctx, event, framework, data).await
+            })
+        },
+        ..Default::default()
+    };
+    assert!(options
+        .commands
+        .iter()
+        .any(|command| command.name == "bookmark"));
+}
+
+#[tokio::test]
+async fn test_bookmarks_can_be_stored_and_read_back() {
+    let db_connection = Mutex::new(Connection::open_in_memory().await.unwrap());
+    create_all_tables_if_nonexistent(&db_connection)
+        .await
+        .unwrap();
+
+    let mut message = serenity::Message::default();
+    message.content = "Hello".to_string();
+    let bookmark = BookmarkedMessage::new(uuid::Uuid::now_v7(), 123456789, message);
+    let bookmark_id = bookmark.bookmark_id();
+    insert_bookmark(&db_connection, bookmark).await.unwrap();
+
+    let bookmark = get_bookmark_by_id(&db_connection, bookmark_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(bookmark.user_id(), 123456789);
+    assert_eq!(bookmark.message().content, "Hello");
+}